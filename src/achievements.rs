@@ -0,0 +1,168 @@
+//! achievements, for the milestones along the way on a board that never
+//! ends. each one unlocks once, on any seed, and pops up as a toast when it
+//! does. they're kept in `achievements.txt`, one name a line. sandbox games
+//! don't count, cards can be made up there.
+
+use infiniteklondike::game::{Event, Game, Move};
+
+use crate::toast;
+
+/// where unlocked achievements are kept
+const ACHIEVEMENTS_PATH: &str = "achievements.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    /// 100 cards on the foundations in one game
+    Founded100,
+    /// 1000 cards on the foundations in one game
+    Founded1000,
+    /// 500 face down cards turned over in one game
+    Revealed500,
+    /// played on column 1000
+    Column1000,
+    /// played on column 10000
+    Column10000,
+    /// 10 columns next to each other emptied out
+    Cleared10,
+}
+
+impl Achievement {
+    const ALL: [Achievement; 6] = [
+        Achievement::Founded100,
+        Achievement::Founded1000,
+        Achievement::Revealed500,
+        Achievement::Column1000,
+        Achievement::Column10000,
+        Achievement::Cleared10,
+    ];
+    /// how many empty columns in a row `Cleared10` wants
+    const CLEARED: usize = 10;
+
+    /// the name it's kept under in the file
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::Founded100 => "founded-100",
+            Achievement::Founded1000 => "founded-1000",
+            Achievement::Revealed500 => "revealed-500",
+            Achievement::Column1000 => "column-1000",
+            Achievement::Column10000 => "column-10000",
+            Achievement::Cleared10 => "cleared-10",
+        }
+    }
+
+    /// what it's for, as the toast says it
+    fn description(self) -> &'static str {
+        match self {
+            Achievement::Founded100 => "100 cards on the foundations in one game",
+            Achievement::Founded1000 => "1000 cards on the foundations in one game",
+            Achievement::Revealed500 => "500 cards turned over in one game",
+            Achievement::Column1000 => "played on column 1000",
+            Achievement::Column10000 => "played on column 10000",
+            Achievement::Cleared10 => "10 columns in a row cleared",
+        }
+    }
+
+    /// whether `game` has earned it, with `cleared` the longest run of
+    /// empty columns the last move was part of
+    fn earned(self, game: &Game, cleared: usize) -> bool {
+        match self {
+            Achievement::Founded100 => game.founded() >= 100,
+            Achievement::Founded1000 => game.founded() >= 1000,
+            Achievement::Revealed500 => game.turned_over() >= 500,
+            Achievement::Column1000 => game.explored() >= 1000,
+            Achievement::Column10000 => game.explored() >= 10000,
+            Achievement::Cleared10 => cleared >= Self::CLEARED,
+        }
+    }
+}
+
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+}
+
+impl Achievements {
+    /// reads the ones unlocked so far. names that aren't known any more are
+    /// left out.
+    pub fn load() -> Self {
+        let text = std::fs::read_to_string(ACHIEVEMENTS_PATH).unwrap_or_default();
+        let unlocked = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| text.lines().any(|line| line.trim() == achievement.key()))
+            .collect();
+        Self { unlocked }
+    }
+
+    fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// unlocks whatever the move that made `events` earned on `game`
+    pub fn update(&mut self, game: &Game, events: &[Event]) {
+        if game.rules().sandbox {
+            return;
+        }
+        let cleared = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Moved(mv) => moved_from(*mv),
+                _ => None,
+            })
+            .map(|column| empty_run(game, column))
+            .max()
+            .unwrap_or(0);
+        for achievement in Achievement::ALL {
+            if !self.is_unlocked(achievement) && achievement.earned(game, cleared) {
+                self.unlock(achievement);
+            }
+        }
+    }
+
+    fn unlock(&mut self, achievement: Achievement) {
+        use std::io::Write;
+        self.unlocked.push(achievement);
+        // another tab might have got there first
+        if Self::load().is_unlocked(achievement) {
+            return;
+        }
+        toast::info(format!("achievement: {}", achievement.description()));
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ACHIEVEMENTS_PATH)
+            .and_then(|mut file| writeln!(file, "{}", achievement.key()));
+        if let Err(e) = written {
+            toast::error(format!("could not write to {}: {}", ACHIEVEMENTS_PATH, e));
+        }
+    }
+}
+
+/// the column `mv` took cards off, where it is after the move
+fn moved_from(mv: Move) -> Option<usize> {
+    match mv {
+        Move::Tableau { from, .. } | Move::Foundation { from, .. } => Some(from),
+        Move::Insert { from, at, .. } => Some(if from >= at { from + 1 } else { from }),
+        Move::Reshuffle { .. } | Move::Draw | Move::Waste { .. } | Move::WasteFoundation { .. } => {
+            None
+        }
+    }
+}
+
+/// how many empty columns in a row there are around `column`, up to as many
+/// as `Cleared10` needs
+fn empty_run(game: &Game, column: usize) -> usize {
+    let is_empty = |x: usize| {
+        game.tableau()
+            .get(x)
+            .is_some_and(|stack| stack.under == 0 && stack.visible().is_empty())
+    };
+    if !is_empty(column) {
+        return 0;
+    }
+    let before = (1..Achievement::CLEARED)
+        .take_while(|n| column.checked_sub(*n).is_some_and(is_empty))
+        .count();
+    let after = (1..Achievement::CLEARED)
+        .take_while(|n| is_empty(column + n))
+        .count();
+    1 + before + after
+}
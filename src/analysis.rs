@@ -0,0 +1,87 @@
+//! e asks whether the columns on screen are worth sticking with. the solver
+//! looks through them on a thread of its own, so the game keeps running,
+//! and the answer pops up once it's done.
+
+use std::ops::Range;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use infiniteklondike::game::Game;
+use infiniteklondike::solver;
+
+/// what the search found out about a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// this many more cards can go on foundations
+    Progress(usize),
+    /// nothing can, but there are reshuffles left to shake it up
+    Reshuffle,
+    /// nothing can, and nothing will change that
+    Dead,
+    /// the search gave up before finding anything or running out of moves
+    Unknown,
+}
+
+impl Verdict {
+    pub fn describe(self, window: &Range<usize>) -> String {
+        let columns = format!("columns {}-{}", window.start, window.end - 1);
+        match self {
+            Verdict::Progress(cards) => format!(
+                "{}: progress possible, {} more cards can go on foundations",
+                columns, cards
+            ),
+            Verdict::Reshuffle => {
+                format!("{}: stuck, but a reshuffle might get things going", columns)
+            }
+            Verdict::Dead => format!("{}: dead, nothing more can go on foundations", columns),
+            Verdict::Unknown => format!("{}: too many moves to look through, no idea", columns),
+        }
+    }
+}
+
+pub struct Analysis {
+    pub window: Range<usize>,
+    /// how many moves had been made when it started, the answer is stale if
+    /// any were made since
+    pub moves: usize,
+    result: Receiver<Verdict>,
+}
+
+impl Analysis {
+    /// how many positions the search looks at before giving up
+    const STATES: usize = 200_000;
+
+    /// starts looking at `window` of `game` in the background
+    pub fn start(game: &Game, window: Range<usize>, moves: usize) -> Self {
+        let (send, result) = mpsc::channel();
+        let game = game.clone();
+        let searched = window.clone();
+        std::thread::spawn(move || {
+            let solution = solver::solve(&game, searched, Self::STATES);
+            let verdict = if solution.founded > game.founded() {
+                Verdict::Progress(solution.founded - game.founded())
+            } else if !solution.exhausted {
+                Verdict::Unknown
+            } else if game.reshuffles_left() > 0 {
+                Verdict::Reshuffle
+            } else {
+                Verdict::Dead
+            };
+            // nobody is listening any more if the game was closed
+            let _ = send.send(verdict);
+        });
+        Self {
+            window,
+            moves,
+            result,
+        }
+    }
+
+    /// the answer, once there is one
+    pub fn poll(&self) -> Option<Verdict> {
+        match self.result.try_recv() {
+            Ok(verdict) => Some(verdict),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Verdict::Unknown),
+        }
+    }
+}
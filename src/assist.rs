@@ -0,0 +1,54 @@
+//! optional help for the player, off unless asked for
+
+use infiniteklondike::cards::BitCard;
+
+use crate::ui::Menu;
+use crate::MenuAction;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Assists {
+    /// ask before a long run goes on top of a card that could go on a
+    /// foundation right now
+    pub warn_burying: bool,
+    /// a makes every forced move on screen, see `solver::forced_moves`
+    pub auto_arrange: bool,
+    /// after a card goes on a foundation, the next ones of its suit on
+    /// screen follow it up one after another
+    pub auto_chain: bool,
+    /// how fast the mouse goes while carrying cards over the columns,
+    /// compared to usual, for placing them with an unsteady hand
+    pub precision: Option<f32>,
+}
+
+impl Assists {
+    /// how many cards a run needs before covering a card with it is worth
+    /// asking about. shorter ones are easy enough to move off again.
+    pub const BURY_RUN: u8 = 3;
+    /// seconds between the cards of an auto chain or sweep, so it can be
+    /// followed
+    pub const AUTOPLAY_STEP: f64 = 0.15;
+    /// how fast the mouse goes for `--precision` without a number
+    pub const PRECISION: f32 = 0.35;
+}
+
+/// cards going on foundations one after another by themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Autoplay {
+    /// the next cards of the suit just put on this foundation, see
+    /// `Assists::auto_chain`
+    Chain(usize),
+    /// every top card on screen that can go on any foundation, until there
+    /// are none left
+    Sweep,
+}
+
+/// asks whether to go ahead with a move that buries `card`
+pub fn bury_menu(card: BitCard) -> Menu<MenuAction> {
+    Menu::new(
+        &format!("bury the {}?", card),
+        &[
+            ("move anyway", MenuAction::Bury),
+            ("cancel", MenuAction::Resume),
+        ],
+    )
+}
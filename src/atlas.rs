@@ -0,0 +1,237 @@
+//! the card atlas and where everything is in it. the built in `cards.png`
+//! is a row of 22x32 sprites: the blank face, the card backs, the ranks from
+//! ace to king, the four suit pips and the outline of an empty pile. sprites
+//! are drawn over each other and tinted to make a card, and stretched to
+//! the size of a card on screen. a copy of it is built into the game, for
+//! when it's run from somewhere without the file, but the file wins when
+//! it's there.
+//!
+//! an asset pack is a folder with its own `cards.png`, and a `manifest.txt`
+//! if its sprites aren't where the built in ones are. the game loads `pack/`
+//! if it's there, or the folder given with `--pack`. the manifest has a line
+//! for each sprite it moves, with the name and where its top left corner
+//! is. the ranks go one after another to the right of the ace, and `size`
+//! is how big every sprite is:
+//!
+//! ```text
+//! # a pack with sprites twice the size, in two rows
+//! size 44 64
+//! face 0 0
+//! ace 0 64
+//! clubs 44 0
+//! back-pattern 88 0
+//! ```
+//!
+//! the names are `face`, `outline`, `ace`, `clubs`, `diamonds`, `hearts`,
+//! `spades` and `back-` followed by the name of a card back.
+
+use std::path::{Path, PathBuf};
+
+use infiniteklondike::cards::Suit;
+use macroquad::prelude::*;
+
+use crate::style::CardBack;
+use crate::toast;
+
+/// the folder an asset pack is loaded from when `--pack` doesn't say
+pub const DEFAULT_PACK: &str = "pack";
+/// the built in atlas
+const ATLAS_PATH: &str = "cards.png";
+/// the copy of it built into the game
+const EMBEDDED_ATLAS: &[u8] = include_bytes!("../cards.png");
+
+/// where each sprite is in an atlas
+#[derive(Debug, Clone, Copy)]
+pub struct Sprites {
+    /// how big every sprite is, in atlas pixels
+    pub size: Vec2,
+    pub face: Vec2,
+    pub outline: Vec2,
+    /// the ace, with the other ranks following it to the right
+    pub ace: Vec2,
+    /// clubs, diamonds, hearts and spades
+    pub suits: [Vec2; 4],
+    /// by where they are in `CardBack::ALL`
+    pub backs: [Vec2; 4],
+}
+
+impl Default for Sprites {
+    fn default() -> Self {
+        Self {
+            size: vec2(22.0, 32.0),
+            face: vec2(0.0, 0.0),
+            outline: vec2(418.0, 0.0),
+            ace: vec2(44.0, 0.0),
+            suits: [
+                vec2(330.0, 0.0),
+                vec2(352.0, 0.0),
+                vec2(374.0, 0.0),
+                vec2(396.0, 0.0),
+            ],
+            backs: [
+                vec2(22.0, 0.0),
+                vec2(440.0, 0.0),
+                vec2(462.0, 0.0),
+                vec2(484.0, 0.0),
+            ],
+        }
+    }
+}
+
+impl Sprites {
+    /// the sprite with its top left corner at `at`
+    fn at(&self, at: Vec2) -> Rect {
+        Rect::new(at.x, at.y, self.size.x, self.size.y)
+    }
+
+    pub fn face(&self) -> Rect {
+        self.at(self.face)
+    }
+
+    pub fn outline(&self) -> Rect {
+        self.at(self.outline)
+    }
+
+    /// the rank of a card, from `BitCard::number`
+    pub fn rank(&self, number: u8) -> Rect {
+        self.at(self.ace + vec2(self.size.x * number as f32, 0.0))
+    }
+
+    pub fn suit(&self, suit: Suit) -> Rect {
+        self.at(self.suits[suit_index(suit)])
+    }
+
+    pub fn back(&self, back: CardBack) -> Rect {
+        let idx = CardBack::ALL.iter().position(|b| *b == back).unwrap_or(0);
+        self.at(self.backs[idx])
+    }
+
+    /// the layout `manifest` describes, starting from the built in one
+    pub fn parse(manifest: &str) -> Result<Self, String> {
+        let mut sprites = Self::default();
+        for (idx, line) in manifest.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = || format!("line {}: expected `name x y`, got `{}`", idx + 1, line);
+            let mut words = line.split_whitespace();
+            let name = words.next().ok_or_else(err)?;
+            let mut number = || -> Result<f32, String> {
+                words
+                    .next()
+                    .and_then(|word| word.parse().ok())
+                    .filter(|n: &f32| *n >= 0.0)
+                    .ok_or_else(err)
+            };
+            let at = vec2(number()?, number()?);
+            let slot = match name {
+                "size" => &mut sprites.size,
+                "face" => &mut sprites.face,
+                "outline" => &mut sprites.outline,
+                "ace" => &mut sprites.ace,
+                "clubs" => &mut sprites.suits[suit_index(Suit::Club)],
+                "diamonds" => &mut sprites.suits[suit_index(Suit::Diamond)],
+                "hearts" => &mut sprites.suits[suit_index(Suit::Heart)],
+                "spades" => &mut sprites.suits[suit_index(Suit::Spade)],
+                _ => {
+                    let back = name
+                        .strip_prefix("back-")
+                        .and_then(|back| back.parse::<CardBack>().ok())
+                        .ok_or_else(|| format!("line {}: unknown sprite `{}`", idx + 1, name))?;
+                    let idx = CardBack::ALL.iter().position(|b| *b == back).unwrap_or(0);
+                    &mut sprites.backs[idx]
+                }
+            };
+            *slot = at;
+        }
+        if sprites.size.x == 0.0 || sprites.size.y == 0.0 {
+            return Err("sprites can't be 0 pixels across".to_owned());
+        }
+        Ok(sprites)
+    }
+
+    /// the layout of the pack in `dir`, or the built in one if it doesn't
+    /// have a manifest
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(dir.join("manifest.txt")) {
+            Ok(manifest) => Self::parse(&manifest),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// where `suit` goes in `Sprites::suits`
+fn suit_index(suit: Suit) -> usize {
+    match suit {
+        Suit::Club => 0,
+        Suit::Diamond => 1,
+        Suit::Heart => 2,
+        Suit::Spade => 3,
+    }
+}
+
+/// the atlas cards are drawn from
+#[derive(Clone, Copy)]
+pub struct Atlas {
+    pub texture: Texture2D,
+    pub sprites: Sprites,
+}
+
+impl Atlas {
+    /// loads the pack in `dir`, or the built in atlas if there isn't one or
+    /// it can't be read
+    pub async fn load(dir: &Path) -> Self {
+        if dir.join(ATLAS_PATH).exists() {
+            match Self::load_pack(dir).await {
+                Ok(atlas) => return atlas,
+                Err(e) => toast::error(format!(
+                    "could not load the asset pack in {}: {}",
+                    dir.display(),
+                    e
+                )),
+            }
+        }
+        let texture = if Path::new(ATLAS_PATH).exists() {
+            load_texture(ATLAS_PATH).await.ok()
+        } else {
+            None
+        };
+        let texture = texture.unwrap_or_else(|| {
+            Texture2D::from_file_with_format(EMBEDDED_ATLAS, Some(ImageFormat::Png))
+        });
+        texture.set_filter(FilterMode::Nearest);
+        Self {
+            texture,
+            sprites: Sprites::default(),
+        }
+    }
+
+    async fn load_pack(dir: &Path) -> Result<Self, String> {
+        let sprites = Sprites::load(dir)?;
+        let path = dir.join(ATLAS_PATH);
+        let texture = load_texture(&path.to_string_lossy())
+            .await
+            .map_err(|e| e.to_string())?;
+        texture.set_filter(FilterMode::Nearest);
+        Ok(Self { texture, sprites })
+    }
+}
+
+/// the atlas image and its layout for drawing without a window: the pack's
+/// in `dir` if there is one, or the built in one
+pub fn load_image(dir: &Path) -> Result<(image::RgbaImage, Sprites), String> {
+    let pack = dir.join(ATLAS_PATH);
+    let (path, sprites) = if pack.exists() {
+        (pack, Sprites::load(dir)?)
+    } else {
+        (PathBuf::from(ATLAS_PATH), Sprites::default())
+    };
+    let image = if path.exists() {
+        image::open(&path).map_err(|e| format!("could not load {}: {}", path.display(), e))?
+    } else {
+        image::load_from_memory(EMBEDDED_ATLAS).map_err(|e| e.to_string())?
+    };
+    Ok((image.to_rgba8(), sprites))
+}
@@ -0,0 +1,352 @@
+//! sound effects for picking cards up, putting them down (or failing to),
+//! cards turning over and cards going on the foundations, and music. they're
+//! all made when the game starts instead of loaded from files, so there's
+//! nothing to lose, and they're played at the master volume from the
+//! settings.
+//!
+//! the music is a few loops of slow chords. each region of the board has
+//! one of them, going round in turn, and scrolling into the next region
+//! fades from one to the other.
+
+use std::cell::{Cell, RefCell};
+use std::f32::consts::{PI, TAU};
+
+use infiniteklondike::game::Event;
+use macroquad::audio::{
+    load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+};
+use macroquad::time::get_frame_time;
+
+/// something that makes a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    PickUp,
+    PutDown,
+    /// cards let go of somewhere they can't go
+    Invalid,
+    /// a card turned over, in a column or from the stock
+    Reveal,
+    Foundation,
+}
+
+impl Effect {
+    const ALL: [Effect; 5] = [
+        Effect::PickUp,
+        Effect::PutDown,
+        Effect::Invalid,
+        Effect::Reveal,
+        Effect::Foundation,
+    ];
+
+    /// the tones that make it up, one after another
+    fn tones(self) -> Vec<Tone> {
+        match self {
+            Effect::PickUp => vec![Tone::new(520.0, 780.0, 0.06, Wave::Sine)],
+            Effect::PutDown => vec![Tone::new(240.0, 180.0, 0.08, Wave::Sine)],
+            Effect::Invalid => vec![
+                Tone::new(160.0, 150.0, 0.09, Wave::Square),
+                Tone::new(120.0, 110.0, 0.12, Wave::Square),
+            ],
+            Effect::Reveal => vec![Tone::new(1400.0, 900.0, 0.04, Wave::Triangle)],
+            Effect::Foundation => vec![
+                Tone::new(660.0, 660.0, 0.08, Wave::Sine),
+                Tone::new(990.0, 990.0, 0.2, Wave::Sine),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Wave {
+    Sine,
+    Square,
+    Triangle,
+}
+
+/// a note sliding from one pitch to another, fading out as it goes
+#[derive(Debug, Clone, Copy)]
+struct Tone {
+    from: f32,
+    to: f32,
+    /// how long it lasts, in seconds
+    length: f32,
+    wave: Wave,
+}
+
+impl Tone {
+    const fn new(from: f32, to: f32, length: f32, wave: Wave) -> Self {
+        Self {
+            from,
+            to,
+            length,
+            wave,
+        }
+    }
+}
+
+/// the sample rate the effects are made at, the one the mixer plays at
+const SAMPLE_RATE: u32 = 44100;
+/// how loud the loudest sample is, out of 1, so the square waves aren't
+/// harsh
+const PEAK: f32 = 0.4;
+
+/// the chords of each music track, as midi notes, each played for
+/// `CHORD_LENGTH`
+const TRACKS: [[[u8; 3]; 4]; 3] = [
+    // c, a minor, f, g
+    [[60, 64, 67], [57, 60, 64], [53, 57, 60], [55, 59, 62]],
+    // d minor, g, a minor, c
+    [[62, 65, 69], [55, 59, 62], [57, 60, 64], [60, 64, 67]],
+    // e minor, c, g, d
+    [[52, 55, 59], [48, 52, 55], [55, 59, 62], [50, 54, 57]],
+];
+/// how long each chord of the music lasts, in seconds
+const CHORD_LENGTH: f32 = 3.0;
+/// how long one track takes to fade into the next, in seconds
+const CROSSFADE: f32 = 2.5;
+
+/// the music that's on
+#[derive(Default)]
+struct Music {
+    tracks: Vec<Sound>,
+    /// the track playing, and how far it's faded in, out of 1
+    playing: Option<(usize, f32)>,
+    /// the track before it, and how far it's still to fade out
+    fading: Option<(usize, f32)>,
+}
+
+impl Music {
+    /// starts `track` fading in, fading out the one that was on
+    fn switch(&mut self, track: usize) {
+        if let Some((old, _)) = self.fading.take() {
+            stop_sound(self.tracks[old]);
+        }
+        self.fading = self.playing.take();
+        play_sound(
+            self.tracks[track],
+            PlaySoundParams {
+                looped: true,
+                volume: 0.0,
+            },
+        );
+        self.playing = Some((track, 0.0));
+    }
+
+    /// moves the fades along by `dt` seconds, playing everything at `volume`
+    fn fade(&mut self, dt: f32, volume: f32) {
+        let step = dt / CROSSFADE;
+        if let Some((track, level)) = &mut self.playing {
+            *level = (*level + step).min(1.0);
+            set_sound_volume(self.tracks[*track], *level * volume);
+        }
+        if let Some((track, level)) = &mut self.fading {
+            *level -= step;
+            if *level <= 0.0 {
+                stop_sound(self.tracks[*track]);
+                self.fading = None;
+            } else {
+                set_sound_volume(self.tracks[*track], *level * volume);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static SOUNDS: RefCell<Vec<Sound>> = const { RefCell::new(Vec::new()) };
+    static MUSIC: RefCell<Music> = RefCell::new(Music::default());
+    static VOLUME: Cell<f32> = const { Cell::new(1.0) };
+    static MUSIC_VOLUME: Cell<f32> = const { Cell::new(0.5) };
+    static MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// makes the effects and the music, ready to play. call once before
+/// anything's played. if either can't be made the game just goes without.
+pub async fn load() -> Result<(), String> {
+    let mut sounds = Vec::new();
+    for effect in Effect::ALL {
+        let sound = load_sound_from_bytes(&wav(&samples(&effect.tones()))).await;
+        sounds.push(sound.map_err(|e| format!("could not load sound effects: {}", e))?);
+    }
+    SOUNDS.with(|loaded| *loaded.borrow_mut() = sounds);
+    let mut tracks = Vec::new();
+    for chords in &TRACKS {
+        let sound = load_sound_from_bytes(&wav(&track(chords))).await;
+        tracks.push(sound.map_err(|e| format!("could not load music: {}", e))?);
+    }
+    MUSIC.with(|music| music.borrow_mut().tracks = tracks);
+    Ok(())
+}
+
+/// how loud everything is, from 0 for silent to 1
+pub fn volume() -> f32 {
+    VOLUME.with(Cell::get)
+}
+
+pub fn set_volume(volume: f32) {
+    VOLUME.with(|cell| cell.set(volume.clamp(0.0, 1.0)));
+}
+
+/// how loud the music is next to the sound effects, from 0 for none to 1
+pub fn music_volume() -> f32 {
+    MUSIC_VOLUME.with(Cell::get)
+}
+
+pub fn set_music_volume(volume: f32) {
+    MUSIC_VOLUME.with(|cell| cell.set(volume.clamp(0.0, 1.0)));
+}
+
+/// mutes everything or turns it back up, returning whether it's muted now
+pub fn toggle_mute() -> bool {
+    MUTED.with(|muted| {
+        muted.set(!muted.get());
+        muted.get()
+    })
+}
+
+/// the volume things are really played at, nothing while muted
+fn loudness() -> f32 {
+    if MUTED.with(Cell::get) {
+        0.0
+    } else {
+        volume()
+    }
+}
+
+/// plays the track for `region`, fading over to it if another one's on.
+/// call once a frame.
+pub fn update_music(region: usize) {
+    MUSIC.with(|music| {
+        let mut music = music.borrow_mut();
+        if music.tracks.is_empty() {
+            return;
+        }
+        let track = region % music.tracks.len();
+        if music.playing.map(|(playing, _)| playing) != Some(track) {
+            music.switch(track);
+        }
+        music.fade(get_frame_time(), loudness() * music_volume());
+    });
+}
+
+pub fn play(effect: Effect) {
+    let volume = loudness();
+    if volume <= 0.0 {
+        return;
+    }
+    let idx = Effect::ALL.iter().position(|e| *e == effect).unwrap_or(0);
+    if let Some(sound) = SOUNDS.with(|sounds| sounds.borrow().get(idx).copied()) {
+        play_sound(
+            sound,
+            PlaySoundParams {
+                looped: false,
+                volume,
+            },
+        );
+    }
+}
+
+/// plays what a move sounds like from the `events` it made: cards going on
+/// a foundation or just being put down, and a card turning over on top
+pub fn play_move(events: &[Event]) {
+    let founded = events
+        .iter()
+        .any(|event| matches!(event, Event::Founded { .. }));
+    play(if founded {
+        Effect::Foundation
+    } else {
+        Effect::PutDown
+    });
+    let revealed = events.iter().any(|event| {
+        matches!(
+            event,
+            Event::Revealed { .. }
+                | Event::TurnedBack { .. }
+                | Event::Reshuffled { .. }
+                | Event::Drawn { .. }
+        )
+    });
+    if revealed {
+        play(Effect::Reveal);
+    }
+}
+
+/// the samples for a loop of `chords`. each chord swells in and dies away
+/// again, so the loop goes round without a seam.
+fn track(chords: &[[u8; 3]]) -> Vec<f32> {
+    let per_chord = (CHORD_LENGTH * SAMPLE_RATE as f32) as usize;
+    let mut samples = Vec::with_capacity(per_chord * chords.len());
+    for chord in chords {
+        // the root an octave down as well, for some weight
+        let notes: Vec<f32> = chord
+            .iter()
+            .map(|note| note_freq(*note))
+            .chain([note_freq(chord[0]) / 2.0])
+            .collect();
+        for n in 0..per_chord {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            let swell = (PI * n as f32 / per_chord as f32).sin();
+            let sum: f32 = notes.iter().map(|freq| (TAU * freq * t).sin()).sum();
+            samples.push(sum / notes.len() as f32 * swell * PEAK);
+        }
+    }
+    samples
+}
+
+/// the pitch of midi note `note`, in hz
+fn note_freq(note: u8) -> f32 {
+    440.0 * 2f32.powf((f32::from(note) - 69.0) / 12.0)
+}
+
+/// the samples for `tones`, one after another
+fn samples(tones: &[Tone]) -> Vec<f32> {
+    let mut samples = Vec::new();
+    for tone in tones {
+        let count = (tone.length * SAMPLE_RATE as f32) as usize;
+        let mut phase = 0.0;
+        for n in 0..count {
+            let done = n as f32 / count as f32;
+            let freq = tone.from + (tone.to - tone.from) * done;
+            phase = (phase + freq / SAMPLE_RATE as f32).fract();
+            let wave = match tone.wave {
+                Wave::Sine => (phase * TAU).sin(),
+                Wave::Square => {
+                    if phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            };
+            // a quick start so it doesn't click, then fading away
+            let attack = (n as f32 / 64.0).min(1.0);
+            let fade = (1.0 - done).powi(2);
+            samples.push(wave * attack * fade * PEAK);
+        }
+    }
+    samples
+}
+
+/// `samples` as a mono 16 bit wav file
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // plain pcm, one channel
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
@@ -0,0 +1,176 @@
+//! autosaves. every tab is saved when the window closes and every so often
+//! while playing, so a crash loses at most a minute or so. each time the
+//! last few autosaves of every tab are kept too, newest first, and the pause
+//! menu can go back to one of them, for a bad move too far back to undo.
+
+use std::time::SystemTime;
+
+use infiniteklondike::save::SaveFormat;
+use macroquad::prelude::get_time;
+
+use crate::tabs::{tab_id, tab_path, Tabs};
+use crate::toast;
+
+/// where every tab is saved, before the extension. always binary, they're
+/// not meant for editing.
+pub const PATH: &str = "autosave";
+
+/// how often to autosave and how many to keep
+pub struct Autosave {
+    /// seconds between autosaves while playing, or 0 for only when the
+    /// window closes
+    every: f64,
+    /// how many old autosaves of each tab to keep
+    keep: usize,
+    /// when the last one was, from `get_time`
+    last: f64,
+}
+
+impl Autosave {
+    /// how often to autosave unless picked with `--autosave-every`
+    pub const EVERY: f64 = 60.0;
+    /// how many to keep unless picked with `--autosave-keep`
+    pub const KEEP: usize = 5;
+
+    pub fn new(every: f64, keep: usize) -> Self {
+        Self {
+            every,
+            keep,
+            last: get_time(),
+        }
+    }
+
+    /// whether it's time for another autosave
+    pub fn is_due(&self) -> bool {
+        self.every > 0.0 && get_time() - self.last >= self.every
+    }
+
+    /// saves every tab, keeping the last few saves of each
+    pub fn save(&mut self, tabs: &mut Tabs) {
+        self.last = get_time();
+        let extension = SaveFormat::Binary.extension();
+        for (_, state) in tabs.iter() {
+            // the oldest falls off the end
+            for n in (1..self.keep).rev() {
+                let _ = std::fs::rename(kept_path(state.id, n), kept_path(state.id, n + 1));
+            }
+        }
+        save_all(tabs);
+        if self.keep == 0 {
+            return;
+        }
+        for (_, state) in tabs.iter() {
+            let _ = std::fs::copy(tab_path(PATH, state.id, extension), kept_path(state.id, 1));
+        }
+    }
+}
+
+/// the `n`th newest kept autosave of the tab with `id`, counting from 1
+pub fn kept_path(id: usize, n: usize) -> String {
+    tab_path(
+        &format!("{}.{}", PATH, n),
+        id,
+        SaveFormat::Binary.extension(),
+    )
+}
+
+/// the kept autosaves of the tab with `id` that are there, newest first,
+/// each with how long ago it was saved
+pub fn kept(id: usize, keep: usize) -> Vec<(usize, String)> {
+    (1..=keep)
+        .filter_map(|n| {
+            let modified = std::fs::metadata(kept_path(id, n))
+                .and_then(|meta| meta.modified())
+                .ok()?;
+            let age = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default()
+                .as_secs();
+            let ago = match age {
+                0..=59 => "just now".to_owned(),
+                60..=3599 => format!("{} min ago", age / 60),
+                _ => format!("{} h {} min ago", age / 3600, age % 3600 / 60),
+            };
+            Some((n, ago))
+        })
+        .collect()
+}
+
+/// the names of the files next to the autosaves
+fn file_names() -> Vec<String> {
+    std::fs::read_dir(".")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+/// the ids of the tabs there are autosaves of, in order
+fn saved_ids() -> Vec<usize> {
+    let extension = SaveFormat::Binary.extension();
+    let mut ids: Vec<usize> = file_names()
+        .iter()
+        .filter_map(|name| tab_id(PATH, name, extension))
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// the id of the tab a kept autosave called `name` is of, if it is one
+fn kept_id(name: &str) -> Option<usize> {
+    let n = name
+        .strip_prefix(PATH)?
+        .strip_prefix('.')?
+        .split(['-', '.'])
+        .next()?;
+    tab_id(
+        &format!("{}.{}", PATH, n),
+        name,
+        SaveFormat::Binary.extension(),
+    )
+}
+
+/// whether there are autosaves from last time to pick up
+pub fn exists() -> bool {
+    !saved_ids().is_empty()
+}
+
+/// saves every tab for next time, and gets rid of the saves of tabs that
+/// have been closed since, kept ones too, so a tab opened later with the
+/// same id doesn't get handed them
+pub fn save_all(tabs: &mut Tabs) {
+    let extension = SaveFormat::Binary.extension();
+    let mut open = Vec::new();
+    for (_, state) in tabs.iter_mut() {
+        let path = tab_path(PATH, state.id, extension);
+        if let Err(e) = state.write_save(&path, SaveFormat::Binary) {
+            toast::error(format!("could not autosave to {}: {}", path, e));
+        }
+        open.push(state.id);
+    }
+    for name in file_names() {
+        let id = tab_id(PATH, &name, extension).or_else(|| kept_id(&name));
+        if id.is_some_and(|id| !open.contains(&id)) {
+            let _ = std::fs::remove_file(name);
+        }
+    }
+}
+
+/// loads what `save_all` saved, a tab for each game, each with the id it
+/// had so its kept autosaves stay its own
+pub fn continue_games(tabs: &mut Tabs) {
+    let extension = SaveFormat::Binary.extension();
+    let ids = saved_ids();
+    let mut loaded = 0;
+    for &id in &ids {
+        if loaded > 0 && !tabs.open() {
+            break;
+        }
+        tabs.set_current_id(id);
+        tabs.current().load_game(&tab_path(PATH, id, extension));
+        loaded += 1;
+    }
+    tabs.switch(0);
+    toast::info(format!("picked up {} games where you left off", loaded));
+}
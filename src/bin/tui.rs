@@ -0,0 +1,445 @@
+//! a terminal frontend, for playing without a gpu or a display server.
+//!
+//! arrows (or hjkl) move the cursor, space picks up and drops cards, f sends
+//! the last card of the column under the cursor to the foundations (1 to 8
+//! to that one in particular), esc puts the cards back and q quits. `tui --stock` plays with a stock: d turns a
+//! card over, w puts it on the column under the cursor and W on a foundation.
+//! `tui --draw-three` turns three over at a time.
+//! `tui watch <replay>` plays a replay back a move at a time instead.
+
+use std::io::{self, Read, Stdin, Write};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+use infiniteklondike::cards::{BitCard, Suit};
+use infiniteklondike::frontend::{InputEvent, InputSource, Key, ReplaySource};
+use infiniteklondike::game::{Game, Move};
+use infiniteklondike::replay::Replay;
+use infiniteklondike::rules::Rules;
+
+const REPLAY_PATH: &str = "last.replay";
+/// how many characters a column takes up
+const COLUMN_WIDTH: usize = 4;
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+];
+/// how long `watch` waits between moves, in seconds
+const WATCH_INTERVAL: f64 = 0.5;
+
+struct Tui {
+    game: Game,
+    moves: Vec<Move>,
+    /// the leftmost column on screen
+    scroll: usize,
+    cursor: usize,
+    /// which visible card in the cursor's column is selected
+    depth: usize,
+    /// the column and index of the cards that were picked up
+    held: Option<(usize, usize)>,
+    message: String,
+}
+
+impl Tui {
+    fn new(seed: u64, rules: Rules) -> Self {
+        Self::with_game(Game::with_rules(seed, 50, rules), format!("seed {}", seed))
+    }
+
+    /// the start of a replay, to watch it
+    fn watching(replay: &Replay) -> Self {
+        Self::with_game(
+            Game::with_rules(replay.seed, replay.columns, replay.rules),
+            format!("watching seed {}", replay.seed),
+        )
+    }
+
+    fn with_game(game: Game, message: String) -> Self {
+        Self {
+            game,
+            moves: Vec::new(),
+            scroll: 0,
+            cursor: 0,
+            depth: 0,
+            held: None,
+            message,
+        }
+    }
+
+    fn visible_len(&self, column: usize) -> usize {
+        self.game.tableau()[column].visible().len().into()
+    }
+
+    fn handle(&mut self, event: InputEvent, columns_on_screen: usize) {
+        self.message.clear();
+        match event {
+            InputEvent::KeyDown(Key::Left) => self.cursor = self.cursor.saturating_sub(1),
+            InputEvent::KeyDown(Key::Right) => self.cursor += 1,
+            InputEvent::KeyDown(Key::Up) => self.depth = self.depth.saturating_sub(1),
+            InputEvent::KeyDown(Key::Down) => self.depth += 1,
+            InputEvent::KeyDown(Key::Select) => self.select(),
+            InputEvent::KeyDown(Key::Char('f')) => self.found(),
+            InputEvent::KeyDown(Key::Char(c @ '1'..='8')) => {
+                self.found_to(c as usize - '1' as usize);
+            }
+            InputEvent::KeyDown(Key::Char('r')) => {
+                self.held = None;
+                self.apply(Move::Reshuffle {
+                    column: self.cursor,
+                });
+            }
+            InputEvent::KeyDown(Key::Char('d')) => {
+                self.held = None;
+                self.apply(Move::Draw);
+            }
+            InputEvent::KeyDown(Key::Char('w')) => {
+                self.held = None;
+                self.apply(Move::Waste { to: self.cursor });
+            }
+            InputEvent::KeyDown(Key::Char('W')) => self.found_waste(),
+            InputEvent::KeyDown(Key::Cancel) => self.held = None,
+            InputEvent::Play(mv) => {
+                // follow the replay around the board
+                self.cursor = match mv {
+                    Move::Tableau { to, .. } | Move::Insert { at: to, .. } => to,
+                    Move::Foundation { from, .. } => from,
+                    Move::Reshuffle { column } | Move::Waste { to: column } => column,
+                    Move::Draw | Move::WasteFoundation { .. } => self.cursor,
+                };
+                self.apply(mv);
+            }
+            _ => {}
+        }
+        self.game.ensure_columns(self.cursor + 1);
+        self.depth = self
+            .depth
+            .min(self.visible_len(self.cursor).saturating_sub(1));
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + columns_on_screen {
+            self.scroll = self.cursor + 1 - columns_on_screen;
+        }
+        self.fill(columns_on_screen);
+    }
+
+    /// deals every column on screen, so there's something to draw for all
+    /// of them however wide the terminal is
+    fn fill(&mut self, columns_on_screen: usize) {
+        self.game.ensure_columns(self.scroll + columns_on_screen);
+    }
+
+    fn select(&mut self) {
+        match self.held.take() {
+            None if self.visible_len(self.cursor) > 0 => {
+                self.held = Some((self.cursor, self.depth));
+            }
+            None => {}
+            Some((from, idx)) => {
+                let mv = Move::Tableau {
+                    from,
+                    idx,
+                    to: self.cursor,
+                };
+                let insert = Move::Insert {
+                    from,
+                    idx,
+                    at: self.cursor,
+                };
+                // past the columns that were played on, a king that can't go
+                // anywhere else starts a new column
+                if self.game.check(mv).is_err() && self.game.check(insert).is_ok() {
+                    self.apply(insert);
+                } else {
+                    self.apply(mv);
+                }
+            }
+        }
+    }
+
+    fn found(&mut self) {
+        self.held = None;
+        let mv = self
+            .game
+            .legal_moves(self.cursor..self.cursor + 1)
+            .into_iter()
+            .find(|mv| matches!(mv, Move::Foundation { .. }));
+        match mv {
+            Some(mv) => self.apply(mv),
+            None => self.message = "that card can't go to a foundation".to_owned(),
+        }
+    }
+
+    /// founds the last card of the column under the cursor on the `slot`th
+    /// foundation shown, counting the free one after the rest
+    fn found_to(&mut self, slot: usize) {
+        self.held = None;
+        let mut foundations: Vec<usize> = self.game.foundations().keys().copied().collect();
+        foundations.sort_unstable();
+        foundations.push(self.game.free_foundation());
+        let Some(&foundation) = foundations.get(slot) else {
+            self.message = format!("there's no foundation {}", slot + 1);
+            return;
+        };
+        self.apply(Move::Foundation {
+            from: self.cursor,
+            foundation,
+        });
+    }
+
+    fn found_waste(&mut self) {
+        self.held = None;
+        let mv = self
+            .game
+            .legal_moves(0..0)
+            .into_iter()
+            .find(|mv| matches!(mv, Move::WasteFoundation { .. }));
+        match mv {
+            Some(mv) => self.apply(mv),
+            None if self.game.waste().is_empty() => {
+                self.message = "the waste is empty".to_owned();
+            }
+            None => self.message = "that card can't go to a foundation".to_owned(),
+        }
+    }
+
+    fn apply(&mut self, mv: Move) {
+        match self.game.apply(mv) {
+            Ok(_) => self.moves.push(mv),
+            Err(e) => self.message = e.to_string(),
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write, columns_on_screen: usize) -> io::Result<()> {
+        write!(out, "\x1b[H\x1b[2J")?;
+        let mut foundations: Vec<_> = self.game.foundations().iter().collect();
+        foundations.sort_by_key(|(idx, _)| **idx);
+        write!(out, "foundations:")?;
+        for (_, card) in foundations {
+            write!(out, " {}", card_text(*card))?;
+        }
+        if self.game.rules().stock {
+            write!(out, "   stock: {} drawn, waste:", self.game.drawn())?;
+            let waste = self.game.waste();
+            if waste.is_empty() {
+                write!(out, " empty")?;
+            }
+            // drawing three shows the last three, only the last one plays
+            let fanned = self.game.rules().cards_per_draw() as usize;
+            for card in &waste[waste.len().saturating_sub(fanned)..] {
+                write!(out, " {}", card_text(*card))?;
+            }
+        }
+        write!(out, "\r\n\r\n")?;
+        let window = self.scroll..self.scroll + columns_on_screen;
+        for x in window.clone() {
+            write!(out, "{:<width$}", x, width = COLUMN_WIDTH)?;
+        }
+        write!(out, "\r\n")?;
+        let tallest = window
+            .clone()
+            .map(|x| self.game.tableau()[x].under as usize + self.visible_len(x))
+            .max()
+            .unwrap_or(0);
+        for y in 0..tallest.max(1) {
+            for x in window.clone() {
+                let column = &self.game.tableau()[x];
+                let under = column.under as usize;
+                // the escape codes don't take up space, so keep track of the width
+                let (cell, width) = if y < under {
+                    ("\x1b[2m░░\x1b[0m".to_owned(), 2)
+                } else if let Some(card) = column.visible().iter().nth(y - under) {
+                    let idx = y - under;
+                    let held =
+                        matches!(self.held, Some((from, from_idx)) if from == x && idx >= from_idx);
+                    let cursor = x == self.cursor && idx == self.depth;
+                    let style = match (cursor, held) {
+                        (true, _) => "\x1b[7m",
+                        (false, true) => "\x1b[4m",
+                        _ => "",
+                    };
+                    let width = RANKS[card.number() as usize].len() + 1;
+                    (format!("{}{}\x1b[0m", style, card_text(card)), width)
+                } else if y == 0 && x == self.cursor {
+                    ("\x1b[7m[]\x1b[0m".to_owned(), 2)
+                } else if y == 0 {
+                    // finished columns are dimmed so they stand out less
+                    ("\x1b[2m[]\x1b[0m".to_owned(), 2)
+                } else {
+                    (String::new(), 0)
+                };
+                write!(out, "{}{:pad$}", cell, "", pad = COLUMN_WIDTH - width)?;
+            }
+            write!(out, "\r\n")?;
+        }
+        write!(
+            out,
+            "\r\n{} on foundations, {} moves, {} reshuffles  {}\r\n",
+            self.game.founded(),
+            self.moves.len(),
+            self.game.reshuffles_left(),
+            self.message
+        )?;
+        write!(
+            out,
+            "arrows/hjkl move, space pick up/drop, f foundation, r reshuffle, esc cancel, q quit\r\n"
+        )?;
+        if self.game.rules().stock {
+            write!(out, "d draw, w waste to column, W waste to foundation\r\n")?;
+        }
+        out.flush()
+    }
+}
+
+fn card_text(card: BitCard) -> String {
+    let (suit, red) = match card.suit() {
+        Suit::Club => ('♣', false),
+        Suit::Diamond => ('♦', true),
+        Suit::Heart => ('♥', true),
+        Suit::Spade => ('♠', false),
+    };
+    let color = if red { "\x1b[31m" } else { "" };
+    format!("{}{}{}\x1b[0m", color, RANKS[card.number() as usize], suit)
+}
+
+/// the keyboard, read from stdin in raw mode
+struct Terminal {
+    stdin: Stdin,
+    /// why reading stopped, if it went wrong
+    error: Option<io::Error>,
+}
+
+impl InputSource for Terminal {
+    fn poll(&mut self, _now: f64) -> Vec<InputEvent> {
+        match read_key(&mut self.stdin) {
+            Ok(key) => key.map(InputEvent::KeyDown).into_iter().collect(),
+            Err(e) => {
+                self.error = Some(e);
+                vec![InputEvent::KeyDown(Key::Quit)]
+            }
+        }
+    }
+}
+
+fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let mut byte = [0];
+    if input.read(&mut byte)? == 0 {
+        return Ok(None);
+    }
+    Ok(match byte[0] {
+        b'h' => Some(Key::Left),
+        b'l' => Some(Key::Right),
+        b'k' => Some(Key::Up),
+        b'j' => Some(Key::Down),
+        b' ' | b'\r' | b'\n' => Some(Key::Select),
+        b'q' | 3 => Some(Key::Quit),
+        c if c.is_ascii_graphic() => Some(Key::Char(c as char)),
+        0x1b => {
+            // either a lone escape or the start of an arrow key
+            let mut seq = [0; 2];
+            if input.read(&mut seq[..1])? == 0 || seq[0] != b'[' {
+                return Ok(Some(Key::Cancel));
+            }
+            input.read_exact(&mut seq[1..])?;
+            match seq[1] {
+                b'A' => Some(Key::Up),
+                b'B' => Some(Key::Down),
+                b'C' => Some(Key::Right),
+                b'D' => Some(Key::Left),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn columns_on_screen() -> usize {
+    let width = stty(&["size"])
+        .ok()
+        .and_then(|size| size.split_whitespace().nth(1)?.parse().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(80);
+    (width / COLUMN_WIDTH).max(1)
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1).peekable();
+    let stock = args.next_if_eq("--stock").is_some();
+    let draw_three = args.next_if_eq("--draw-three").is_some();
+    let rules = Rules {
+        stock: stock || draw_three,
+        draw_three,
+        ..Rules::default()
+    };
+    let first = args.next();
+    let (mut tui, mut replay) = if first.as_deref() == Some("watch") {
+        let path = args.next().unwrap_or_else(|| REPLAY_PATH.to_owned());
+        let replay = Replay::load(&path)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        (
+            Tui::watching(&replay),
+            Some(ReplaySource::new(&replay, WATCH_INTERVAL)),
+        )
+    } else {
+        let seed = first
+            .and_then(|arg| arg.parse().ok())
+            .unwrap_or_else(rand::random);
+        (Tui::new(seed, rules), None)
+    };
+    let saved = stty(&["-g"])?;
+    // a panic would leave the terminal raw, with nothing echoed
+    let hook = std::panic::take_hook();
+    let restore = saved.clone();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = stty(&[&restore]);
+        hook(info);
+    }));
+    // reads give up after a tenth of a second, so a lone escape doesn't wait
+    // for the next key to come in
+    stty(&["raw", "-echo", "min", "0", "time", "1"])?;
+    let mut terminal = Terminal {
+        stdin: io::stdin(),
+        error: None,
+    };
+    let mut stdout = io::stdout();
+    let start = Instant::now();
+    let result = (|| {
+        let columns = columns_on_screen();
+        tui.fill(columns);
+        tui.draw(&mut stdout, columns)?;
+        loop {
+            let now = start.elapsed().as_secs_f64();
+            let mut events = terminal.poll(now);
+            if let Some(replay) = &mut replay {
+                // only the replay moves cards while watching
+                events.retain(|event| *event == InputEvent::KeyDown(Key::Quit));
+                events.extend(replay.poll(now));
+            }
+            for event in events {
+                if event == InputEvent::KeyDown(Key::Quit) {
+                    return terminal.error.take().map_or(Ok(()), Err);
+                }
+                let columns = columns_on_screen();
+                tui.handle(event, columns);
+                if replay.as_ref().is_some_and(ReplaySource::is_done) {
+                    tui.message = "end of the replay, q quits".to_owned();
+                }
+                tui.draw(&mut stdout, columns)?;
+            }
+        }
+    })();
+    stty(&[&saved])?;
+    write!(stdout, "\x1b[H\x1b[2J")?;
+    // watching leaves the replay as it was
+    if replay.is_none() {
+        if let Err(e) = Replay::record(&tui.game, &tui.moves).save(REPLAY_PATH) {
+            eprintln!("could not save replay: {}", e);
+        }
+    }
+    result
+}
@@ -0,0 +1,139 @@
+//! named camera positions to jump back to. b names a new one, the number
+//! keys jump to them and shift plus a number forgets one. j goes through
+//! them in turn, and shift+j the other way. they're kept in saves.
+
+use macroquad::prelude::*;
+
+use crate::input::{get_char_pressed, is_key_down, is_key_pressed};
+
+use crate::{ctrl_down, toast, NUMBER_KEYS};
+
+pub struct Bookmark {
+    pub name: String,
+    pub camera: Vec2,
+}
+
+#[derive(Default)]
+pub struct Bookmarks {
+    pub marks: Vec<Bookmark>,
+    /// the name being typed for a new bookmark
+    naming: Option<String>,
+    /// the bookmark j last went to
+    cycled: Option<usize>,
+}
+
+impl Bookmarks {
+    /// one for each number key
+    const MAX: usize = 9;
+
+    pub fn is_naming(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    /// the bookmarks as saves keep them
+    pub fn to_save(&self) -> Vec<(String, [f32; 2])> {
+        self.marks
+            .iter()
+            .map(|mark| (mark.name.clone(), mark.camera.into()))
+            .collect()
+    }
+
+    /// the bookmarks a save kept
+    pub fn from_save(marks: Vec<(String, [f32; 2])>) -> Self {
+        Self {
+            marks: marks
+                .into_iter()
+                .map(|(name, camera)| Bookmark {
+                    name,
+                    camera: camera.into(),
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// handles input, returning where to jump to if a bookmark was picked.
+    /// `first_column` names bookmarks that weren't given a name.
+    pub fn update(&mut self, camera: Vec2, first_column: usize) -> Option<Vec2> {
+        if let Some(name) = &mut self.naming {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() && name.len() < 24 {
+                    name.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+            if is_key_pressed(KeyCode::Escape) {
+                self.naming = None;
+            } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+                let mut name = self.naming.take().unwrap();
+                if name.trim().is_empty() {
+                    name = format!("column {}", first_column);
+                }
+                self.marks.push(Bookmark { name, camera });
+            }
+            return None;
+        }
+        if ctrl_down() {
+            return None;
+        }
+        if is_key_pressed(KeyCode::B) {
+            if self.marks.len() < Self::MAX {
+                // the b that started this shouldn't end up in the name
+                while get_char_pressed().is_some() {}
+                self.naming = Some(String::new());
+            } else {
+                toast::error(format!("all {} bookmarks are taken", Self::MAX));
+            }
+            return None;
+        }
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        if is_key_pressed(KeyCode::J) {
+            let len = self.marks.len();
+            if len == 0 {
+                toast::error("no bookmarks yet, b makes one");
+                return None;
+            }
+            let idx = match (self.cycled, shift) {
+                (Some(idx), false) => (idx + 1) % len,
+                (Some(idx), true) => (idx + len - 1) % len,
+                (None, false) => 0,
+                (None, true) => len - 1,
+            };
+            self.cycled = Some(idx);
+            let mark = &self.marks[idx];
+            toast::info(format!("{} {}", idx + 1, mark.name));
+            return Some(mark.camera);
+        }
+        let idx = NUMBER_KEYS.iter().position(|key| is_key_pressed(*key))?;
+        if shift {
+            if idx < self.marks.len() {
+                self.marks.remove(idx);
+                self.cycled = None;
+            }
+            None
+        } else {
+            self.marks.get(idx).map(|mark| mark.camera)
+        }
+    }
+
+    pub fn draw(&self) {
+        let x = screen_width() - 160.0;
+        for (idx, mark) in self.marks.iter().enumerate() {
+            let label = format!("{} {}", idx + 1, mark.name);
+            draw_text(&label, x, 16.0 + 16.0 * idx as f32, 16.0, GRAY);
+        }
+        if let Some(name) = &self.naming {
+            let prompt = format!("bookmark name: {}_", name);
+            draw_rectangle(
+                0.0,
+                0.0,
+                screen_width(),
+                24.0,
+                Color::new(0.0, 0.0, 0.0, 0.8),
+            );
+            draw_text(&prompt, 8.0, 17.0, 20.0, WHITE);
+        }
+    }
+}
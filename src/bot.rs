@@ -0,0 +1,58 @@
+//! the game playing itself. p turns it on and off: once a second it makes
+//! the next move of the best plan the solver can find for the columns on
+//! screen, and when there's nothing left to do there it moves along the
+//! board to find more.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use infiniteklondike::game::{Game, Move};
+use infiniteklondike::solver;
+use macroquad::prelude::get_time;
+
+pub struct Bot {
+    /// what's left of the solver's plan
+    plan: VecDeque<Move>,
+    /// the columns the plan was made for
+    window: Range<usize>,
+    /// when the last move was made, from `get_time`
+    last: f64,
+}
+
+impl Bot {
+    /// seconds between moves, slow enough to follow
+    const STEP: f64 = 1.0;
+    /// how many positions the solver gets to look at for each plan. it runs
+    /// between frames, so this keeps it from stalling the window.
+    const STATES: usize = 5_000;
+
+    pub fn new() -> Self {
+        Self {
+            plan: VecDeque::new(),
+            window: 0..0,
+            // the first move waits a step too, so turning it on isn't jarring
+            last: get_time(),
+        }
+    }
+
+    /// whether it's time for another move
+    pub fn is_due(&self) -> bool {
+        get_time() - self.last >= Self::STEP
+    }
+
+    /// the next move to make on the columns in `window`, planning again if
+    /// the plan ran out, the window moved or the board changed under it.
+    /// `None` when nothing there gets another card on a foundation.
+    pub fn next_move(&mut self, game: &Game, window: Range<usize>) -> Option<Move> {
+        self.last = get_time();
+        let planned =
+            window == self.window && self.plan.front().is_some_and(|mv| game.check(*mv).is_ok());
+        if !planned {
+            self.plan = solver::solve(game, window.clone(), Self::STATES)
+                .moves
+                .into();
+            self.window = window;
+        }
+        self.plan.pop_front()
+    }
+}
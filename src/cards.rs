@@ -1,11 +1,126 @@
-use rand::Rng;
+//! cards, stacks of cards, and the columns they sit in
 
-use crate::Suit;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use rand::{distributions::Standard, prelude::Distribution, Rng};
+
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Suit {
+    Club = 0b10,
+    Diamond = 0b00,
+    Heart = 0b01,
+    Spade = 0b11,
+}
+
+impl Suit {
+    /// the first letter of the suit's name
+    pub fn letter(self) -> char {
+        match self {
+            Suit::Club => 'C',
+            Suit::Diamond => 'D',
+            Suit::Heart => 'H',
+            Suit::Spade => 'S',
+        }
+    }
+}
+
+impl Distribution<Suit> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Suit {
+        match rng.gen_range(0..=3) {
+            0 => Suit::Club,
+            1 => Suit::Diamond,
+            2 => Suit::Heart,
+            _ => Suit::Spade,
+        }
+    }
+}
+
+/// which cards the game is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Deck {
+    /// all 52
+    #[default]
+    Full,
+    /// the 32 card piquet deck, stripped of the twos to sixes. sevens go
+    /// on aces on the foundations, and aces on sevens in the tableau.
+    Piquet,
+}
+
+impl Deck {
+    /// the ranks in the deck, by `BitCard::number`
+    const PIQUET_RANKS: [u8; 8] = [0, 6, 7, 8, 9, 10, 11, 12];
+
+    /// a random card from the deck. the full deck draws exactly the same
+    /// cards it always has, so seeds don't change.
+    pub(crate) fn random_card(self, rng: &mut impl Rng) -> BitCard {
+        match self {
+            Deck::Full => BitCard::random(rng),
+            Deck::Piquet => {
+                let n = rng.gen_range(0..32u8);
+                BitCard(Self::PIQUET_RANKS[(n >> 2) as usize] << 2 | n & 0b11)
+            }
+        }
+    }
+
+    /// whether `card` is in the deck
+    pub fn contains(self, card: BitCard) -> bool {
+        match self {
+            Deck::Full => true,
+            Deck::Piquet => Self::PIQUET_RANKS.contains(&card.number()),
+        }
+    }
+
+    /// how many ranks of the deck come before rank `number`, so how many
+    /// cards are under it on a foundation
+    pub fn rank_index(self, number: u8) -> usize {
+        match self {
+            Deck::Full => number as usize,
+            Deck::Piquet => Self::PIQUET_RANKS
+                .iter()
+                .position(|rank| *rank == number)
+                .unwrap_or(0),
+        }
+    }
+
+    /// whether rank `number` comes straight after `under` in the deck
+    fn follows(self, number: u8, under: u8) -> bool {
+        match self {
+            Deck::Piquet if under == 0 => number == 6,
+            _ => number == under + 1,
+        }
+    }
+}
+
+impl fmt::Display for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Deck::Full => "full",
+            Deck::Piquet => "piquet",
+        })
+    }
+}
+
+impl FromStr for Deck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Deck::Full),
+            "piquet" => Ok(Deck::Piquet),
+            _ => Err(format!("unknown deck `{}`, try full or piquet", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// a BitCard has the following layout
 ///
-/// ```
+/// ```text
 /// |-n/a-| |-pip-| |-suit-|
 ///    7    6     2 1      0
 /// ```
@@ -14,6 +129,12 @@ pub struct BitCard(u8);
 // also a bitcard is any number from 0 to 51 (inclusive)
 
 impl BitCard {
+    /// `number` is 0 for an ace up to 12 for a king
+    pub fn new(number: u8, suit: Suit) -> Self {
+        debug_assert!(number < 13);
+        Self(number << 2 | suit as u8)
+    }
+
     fn color(self) -> u8 {
         self.0 & 0b10
     }
@@ -51,35 +172,198 @@ impl BitCard {
         self.number() == 12
     }
 
-    pub fn is_next_card(self, other: Self) -> bool {
-        other.number() + 1 == self.number()
+    /// whether this card goes on `other` on a foundation, going by rank
+    pub fn is_next_card(self, other: Self, deck: Deck) -> bool {
+        deck.follows(self.number(), other.number())
     }
 
     pub fn same_suit(self, other: Self) -> bool {
         self.suit_raw() == other.suit_raw()
     }
 
-    fn can_stack_on(self, under: Self) -> bool {
-        self.color() != under.color() && under.number() == self.number() + 1
+    fn can_stack_on(self, under: Self, deck: Deck) -> bool {
+        self.color() != under.color() && deck.follows(under.number(), self.number())
     }
 
     // note to future: if i ever want to replace this with xoroshiro or something,
     // https://stackoverflow.com/questions/10984974/why-do-people-say-there-is-modulo-bias-when-using-a-random-number-generator
     // the best is 62 bits
-    fn random(rng: &mut impl Rng) -> Self {
+    pub(crate) fn random(rng: &mut impl Rng) -> Self {
         Self(rng.gen_range(0..52))
     }
 
     pub fn is_red(self) -> bool {
         self.color() == 0
     }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        (bits < 52).then_some(Self(bits))
+    }
+}
+
+/// cards are written as their rank and the first letter of their suit, like
+/// `AS`, `10H` or `QD`
+impl fmt::Display for BitCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            RANKS[self.number() as usize],
+            self.suit().letter()
+        )
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseCardError;
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not a card, cards look like AS, 10H or QD")
+    }
+}
+
+impl FromStr for BitCard {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() {
+            return Err(ParseCardError);
+        }
+        let s = s.to_ascii_uppercase();
+        let (rank, suit) = s.split_at(s.len().checked_sub(1).ok_or(ParseCardError)?);
+        let number = RANKS
+            .iter()
+            .position(|r| *r == rank)
+            .ok_or(ParseCardError)?;
+        let suit = match suit {
+            "C" => Suit::Club,
+            "D" => Suit::Diamond,
+            "H" => Suit::Heart,
+            "S" => Suit::Spade,
+            _ => return Err(ParseCardError),
+        };
+        Ok(Self::new(number as u8, suit))
+    }
+}
+
+/// which card out of all the ones with the same rank and suit. the deck
+/// never runs out, so there can be any number of each around, but every
+/// card comes from somewhere in the deal and that's what this is made of.
+/// the same seed always gives the same cards the same ids, so they can be
+/// passed between games playing the same board.
+///
+/// ```text
+/// |-column-| |-left-| |-kind-|
+///  63    32  31     3  2    0
+/// ```
+///
+/// for the kinds without a column and a count, everything above the kind is
+/// one number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct CardId(u64);
+
+/// where a card came from, as told by its [`CardId`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// face up on `column` of the deal when it was dealt. columns of the
+    /// deal don't count inserted ones, so they stay put when some are.
+    Dealt(u64),
+    /// turned over on `column` of the deal, leaving `left` face down cards
+    /// under it
+    Revealed { column: u64, left: u32 },
+    /// dealt by reshuffle number `n` of the game
+    Reshuffled(u64),
+    /// card `n` of the stock
+    Drawn(u64),
+    /// the `n`th card put down by hand, in the sandbox or from the console
+    Placed(u64),
+    /// already there when a save from before cards had ids was loaded
+    Unknown(u64),
+}
+
+impl CardId {
+    const DEALT: u64 = 0;
+    const REVEALED: u64 = 1;
+    const RESHUFFLED: u64 = 2;
+    const DRAWN: u64 = 3;
+    const PLACED: u64 = 4;
+    const UNKNOWN: u64 = 5;
+
+    fn numbered(kind: u64, n: u64) -> Self {
+        debug_assert!(n < 1 << 61);
+        Self(n << 3 | kind)
+    }
+
+    pub(crate) fn dealt(column: u64) -> Self {
+        Self::numbered(Self::DEALT, column)
+    }
+
+    pub(crate) fn revealed(column: u64, left: u32) -> Self {
+        debug_assert!(column < 1 << 32 && left < 1 << 29);
+        Self(column << 32 | u64::from(left) << 3 | Self::REVEALED)
+    }
+
+    pub(crate) fn reshuffled(n: u64) -> Self {
+        Self::numbered(Self::RESHUFFLED, n)
+    }
+
+    pub(crate) fn drawn(n: u64) -> Self {
+        Self::numbered(Self::DRAWN, n)
+    }
+
+    pub(crate) fn placed(n: u64) -> Self {
+        Self::numbered(Self::PLACED, n)
+    }
+
+    pub(crate) fn unknown(n: u64) -> Self {
+        Self::numbered(Self::UNKNOWN, n)
+    }
+
+    pub fn origin(self) -> Origin {
+        let n = self.0 >> 3;
+        match self.0 & 0b111 {
+            Self::DEALT => Origin::Dealt(n),
+            Self::REVEALED => Origin::Revealed {
+                column: self.0 >> 32,
+                left: (n & ((1 << 29) - 1)) as u32,
+            },
+            Self::RESHUFFLED => Origin::Reshuffled(n),
+            Self::DRAWN => Origin::Drawn(n),
+            Self::PLACED => Origin::Placed(n),
+            _ => Origin::Unknown(n),
+        }
+    }
+
+    pub(crate) fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub(crate) fn from_bits(bits: u64) -> Option<Self> {
+        (bits & 0b111 <= Self::UNKNOWN).then_some(Self(bits))
+    }
+}
+
+/// ids are written in hex, like `1a8`
+impl fmt::Display for CardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}", self.0)
+    }
+}
+
+/// a run of face up cards, bottom card first
+#[derive(Clone)]
 pub struct CardStack {
     // since we only have at most 13 cards, a byte is small enough to store the size
     len: u8,
     // it is not possible to stack more than K -> A, so only 13 are needed
     cards: [BitCard; 13],
+    /// which card each one is, see [`CardId`]
+    ids: [CardId; 13],
 }
 
 impl CardStack {
@@ -87,6 +371,7 @@ impl CardStack {
         Self {
             len: 0,
             cards: [BitCard(0); 13],
+            ids: [CardId::default(); 13],
         }
     }
 
@@ -94,56 +379,87 @@ impl CardStack {
         self.len == 0
     }
 
-    pub fn can_stack(&self, top_card: BitCard) -> bool {
+    pub fn can_stack(&self, top_card: BitCard, deck: Deck) -> bool {
         if self.is_empty() {
             top_card.is_king()
         } else {
-            top_card.can_stack_on(self.cards[(self.len - 1) as usize])
+            top_card.can_stack_on(self.cards[(self.len - 1) as usize], deck)
         }
     }
 
-    fn one_random(rng: &mut impl Rng) -> Self {
-        let mut cards = [BitCard(0); 13];
-        cards[0] = BitCard::random(rng);
-        Self { len: 1, cards }
+    /// whether the cards from `idx` up each go on the one under them, so
+    /// they can be moved together
+    pub fn is_run_from(&self, idx: usize, deck: Deck) -> bool {
+        let cards = &self.cards[idx.min(self.len as usize)..self.len as usize];
+        cards
+            .windows(2)
+            .all(|pair| pair[1].can_stack_on(pair[0], deck))
+    }
+
+    /// how many cards from the top go on each other, and so can be moved
+    /// together
+    pub fn run_len(&self, deck: Deck) -> usize {
+        let len = self.len as usize;
+        (0..len)
+            .find(|idx| self.is_run_from(*idx, deck))
+            .map_or(0, |idx| len - idx)
+    }
+
+    fn one_random(rng: &mut impl Rng, deck: Deck, id: CardId) -> Self {
+        let mut stack = Self::empty();
+        stack.push(deck.random_card(rng), id);
+        stack
     }
 
     pub fn iter(&self) -> impl ExactSizeIterator<Item = BitCard> + '_ {
         self.cards[..(self.len as usize)].iter().cloned()
     }
 
+    /// the ids of the cards, in the same order as [`CardStack::iter`]
+    pub fn ids(&self) -> &[CardId] {
+        &self.ids[..self.len as usize]
+    }
+
+    pub(crate) fn ids_mut(&mut self) -> &mut [CardId] {
+        &mut self.ids[..self.len as usize]
+    }
+
     pub fn len(&self) -> u8 {
         self.len
     }
 
-    pub fn pop(&mut self) -> Option<BitCard> {
+    pub fn pop(&mut self) -> Option<(BitCard, CardId)> {
         if self.is_empty() {
             None
         } else {
             self.len -= 1;
-            Some(self.cards[self.len as usize])
+            let idx = self.len as usize;
+            Some((self.cards[idx], self.ids[idx]))
         }
     }
 
-    pub fn push(&mut self, card: BitCard) {
+    pub fn push(&mut self, card: BitCard, id: CardId) {
         debug_assert!(self.len < 13);
         self.cards[self.len as usize] = card;
+        self.ids[self.len as usize] = id;
         self.len += 1;
     }
 
     pub(crate) fn take_from(&mut self, other: &mut Self, visible_idx: usize) {
         debug_assert!(other.len as usize > visible_idx);
         let idx = visible_idx as u8;
-        let taken = &other.cards[visible_idx..other.len as usize];
+        let taken = visible_idx..other.len as usize;
         self.len = taken.len() as u8;
-        self.cards[..taken.len()].copy_from_slice(taken);
+        self.cards[..taken.len()].copy_from_slice(&other.cards[taken.clone()]);
+        self.ids[..taken.len()].copy_from_slice(&other.ids[taken]);
         other.len = idx;
     }
 
     pub(crate) fn append(&mut self, from: &mut Self) {
         debug_assert!(self.len + from.len <= 13);
-        self.cards[self.len as usize..(self.len + from.len) as usize]
-            .copy_from_slice(&from.cards[..from.len as usize]);
+        let to = self.len as usize..(self.len + from.len) as usize;
+        self.cards[to.clone()].copy_from_slice(&from.cards[..from.len as usize]);
+        self.ids[to].copy_from_slice(&from.ids[..from.len as usize]);
         self.len += from.len;
         from.len = 0;
     }
@@ -151,21 +467,73 @@ impl CardStack {
     pub fn top(&self) -> BitCard {
         self.cards[0]
     }
+
+    /// the card at the end of the stack, i.e. the one you can actually pick up
+    pub fn last(&self) -> Option<BitCard> {
+        self.iter().last()
+    }
 }
 
+/// one column of the tableau: a number of face down cards, which are only
+/// decided when they get revealed, and the face up stack on top of them
+#[derive(Clone)]
 pub struct Column {
     visible: CardStack,
+    /// how many face down cards are left
     pub under: u32,
+    /// face up cards that decayed back face down, with their ids, the last
+    /// one to go last. they're the top face down cards, and come back up
+    /// before anything dealt under them.
+    turned_under: Vec<(BitCard, CardId)>,
 }
 
 impl Column {
-    pub fn new(rng: &mut impl Rng, under: u32) -> Self {
+    /// a column as it was dealt, with its face up card called `id`
+    pub fn new(rng: &mut impl Rng, under: u32, deck: Deck, id: CardId) -> Self {
         Self {
-            visible: CardStack::one_random(rng),
+            visible: CardStack::one_random(rng, deck, id),
             under,
+            turned_under: Vec::new(),
         }
     }
 
+    /// a column with exactly these cards, for loading saved games
+    pub(crate) fn from_parts(under: u32, visible: CardStack) -> Self {
+        Self {
+            visible,
+            under,
+            turned_under: Vec::new(),
+        }
+    }
+
+    /// puts back the face up cards that had decayed, first to go first, for
+    /// loading saved games. there can't be more of them than face down
+    /// cards.
+    pub(crate) fn with_turned_under(
+        mut self,
+        turned_under: Vec<(BitCard, CardId)>,
+    ) -> Result<Self, &'static str> {
+        if turned_under.len() > self.under as usize {
+            return Err("more cards decayed than are face down");
+        }
+        self.turned_under = turned_under;
+        Ok(self)
+    }
+
+    /// the face down cards that were face up before they decayed, with
+    /// their ids, the last one to go last
+    pub fn turned_under(&self) -> &[(BitCard, CardId)] {
+        &self.turned_under
+    }
+
+    /// the face down card `row` cards up from the bottom, if it's one that
+    /// decayed and so is already known
+    pub fn turned_under_at(&self, row: u32) -> Option<BitCard> {
+        let first = self.under.checked_sub(self.turned_under.len() as u32)?;
+        let idx = row.checked_sub(first)?;
+        self.turned_under.get(idx as usize).map(|(card, _)| *card)
+    }
+
     pub fn is_visible_empty(&self) -> bool {
         self.visible.len == 0
     }
@@ -174,13 +542,38 @@ impl Column {
         &self.visible
     }
 
-    pub(crate) fn maybe_reveal_card(&mut self, rng: &mut impl Rng) {
-        if !(self.under > 0 && self.is_visible_empty()) {
-            return;
-        }
-        self.visible.cards[0] = BitCard::random(rng);
-        self.visible.len = 1;
+    /// whether every card the column was dealt has been played out of it,
+    /// with nothing face down left to turn over
+    pub fn is_finished(&self) -> bool {
+        self.under == 0 && self.is_visible_empty()
+    }
+
+    /// turns the top face down card over. it comes up as `card`, called
+    /// `id`, unless it's one that decayed, which comes back as it was.
+    /// returns the card that came up.
+    pub(crate) fn turn_over(&mut self, card: BitCard, id: CardId) -> BitCard {
+        let (card, id) = self.turned_under.pop().unwrap_or((card, id));
+        self.visible.push(card, id);
         self.under -= 1;
+        card
+    }
+
+    /// turns the bottom face up card back face down, the other way from
+    /// `turn_over`. it's kept, and is the next card turned over.
+    pub(crate) fn turn_under(&mut self) -> Option<BitCard> {
+        if self.visible.is_empty() {
+            return None;
+        }
+        let card = self.visible.top();
+        let id = self.visible.ids()[0];
+        let mut rest = CardStack::empty();
+        if self.visible.len > 1 {
+            rest.take_from(&mut self.visible, 1);
+        }
+        self.visible = rest;
+        self.under += 1;
+        self.turned_under.push((card, id));
+        Some(card)
     }
 
     pub fn append(&mut self, from: &mut CardStack) {
@@ -191,3 +584,79 @@ impl Column {
         &mut self.visible
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Move};
+    use crate::rules::Rules;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::HashSet;
+
+    fn is_stripped(card: BitCard) -> bool {
+        (1..=5).contains(&card.number())
+    }
+
+    #[test]
+    fn piquet_deals_32_cards() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let drawn: HashSet<u8> = (0..2000)
+            .map(|_| Deck::Piquet.random_card(&mut rng).bits())
+            .collect();
+        assert_eq!(drawn.len(), 32);
+        assert!(drawn.iter().all(|bits| !is_stripped(BitCard(*bits))));
+
+        let rules = Rules {
+            deck: Deck::Piquet,
+            ..Rules::default()
+        };
+        let game = Game::with_rules(1, 100, rules);
+        for (x, column) in game.tableau().iter() {
+            assert!(column.visible().iter().all(|card| !is_stripped(card)));
+            for row in 0..column.under {
+                assert!(!is_stripped(game.hidden_card(x, row).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn piquet_foundations_go_ace_then_seven() {
+        let deck = Deck::Piquet;
+        let ranks = Deck::PIQUET_RANKS;
+        for pair in ranks.windows(2) {
+            let under = BitCard::new(pair[0], Suit::Heart);
+            let card = BitCard::new(pair[1], Suit::Heart);
+            assert!(card.is_next_card(under, deck));
+        }
+        let ace = BitCard::new(0, Suit::Heart);
+        assert!(!BitCard::new(1, Suit::Heart).is_next_card(ace, deck));
+        assert!(!BitCard::new(7, Suit::Heart).is_next_card(ace, deck));
+        assert_eq!(deck.rank_index(12), 7);
+
+        let rules = Rules {
+            deck,
+            sandbox: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_rules(1, 10, rules);
+        for number in [7, 6, 0] {
+            assert!(game.place(0, BitCard::new(number, Suit::Heart)));
+        }
+        assert!(!game.place(0, BitCard::new(1, Suit::Heart)));
+        for _ in 0..3 {
+            let mv = Move::Foundation {
+                from: 0,
+                foundation: 0,
+            };
+            game.apply(mv).unwrap();
+        }
+        assert_eq!(game.founded(), 3);
+
+        // and the ace goes on the seven the other way round in the tableau
+        let mut column = CardStack::empty();
+        column.push(BitCard::new(6, Suit::Spade), CardId::placed(0));
+        assert!(column.can_stack(ace, deck));
+        assert!(!column.can_stack(ace, Deck::Full));
+    }
+}
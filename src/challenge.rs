@@ -0,0 +1,251 @@
+//! daily and weekly challenges. everyone playing on the same day (or in the
+//! same iso week, for weekly ones) gets the same deal with the same rules and
+//! a fixed amount of time to get as many cards onto the foundations as they
+//! can. days and weeks go by utc, so they change over at the same moment for
+//! everyone.
+//!
+//! results go on a leaderboard file, one line per game:
+//!
+//! ```text
+//! weekly 2026-W42 6ec6bb26f581214f 17 203
+//! ```
+//!
+//! which is the kind of challenge, which day or week it was, the key of the
+//! rules it was played with (see [`Rules::key`]), cards founded and moves
+//! made. more cards is better, and fewer moves breaks ties. lines from before
+//! the rules were written down are taken to be the normal rules.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::Fnv;
+use crate::rules::Rules;
+
+const DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    /// how long a game of the challenge lasts, in seconds
+    pub fn time_budget(self) -> f64 {
+        match self {
+            Period::Daily => 10.0 * 60.0,
+            Period::Weekly => 30.0 * 60.0,
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+        })
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(Period::Daily),
+            "weekly" => Ok(Period::Weekly),
+            _ => Err(format!("unknown challenge `{}`, try daily or weekly", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub period: Period,
+    /// which day or week it's for, like `2026-10-15` or `2026-W42`
+    pub name: String,
+    pub seed: u64,
+    /// when the next one starts, in seconds since the unix epoch
+    ends: i64,
+}
+
+impl Challenge {
+    /// the challenge going on right now
+    pub fn current(period: Period) -> Self {
+        Self::at(period, now())
+    }
+
+    /// the challenge going on `time` seconds after the unix epoch
+    pub fn at(period: Period, time: i64) -> Self {
+        let day = time.div_euclid(DAY);
+        let (name, ends) = match period {
+            Period::Daily => {
+                let (year, month, day_of_month) = civil_from_days(day);
+                let name = format!("{}-{:02}-{:02}", year, month, day_of_month);
+                (name, (day + 1) * DAY)
+            }
+            Period::Weekly => {
+                let (year, week, monday) = iso_week(day);
+                (format!("{}-W{:02}", year, week), (monday + 7) * DAY)
+            }
+        };
+        let mut hash = Fnv::new();
+        hash.write(format!("{} {}", period, name).as_bytes());
+        Self {
+            period,
+            name,
+            seed: hash.finish(),
+            ends,
+        }
+    }
+
+    /// every challenge is played with the normal rules
+    pub fn rules(&self) -> Rules {
+        Rules::default()
+    }
+
+    /// how many seconds are left before the next one starts
+    pub fn time_left(&self) -> i64 {
+        (self.ends - now()).max(0)
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} challenge {}", self.period, self.name)
+    }
+}
+
+/// how one game of a challenge went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub period: Period,
+    pub name: String,
+    /// the [`Rules::key`] of the rules the game was played with
+    pub variant: u64,
+    pub founded: usize,
+    pub moves: usize,
+}
+
+impl Entry {
+    /// whether this game was for `challenge`, under the same rules
+    pub fn is_for(&self, challenge: &Challenge) -> bool {
+        self.period == challenge.period
+            && self.name == challenge.name
+            && self.variant == challenge.rules().key()
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {:016x} {} {}",
+            self.period, self.name, self.variant, self.founded, self.moves
+        )
+    }
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (period, name, variant, founded, moves) = match words.as_slice() {
+            [period, name, variant, founded, moves] => {
+                let variant = u64::from_str_radix(variant, 16)
+                    .map_err(|_| format!("`{}` isn't a rules key", variant))?;
+                (period, name, variant, founded, moves)
+            }
+            [period, name, founded, moves] => {
+                (period, name, Rules::default().key(), founded, moves)
+            }
+            _ => {
+                return Err(format!(
+                    "`{}` should be: period name rules founded moves",
+                    s
+                ))
+            }
+        };
+        let number = |word: &str| {
+            word.parse()
+                .map_err(|_| format!("`{}` isn't a number", word))
+        };
+        Ok(Entry {
+            period: period.parse()?,
+            name: name.to_string(),
+            variant,
+            founded: number(founded)?,
+            moves: number(moves)?,
+        })
+    }
+}
+
+/// the best games of one challenge, best first. lines that can't be read
+/// are left out.
+pub fn leaderboard(path: impl AsRef<Path>, challenge: &Challenge) -> Vec<Entry> {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let mut entries: Vec<Entry> = text
+        .lines()
+        .filter_map(|line| line.parse().ok())
+        .filter(|entry: &Entry| entry.is_for(challenge))
+        .collect();
+    entries.sort_by_key(|entry| (std::cmp::Reverse(entry.founded), entry.moves));
+    entries
+}
+
+/// adds a game to the end of the leaderboard file
+pub fn record(path: impl AsRef<Path>, entry: &Entry) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", entry)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64)
+}
+
+/// the year, month and day `days` days after 1970-01-01, from
+/// http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// the other way around from `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// the iso year and week `days` after 1970-01-01 falls in, and the day its
+/// monday is. weeks belong to the year their thursday is in.
+fn iso_week(days: i64) -> (i64, u32, i64) {
+    // 1970-01-01 was a thursday
+    let weekday = (days + 3).rem_euclid(7);
+    let monday = days - weekday;
+    let (year, _, _) = civil_from_days(monday + 3);
+    let week = (monday + 3 - days_from_civil(year, 1, 1)) / 7 + 1;
+    (year, week as u32, monday)
+}
@@ -0,0 +1,494 @@
+//! headless subcommands, for poking at the game without opening a window
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use infiniteklondike::challenge::Period;
+use infiniteklondike::game::Game;
+use infiniteklondike::replay::Replay;
+use infiniteklondike::rules::Topology;
+use infiniteklondike::save::SaveFormat;
+use infiniteklondike::{simulate, solver};
+
+use crate::assist::Assists;
+use crate::hotseat::HotSeat;
+use crate::input::Script;
+use crate::layout::Orientation;
+use crate::settings::Settings;
+use crate::{atlas, config, preview, server};
+
+const USAGE: &str = "usage: infiniteklondike [command | options]
+
+with no command, starts the game. new games start with what settings.toml
+says, and the options below go on top of that.
+
+options:
+    --serve [addr]     serve the board and a stream of moves over http
+                       (default 127.0.0.1:7878)
+    --save-format <f>  write quicksaves as binary (default) or pretty json
+    --autosave-every <s>
+                       autosave every tab this often while playing (default
+                       60), or 0 to only autosave when the window closes
+    --autosave-keep <n>
+                       how many old autosaves of each tab to keep for the
+                       pause menu to go back to (default 5)
+    --pin-foundations  keep the foundations in a bar at the top of the window
+                       instead of out in the tableau
+    --foundations-at-bottom
+                       put the foundations along the bottom of the window
+    --vertical         lay the columns out top to bottom and scroll down
+                       instead of sideways, for portrait screens
+    --layout <l>       auto (default), desktop or mobile. mobile has bigger
+                       cards, the foundations docked at the bottom and
+                       scrolls by dragging the background. auto picks mobile
+                       when the window is taller than it is wide
+    --grid             experimental: piles on a grid going right and down,
+                       cards only move between neighbouring piles
+    --seed <n>         deal from this seed, so someone else starting with the
+                       same seed and rules gets the same board
+    --seeds-from <n>   pick the seeds of new games from a generator started at
+                       n instead of at random, so a whole session (or a
+                       --script run) deals the same games every time
+    --daily            play today's challenge: the same deal for everyone, ten
+                       minutes to found as many cards as possible
+    --weekly           the same for this week, with half an hour
+    --reveal <r>       what happens when a column runs out of face up cards:
+                       one (default) turns the next card over, chance:<p>
+                       only does p times out of a hundred, and many:<n>
+                       turns n over at once
+    --deck <d>         full (default) plays with all 52 cards, piquet with the
+                       32 card deck that has no twos to sixes
+    --grab <g>         drag (default) carries cards while the button is held
+                       and drops them where it's let go. click picks them up
+                       with one click and puts them down with the next
+    --scoring <s>      standard (default) gives 10 points for each card put on a
+                       foundation and 5 for each card turned over. vegas pays
+                       5 a card against a 52 point buy-in
+    --sandbox          unranked free play: any run goes on any column, c adds
+                       a card and delete removes one
+    --practice         unranked: face down cards are shown greyed out
+    --decay            hard mode: face up cards left alone on screen for 30
+                       seconds turn back face down, one at a time from the
+                       bottom, with a countdown for the last 10
+    --stock            classic stock and waste in the corner: click the stock
+                       (or press d) to turn a card over onto the waste
+    --draw-three       a stock that turns three cards over at a time, with
+                       only the last one playable
+    --warn-burying     ask before putting a long run on a card that could go
+                       on a foundation
+    --auto-arrange     a makes every move on screen that's the only useful one
+                       its column has, over and over
+    --auto-chain       after a card goes on a foundation, the next cards of its
+                       suit on the tops of the columns on screen follow it
+    --precision [f]    slow the mouse down to f times its speed (default
+                       0.35) while carrying cards over the columns, for
+                       placing them with an unsteady hand
+    --monochrome       black and white only, with red suits dotted instead
+                       of coloured and no animation, for e-ink screens
+    --suit-marks <m>   pips (default), letters or shapes. letters and shapes
+                       draw the suit big on every card, for when the pips
+                       are too small to tell apart
+    --four-colors      green clubs and blue diamonds, so every suit has its
+                       own colour instead of just red and white
+    --corner-badges    the suit's shape small in the top right corner of
+                       every card, next to the rank
+    --card-back <b>    pattern (default), infinity, stripes or checks: which
+                       back face down cards have
+    --table <t>        black (default), felt, navy or wine: the colour of the
+                       table under the cards
+    --run-badges       a badge on the top card of each column with how many
+                       cards can be moved along with it, coloured by length
+    --power-save       slow down to a few frames a second while nothing is
+                       happening, for leaving the game open in the background
+    --low-memory       keep as little in memory as possible, for small machines
+                       like a raspberry pi. turns off card history
+    --stream <dir>     write the columns played on to a directory of their
+                       own under <dir> for each game as you go, instead of
+                       keeping them all in memory
+    --hot-seat         two players take turns on the same board, each
+                       founding cards and scoring for themselves
+    --turn-length <s>  how many seconds a hot seat turn lasts (default 60)
+    --idle-after <s>   stop the session timer after this many seconds
+                       without any input (default 60)
+    --pack <dir>       load cards.png from this asset pack folder instead,
+                       with its manifest.txt saying where the sprites are
+                       (default pack). see src/atlas.rs
+    --script <file>    play back recorded mouse and keyboard input on top of
+                       the real input, for testing. see src/input.rs
+
+commands:
+    verify <replay>    re-simulate a replay from its seed and check every move
+    solve --seed <n> [--columns <a..b>] [--states <n>]
+                       search a window of columns for foundation plays
+    render --seed <n> [--columns <k>] [--out <png>] [--pack <dir>]
+                       draw the first columns of a seed to an image
+    simulate [--seeds <n>] [--first-seed <n>] [--columns <n>] [--region <n>]
+                       deal lots of seeds and print statistics about them";
+
+/// how the game was asked to start
+#[derive(Default)]
+pub struct Options {
+    /// where to serve the board from, if at all
+    pub serve: Option<String>,
+    pub save_format: SaveFormat,
+    pub settings: Settings,
+    /// what went wrong reading the settings file, for toasts once the
+    /// window is up
+    pub settings_errors: Vec<String>,
+    /// whether to sleep between frames while idle
+    pub power_save: bool,
+    /// input to play back, for testing
+    pub script: Option<Script>,
+    /// seconds between autosaves, if not the usual
+    pub autosave_every: Option<f64>,
+    /// how many old autosaves of each tab to keep, if not the usual
+    pub autosave_keep: Option<usize>,
+    /// what to start the generator new games get their seeds from at,
+    /// instead of picking them at random
+    pub seeds_from: Option<u64>,
+    /// where games keep the chunks of the board they've let go of, if
+    /// they're streamed to disk
+    pub stream: Option<PathBuf>,
+    /// the folder to load an asset pack from, if it has one
+    pub pack: PathBuf,
+}
+
+/// reads the options for starting the game normally
+pub fn options(args: &[String]) -> Options {
+    let (settings, settings_errors) = config::load();
+    let mut options = Options {
+        settings,
+        settings_errors,
+        ..Options::default()
+    };
+    if let Some(idx) = args.iter().position(|arg| arg == "--serve") {
+        let addr = args.get(idx + 1).filter(|addr| !addr.starts_with("--"));
+        options.serve = Some(addr.map_or(server::DEFAULT_ADDR, String::as_str).to_owned());
+    }
+    options.settings.layout.pin_foundations |= args.iter().any(|arg| arg == "--pin-foundations");
+    if args.iter().any(|arg| arg == "--vertical") {
+        options.settings.layout.orientation = Orientation::Vertical;
+    }
+    if let Some(profile) = flag(args, "layout") {
+        match profile.parse() {
+            Ok(profile) => options.settings.layout.profile = profile,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if args.iter().any(|arg| arg == "--grid") {
+        options.settings.layout.orientation = Orientation::Grid;
+        options.settings.rules.topology = Topology::Grid;
+    }
+    if let Some(seed) = flag(args, "seed") {
+        match seed.parse() {
+            Ok(seed) => options.settings.seed = Some(seed),
+            Err(_) => eprintln!("`{}` isn't a seed, seeds are whole numbers", seed),
+        }
+    }
+    if args.iter().any(|arg| arg == "--daily") {
+        options.settings.challenge = Some(Period::Daily);
+    }
+    if args.iter().any(|arg| arg == "--weekly") {
+        options.settings.challenge = Some(Period::Weekly);
+    }
+    if let Some(reveal) = flag(args, "reveal") {
+        match reveal.parse() {
+            Ok(reveal) => options.settings.rules.reveal = reveal,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(deck) = flag(args, "deck") {
+        match deck.parse() {
+            Ok(deck) => options.settings.rules.deck = deck,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(grab) = flag(args, "grab") {
+        match grab.parse() {
+            Ok(grab) => options.settings.grab = grab,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(scoring) = flag(args, "scoring") {
+        match scoring.parse() {
+            Ok(scoring) => options.settings.scoring = scoring,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    options.settings.rules.sandbox |= args.iter().any(|arg| arg == "--sandbox");
+    options.settings.rules.practice |= args.iter().any(|arg| arg == "--practice");
+    options.settings.rules.decay |= args.iter().any(|arg| arg == "--decay");
+    options.settings.rules.draw_three |= args.iter().any(|arg| arg == "--draw-three");
+    options.settings.rules.stock |=
+        options.settings.rules.draw_three || args.iter().any(|arg| arg == "--stock");
+    options.settings.assists.warn_burying |= args.iter().any(|arg| arg == "--warn-burying");
+    options.settings.assists.auto_arrange |= args.iter().any(|arg| arg == "--auto-arrange");
+    options.settings.assists.auto_chain |= args.iter().any(|arg| arg == "--auto-chain");
+    if args.iter().any(|arg| arg == "--precision") {
+        let mut factor = Assists::PRECISION;
+        match flag(args, "precision") {
+            Some(value) if !value.starts_with("--") => match value.parse::<f32>() {
+                Ok(value) if value > 0.0 && value <= 1.0 => factor = value,
+                _ => eprintln!("`{}` isn't a speed between 0 and 1", value),
+            },
+            _ => {}
+        }
+        options.settings.assists.precision = Some(factor);
+    }
+    options.settings.style.monochrome |= args.iter().any(|arg| arg == "--monochrome");
+    options.settings.style.four_colors |= args.iter().any(|arg| arg == "--four-colors");
+    options.settings.style.corner_badges |= args.iter().any(|arg| arg == "--corner-badges");
+    options.settings.style.run_badges |= args.iter().any(|arg| arg == "--run-badges");
+    if let Some(back) = flag(args, "card-back") {
+        match back.parse() {
+            Ok(back) => options.settings.style.card_back = back,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(table) = flag(args, "table") {
+        match table.parse() {
+            Ok(table) => options.settings.style.table = table,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(marks) = flag(args, "suit-marks") {
+        match marks.parse() {
+            Ok(marks) => options.settings.style.suit_marks = marks,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    options.power_save = args.iter().any(|arg| arg == "--power-save");
+    options.settings.low_memory |= args.iter().any(|arg| arg == "--low-memory");
+    options.stream = flag(args, "stream").map(PathBuf::from);
+    options.pack = PathBuf::from(flag(args, "pack").unwrap_or(atlas::DEFAULT_PACK));
+    if args.iter().any(|arg| arg == "--hot-seat") {
+        let mut turn_length = HotSeat::TURN_LENGTH;
+        if let Some(seconds) = flag(args, "turn-length") {
+            match seconds.parse::<f64>() {
+                Ok(seconds) if seconds > 0.0 => turn_length = seconds,
+                _ => eprintln!("`{}` isn't a number of seconds", seconds),
+            }
+        }
+        options.settings.hot_seat = Some(turn_length);
+    }
+    if let Some(seconds) = flag(args, "idle-after") {
+        match seconds.parse::<f64>() {
+            Ok(seconds) if seconds > 0.0 => options.settings.idle_after = seconds,
+            _ => eprintln!("`{}` isn't a number of seconds", seconds),
+        }
+    }
+    options.settings.layout.foundations_at_bottom |=
+        args.iter().any(|arg| arg == "--foundations-at-bottom");
+    if let Some(seconds) = flag(args, "autosave-every") {
+        match seconds.parse::<f64>() {
+            Ok(seconds) if seconds >= 0.0 => options.autosave_every = Some(seconds),
+            _ => eprintln!("`{}` isn't a number of seconds", seconds),
+        }
+    }
+    if let Some(start) = flag(args, "seeds-from") {
+        match start.parse() {
+            Ok(start) => options.seeds_from = Some(start),
+            Err(_) => eprintln!("`{}` isn't a seed, seeds are whole numbers", start),
+        }
+    }
+    if let Some(keep) = flag(args, "autosave-keep") {
+        match keep.parse() {
+            Ok(keep) => options.autosave_keep = Some(keep),
+            Err(_) => eprintln!("`{}` isn't a number of autosaves", keep),
+        }
+    }
+    if let Some(format) = flag(args, "save-format") {
+        match format.parse() {
+            Ok(format) => options.save_format = format,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if let Some(path) = flag(args, "script") {
+        match Script::load(path) {
+            Ok(script) => options.script = Some(script),
+            Err(e) => eprintln!("could not read script {}: {}", path, e),
+        }
+    }
+    options
+}
+
+/// runs a subcommand if one was given, returning the exit code.
+/// returns `None` when the game should start normally.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    if command.starts_with("--") && command != "--help" {
+        return None;
+    }
+    Some(match command.as_str() {
+        "verify" => verify(rest),
+        "solve" => solve(rest),
+        "render" => render(rest),
+        "simulate" => simulate(rest),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            0
+        }
+        _ => {
+            eprintln!("unknown command `{}`\n\n{}", command, USAGE);
+            2
+        }
+    })
+}
+
+fn verify(args: &[String]) -> i32 {
+    let path = match args {
+        [path] => path,
+        _ => {
+            eprintln!("usage: infiniteklondike verify <replay>");
+            return 2;
+        }
+    };
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("could not read {}: {}", path, e);
+            return 1;
+        }
+    };
+    match replay.verify() {
+        Ok(game) => {
+            println!(
+                "ok: {} moves, {} cards on foundations, hash {:016x}",
+                replay.moves.len(),
+                game.founded(),
+                replay.hash
+            );
+            0
+        }
+        Err(e) => {
+            println!("invalid: {}", e);
+            1
+        }
+    }
+}
+
+/// the value after `--name`, if it was given
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg.strip_prefix("--") == Some(name))
+        .and_then(|idx| args.get(idx + 1))
+        .map(String::as_str)
+}
+
+/// parses `--name`, falling back to `default` if it isn't there
+fn parse_flag<T: FromStr>(args: &[String], name: &str, default: Option<T>) -> Result<T, String> {
+    match flag(args, name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("bad value for --{}: {}", name, value)),
+        None => default.ok_or_else(|| format!("missing --{}", name)),
+    }
+}
+
+/// parses `a..b`
+fn parse_range(text: &str) -> Option<Range<usize>> {
+    let (start, end) = text.split_once("..")?;
+    let range = start.parse().ok()?..end.parse().ok()?;
+    (!range.is_empty()).then_some(range)
+}
+
+fn solve(args: &[String]) -> i32 {
+    let seed = parse_flag::<u64>(args, "seed", None);
+    let window = match flag(args, "columns") {
+        Some(text) => parse_range(text).ok_or(format!("bad value for --columns: {}", text)),
+        None => Ok(0..50),
+    };
+    let states = parse_flag(args, "states", Some(200_000));
+    let (seed, window, states) = match (seed, window, states) {
+        (Ok(seed), Ok(window), Ok(states)) => (seed, window, states),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            eprintln!(
+                "{}\nusage: infiniteklondike solve --seed <n> [--columns <a..b>] [--states <n>]",
+                e
+            );
+            return 2;
+        }
+    };
+    let mut game = Game::new(seed, 0);
+    game.ensure_range(window.clone());
+    let solution = solver::solve(&game, window.clone(), states);
+    let dealt: usize = window
+        .clone()
+        .map(|x| game.tableau()[x].under as usize + 1)
+        .sum();
+    println!(
+        "columns {}..{}: {} of {} cards reachable on foundations in {} moves",
+        window.start,
+        window.end,
+        solution.founded,
+        dealt,
+        solution.moves.len()
+    );
+    println!(
+        "explored {} positions{}",
+        solution.explored,
+        if solution.exhausted {
+            ""
+        } else {
+            " (gave up early, there may be better)"
+        }
+    );
+    0
+}
+
+fn render(args: &[String]) -> i32 {
+    let seed = parse_flag::<u64>(args, "seed", None);
+    let columns = parse_flag(args, "columns", Some(10));
+    let out = flag(args, "out").unwrap_or("preview.png");
+    let (seed, columns) = match (seed, columns) {
+        (Ok(seed), Ok(columns)) => (seed, columns),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!(
+                "{}\nusage: infiniteklondike render --seed <n> [--columns <k>] [--out <png>]",
+                e
+            );
+            return 2;
+        }
+    };
+    let pack = Path::new(flag(args, "pack").unwrap_or(atlas::DEFAULT_PACK));
+    let (atlas, sprites) = match atlas::load_image(pack) {
+        Ok(atlas) => atlas,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let game = Game::new(seed, columns);
+    match preview::render(&game, &atlas, &sprites, columns).save(out) {
+        Ok(()) => {
+            println!("wrote {}", out);
+            0
+        }
+        Err(e) => {
+            eprintln!("could not write {}: {}", out, e);
+            1
+        }
+    }
+}
+
+fn simulate(args: &[String]) -> i32 {
+    let seeds = parse_flag(args, "seeds", Some(1000));
+    let first_seed = parse_flag(args, "first-seed", Some(0));
+    let columns = parse_flag(args, "columns", Some(100));
+    let region = parse_flag(args, "region", Some(10));
+    match (seeds, first_seed, columns, region) {
+        (Ok(seeds), Ok(first_seed), Ok(columns), Ok(region)) if region > 0 => {
+            print!("{}", simulate::simulate(first_seed, seeds, columns, region));
+            0
+        }
+        (Err(e), ..) | (_, Err(e), ..) | (.., Err(e), _) | (.., Err(e)) => {
+            eprintln!("{}", e);
+            2
+        }
+        _ => {
+            eprintln!("--region has to be at least 1");
+            2
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! the settings file. `settings.toml` has what games start with, before the
+//! command line gets its say, and is written back when settings change in
+//! game. it's the bit of toml that's needed: `[sections]`, `key = value`
+//! lines with quoted strings, numbers or true and false, and `#` comments.
+//! when there isn't one, one is written with the defaults to be edited.
+//!
+//! ```toml
+//! grab = "drag"
+//!
+//! [layout]
+//! card_scale = 1.2
+//! invert_pan = true
+//!
+//! [rules]
+//! reveal = "many:3"
+//! ```
+
+use std::io;
+use std::str::FromStr;
+
+use crate::layout::Orientation;
+use crate::settings::Settings;
+
+/// where the settings are kept
+const SETTINGS_PATH: &str = "settings.toml";
+
+/// the settings from the file, or the defaults for anything it doesn't say,
+/// and what went wrong reading it. lines that can't be read are skipped.
+/// this runs before there's a window, so the errors are toasted later.
+pub fn load() -> (Settings, Vec<String>) {
+    let mut settings = Settings::default();
+    let mut errors = Vec::new();
+    match std::fs::read_to_string(SETTINGS_PATH) {
+        Ok(text) => {
+            for (line, e) in apply(&mut settings, &text) {
+                errors.push(format!("{} line {}: {}", SETTINGS_PATH, line, e));
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if let Err(e) = save(&settings) {
+                errors.push(format!("could not write {}: {}", SETTINGS_PATH, e));
+            }
+        }
+        Err(e) => errors.push(format!("could not read {}: {}", SETTINGS_PATH, e)),
+    }
+    (settings, errors)
+}
+
+/// writes `settings` to the file. only the ones that make sense to keep
+/// between runs are written: not the seed, the challenge or hot seat.
+pub fn save(settings: &Settings) -> io::Result<()> {
+    std::fs::write(SETTINGS_PATH, to_toml(settings))
+}
+
+/// reads `text` into `settings`, returning the lines it couldn't
+fn apply(settings: &mut Settings, text: &str) -> Vec<(usize, String)> {
+    let mut errors = Vec::new();
+    let mut section = String::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let result = if let Some(name) = line.strip_prefix('[') {
+            match name.strip_suffix(']') {
+                Some(name) => {
+                    section = name.trim().to_owned();
+                    Ok(())
+                }
+                None => Err("a section needs a closing `]`".to_owned()),
+            }
+        } else if let Some((key, value)) = line.split_once('=') {
+            unquote(value.trim()).and_then(|value| set(settings, &section, key.trim(), value))
+        } else {
+            Err(format!("expected `key = value`, got `{}`", line))
+        };
+        if let Err(e) = result {
+            errors.push((idx + 1, e));
+        }
+    }
+    errors
+}
+
+/// `line` without a `#` comment, leaving any in quotes alone
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// a value without the quotes around it, if it's a string
+fn unquote(value: &str) -> Result<&str, String> {
+    match value.strip_prefix('"') {
+        Some(rest) => rest
+            .strip_suffix('"')
+            .ok_or_else(|| format!("missing the closing quote in {}", value)),
+        None => Ok(value),
+    }
+}
+
+/// parses `value` for `key`
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("bad value for {}: {}", key, value))
+}
+
+/// parses a number for `key` that has to be more than 0
+fn positive<T: FromStr + PartialOrd + Default>(key: &str, value: &str) -> Result<T, String> {
+    match parse(key, value)? {
+        value if value > T::default() => Ok(value),
+        _ => Err(format!("{} has to be more than 0", key)),
+    }
+}
+
+/// parses a volume, from 0 to 1
+fn volume(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(volume) if (0.0..=1.0).contains(&volume) => Ok(volume),
+        _ => Err(format!("`{}` isn't a volume between 0 and 1", value)),
+    }
+}
+
+/// sets `key` in `section` to `value`
+fn set(settings: &mut Settings, section: &str, key: &str, value: &str) -> Result<(), String> {
+    let layout = &mut settings.layout;
+    let rules = &mut settings.rules;
+    let style = &mut settings.style;
+    let assists = &mut settings.assists;
+    match (section, key) {
+        ("", "grab") => settings.grab = value.parse()?,
+        ("", "scoring") => settings.scoring = value.parse()?,
+        ("", "idle_after") => settings.idle_after = positive(key, value)?,
+        ("", "low_memory") => settings.low_memory = parse(key, value)?,
+        ("", "volume") => settings.volume = volume(value)?,
+        ("", "music") => settings.music = volume(value)?,
+        ("layout", "profile") => layout.profile = value.parse()?,
+        ("layout", "vertical") => {
+            if parse(key, value)? {
+                layout.orientation = Orientation::Vertical;
+            }
+        }
+        ("layout", "card_scale") => layout.card_scale = positive(key, value)?,
+        ("layout", "pan_speed") => layout.pan_speed = positive(key, value)?,
+        ("layout", "invert_pan") => layout.invert_pan = parse(key, value)?,
+        ("layout", "pin_foundations") => layout.pin_foundations = parse(key, value)?,
+        ("layout", "foundations_at_bottom") => layout.foundations_at_bottom = parse(key, value)?,
+        ("rules", "stock") => rules.stock = parse(key, value)?,
+        ("rules", "draw_three") => {
+            rules.draw_three = parse(key, value)?;
+            rules.stock |= rules.draw_three;
+        }
+        ("rules", "deck") => rules.deck = value.parse()?,
+        ("rules", "reveal") => rules.reveal = value.parse()?,
+        ("rules", "decay") => rules.decay = parse(key, value)?,
+        ("rules", "practice") => rules.practice = parse(key, value)?,
+        ("rules", "sandbox") => rules.sandbox = parse(key, value)?,
+        ("style", "monochrome") => style.monochrome = parse(key, value)?,
+        ("style", "suit_marks") => style.suit_marks = value.parse()?,
+        ("style", "four_colors") => style.four_colors = parse(key, value)?,
+        ("style", "corner_badges") => style.corner_badges = parse(key, value)?,
+        ("style", "run_badges") => style.run_badges = parse(key, value)?,
+        ("style", "card_back") => style.card_back = value.parse()?,
+        ("style", "table") => style.table = value.parse()?,
+        ("assists", "warn_burying") => assists.warn_burying = parse(key, value)?,
+        ("assists", "auto_arrange") => assists.auto_arrange = parse(key, value)?,
+        ("assists", "auto_chain") => assists.auto_chain = parse(key, value)?,
+        ("assists", "precision") => {
+            assists.precision = match value {
+                "false" => None,
+                _ => match parse::<f32>(key, value)? {
+                    factor if factor > 0.0 && factor <= 1.0 => Some(factor),
+                    _ => return Err(format!("`{}` isn't a speed between 0 and 1", value)),
+                },
+            }
+        }
+        ("", _) => return Err(format!("unknown setting `{}`", key)),
+        _ => return Err(format!("unknown setting `{}` in [{}]", key, section)),
+    }
+    Ok(())
+}
+
+/// the file for `settings`
+fn to_toml(settings: &Settings) -> String {
+    let Settings {
+        layout,
+        rules,
+        style,
+        assists,
+        ..
+    } = settings;
+    let precision = match assists.precision {
+        Some(factor) => factor.to_string(),
+        None => "false".to_owned(),
+    };
+    format!(
+        "# what new games start with, see the readme for what each one does
+grab = \"{}\"
+scoring = \"{}\"
+idle_after = {}
+low_memory = {}
+volume = {}
+music = {}
+
+[layout]
+profile = \"{}\"
+vertical = {}
+card_scale = {}
+pan_speed = {}
+invert_pan = {}
+pin_foundations = {}
+foundations_at_bottom = {}
+
+[rules]
+stock = {}
+draw_three = {}
+deck = \"{}\"
+reveal = \"{}\"
+decay = {}
+practice = {}
+sandbox = {}
+
+[style]
+monochrome = {}
+suit_marks = \"{}\"
+four_colors = {}
+corner_badges = {}
+run_badges = {}
+card_back = \"{}\"
+table = \"{}\"
+
+[assists]
+warn_burying = {}
+auto_arrange = {}
+auto_chain = {}
+precision = {}
+",
+        settings.grab,
+        settings.scoring,
+        settings.idle_after,
+        settings.low_memory,
+        settings.volume,
+        settings.music,
+        layout.profile,
+        layout.orientation == Orientation::Vertical,
+        layout.card_scale,
+        layout.pan_speed,
+        layout.invert_pan,
+        layout.pin_foundations,
+        layout.foundations_at_bottom,
+        rules.stock,
+        rules.draw_three,
+        rules.deck,
+        rules.reveal,
+        rules.decay,
+        rules.practice,
+        rules.sandbox,
+        style.monochrome,
+        style.suit_marks,
+        style.four_colors,
+        style.corner_badges,
+        style.run_badges,
+        style.card_back,
+        style.table,
+        assists.warn_burying,
+        assists.auto_arrange,
+        assists.auto_chain,
+        precision,
+    )
+}
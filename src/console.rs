@@ -0,0 +1,174 @@
+//! a console that drops down from the top of the window with ~, for poking
+//! at the game while working on it. only there in debug builds.
+
+use infiniteklondike::cards::BitCard;
+use macroquad::prelude::*;
+
+use crate::input::{get_char_pressed, is_key_pressed};
+use crate::State;
+
+const HELP: &str = "seed | goto <column> | give <card> col <column> | reveal <column>|all | stats";
+
+/// something typed into the console
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Command {
+    /// print the seed
+    Seed,
+    /// move the camera to a column
+    Goto(usize),
+    /// put a card on a column, ignoring the rules
+    Give {
+        card: BitCard,
+        column: usize,
+    },
+    /// turn over the next face down card of a column, or of every column on
+    /// screen
+    Reveal(Option<usize>),
+    Stats,
+    Help,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<usize>()
+                .map_err(|_| format!("`{}` isn't a column", word))
+        };
+        match words.as_slice() {
+            ["seed"] => Ok(Command::Seed),
+            ["goto", column] => Ok(Command::Goto(number(column)?)),
+            ["give", card, "col", column] | ["give", card, column] => Ok(Command::Give {
+                card: card
+                    .to_uppercase()
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a card, try 7H or QS", card))?,
+                column: number(column)?,
+            }),
+            ["reveal", "all"] => Ok(Command::Reveal(None)),
+            ["reveal", column] => Ok(Command::Reveal(Some(number(column)?))),
+            ["stats"] => Ok(Command::Stats),
+            ["help"] => Ok(Command::Help),
+            _ => Err(format!("unknown command `{}`, try help", line)),
+        }
+    }
+}
+
+impl State {
+    /// does a console command, returning what to print
+    fn run_command(&mut self, command: Command) -> String {
+        match command {
+            Command::Seed => self.game.seed().to_string(),
+            Command::Goto(column) => {
+                self.jump_to(column);
+                format!("went to column {}", column)
+            }
+            Command::Give { card, column } => {
+                self.game.ensure_columns(column + 1);
+                if self.game.place(column, card) {
+                    self.publish(&[]);
+                    format!("put {} on column {}", card, column)
+                } else {
+                    format!("column {} is full", column)
+                }
+            }
+            Command::Reveal(column) => {
+                let columns = match column {
+                    Some(column) => {
+                        self.game.ensure_columns(column + 1);
+                        vec![column]
+                    }
+                    None => self.layout.visible_columns(self.camera),
+                };
+                let revealed = columns
+                    .into_iter()
+                    .filter(|column| self.game.force_reveal(*column).is_some())
+                    .count();
+                self.publish(&[]);
+                format!("turned over {} cards", revealed)
+            }
+            Command::Stats => format!(
+                "{} of {} columns dealt, {} cards on foundations, {} moves, hash {:016x}",
+                self.game.tableau().dealt(),
+                self.game.columns(),
+                self.game.founded(),
+                self.moves.len(),
+                self.game.state_hash()
+            ),
+            Command::Help => HELP.to_owned(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Console {
+    open: bool,
+    line: String,
+    /// everything typed and printed so far, oldest first
+    log: Vec<String>,
+}
+
+impl Console {
+    const HEIGHT: f32 = 240.0;
+    const LINE_HEIGHT: f32 = 18.0;
+
+    /// handles input, returning whether the console has the keyboard
+    pub fn update(&mut self, state: &mut State) -> bool {
+        if !cfg!(debug_assertions) {
+            return false;
+        }
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+            // the ~ that opened it shouldn't end up typed
+            while get_char_pressed().is_some() {}
+            return true;
+        }
+        if !self.open {
+            return false;
+        }
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                self.line.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            let line = std::mem::take(&mut self.line);
+            if !line.trim().is_empty() {
+                let output = match Command::parse(&line) {
+                    Ok(command) => state.run_command(command),
+                    Err(e) => e,
+                };
+                self.log.push(format!("> {}", line));
+                self.log.push(output);
+            }
+        }
+        true
+    }
+
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            Self::HEIGHT,
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        let prompt_y = Self::HEIGHT - 8.0;
+        draw_text(&format!("> {}_", self.line), 8.0, prompt_y, 20.0, WHITE);
+        for (n, line) in self.log.iter().rev().enumerate() {
+            let y = prompt_y - Self::LINE_HEIGHT * (n + 1) as f32;
+            if y < Self::LINE_HEIGHT {
+                break;
+            }
+            draw_text(line, 8.0, y, 18.0, GRAY);
+        }
+    }
+}
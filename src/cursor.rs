@@ -0,0 +1,63 @@
+//! the mouse pointer. the system one is hidden while playing and this one
+//! is drawn instead, changing shape to show what the mouse would do.
+
+use macroquad::prelude::*;
+
+use crate::input::mouse_position;
+use crate::style::Style;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cursor {
+    /// nothing to pick up, or a menu is open
+    Arrow,
+    /// over cards that can be picked up
+    Open,
+    /// carrying cards over somewhere they can go
+    Grabbing,
+    /// carrying cards over somewhere they can't go
+    Invalid,
+}
+
+impl Cursor {
+    /// draws the cursor at the mouse, on top of everything else
+    pub fn draw(self, style: Style) {
+        let (x, y) = mouse_position();
+        let (fill, outline) = (WHITE, BLACK);
+        match self {
+            Cursor::Arrow => {
+                let (a, b, c) = (vec2(x, y), vec2(x, y + 18.0), vec2(x + 12.0, y + 13.0));
+                draw_triangle(a, b, c, fill);
+                draw_triangle_lines(a, b, c, 1.5, outline);
+            }
+            Cursor::Open => {
+                // a palm with the fingers spread
+                let fingers = (0..4).map(|finger| x - 6.0 + 4.0 * finger as f32);
+                draw_circle(x, y + 4.0, 7.0, outline);
+                for fx in fingers.clone() {
+                    draw_rectangle(fx - 1.5, y - 8.0, 5.0, 10.0, outline);
+                }
+                // the fills go over every outline so they join up
+                draw_circle(x, y + 4.0, 6.0, fill);
+                for fx in fingers {
+                    draw_rectangle(fx - 0.5, y - 7.0, 3.0, 9.0, fill);
+                }
+            }
+            Cursor::Grabbing => {
+                // the fingers curled into a fist
+                draw_circle(x, y, 8.0, outline);
+                draw_circle(x, y, 7.0, fill);
+                for knuckle in 0..3 {
+                    let kx = x - 4.0 + 4.0 * knuckle as f32;
+                    draw_line(kx, y - 6.0, kx, y - 2.0, 1.0, outline);
+                }
+            }
+            Cursor::Invalid => {
+                let color = if style.monochrome { fill } else { RED };
+                for (dx, dy) in [(7.0, 7.0), (7.0, -7.0)] {
+                    draw_line(x - dx, y - dy, x + dx, y + dy, 5.0, outline);
+                    draw_line(x - dx, y - dy, x + dx, y + dy, 3.0, color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! the clocks for `--decay` games. every column on screen with more than
+//! one face up card has one, and when it runs out the bottom face up card
+//! turns back face down. anything happening to a column starts its clock
+//! over, and columns off screen keep theirs stopped, so the way to stay
+//! ahead is to keep moving along the board. the last few seconds count down
+//! on the card that's about to go.
+
+use std::collections::HashMap;
+
+use infiniteklondike::tableau::Tableau;
+
+/// a column's clock
+struct Clock {
+    /// seconds until the column decays
+    left: f64,
+    /// the face down and face up counts when the clock started, so it can
+    /// tell when the column's been touched
+    under: u32,
+    visible: u8,
+}
+
+#[derive(Default)]
+pub struct Decay {
+    clocks: HashMap<usize, Clock>,
+}
+
+impl Decay {
+    /// how long a column can be left alone
+    const AFTER: f64 = 30.0;
+    /// how long before it decays the countdown shows
+    const WARN: f64 = 10.0;
+
+    /// runs the clocks of `columns` on `tableau` for `elapsed` seconds,
+    /// returning the columns whose clocks ran out
+    pub fn update(&mut self, tableau: &Tableau, columns: &[usize], elapsed: f64) -> Vec<usize> {
+        let mut out = Vec::new();
+        for &column in columns {
+            let Some(stack) = tableau.get(column) else {
+                continue;
+            };
+            let visible = stack.visible().len();
+            if visible < 2 {
+                self.clocks.remove(&column);
+                continue;
+            }
+            let clock = self.clocks.entry(column).or_insert(Clock {
+                left: Self::AFTER,
+                under: stack.under,
+                visible,
+            });
+            if (clock.under, clock.visible) != (stack.under, visible) {
+                *clock = Clock {
+                    left: Self::AFTER,
+                    under: stack.under,
+                    visible,
+                };
+                continue;
+            }
+            clock.left -= elapsed;
+            if clock.left <= 0.0 {
+                // the column changing starts it again next frame
+                out.push(column);
+            }
+        }
+        out
+    }
+
+    /// the whole seconds left on `column`'s clock, once it's close enough to
+    /// show
+    pub fn countdown(&self, column: usize) -> Option<u32> {
+        let clock = self.clocks.get(&column)?;
+        (clock.left < Self::WARN).then(|| clock.left.max(0.0).ceil() as u32)
+    }
+}
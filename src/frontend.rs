@@ -0,0 +1,157 @@
+//! input from any frontend, boiled down to the same few events. each
+//! frontend turns whatever it reads (macroquad's mouse and keyboard, bytes
+//! from a terminal, a script of recorded input, a replay) into
+//! [`InputEvent`]s through an [`InputSource`], and the parts that only care
+//! about what the player did, like telling a double click from two single
+//! ones, live in [`Gestures`] instead of being worked out again by every
+//! frontend.
+
+use std::collections::VecDeque;
+
+use crate::game::Move;
+use crate::replay::Replay;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Primary,
+    Secondary,
+    Middle,
+}
+
+/// a key, by what it's for rather than where it is on the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// enter or space
+    Select,
+    /// escape
+    Cancel,
+    Quit,
+    /// anything typed
+    Char(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// the pointer is now at `x`, `y`, in whatever the frontend measures its
+    /// screen in
+    Pointer {
+        x: f32,
+        y: f32,
+    },
+    PointerDown(Button),
+    PointerUp(Button),
+    KeyDown(Key),
+    KeyUp(Key),
+    /// a whole move decided somewhere else, like in a replay
+    Play(Move),
+}
+
+/// somewhere input comes from
+pub trait InputSource {
+    /// everything that happened since the last call. `now` is in seconds,
+    /// counted from whenever the frontend likes.
+    fn poll(&mut self, now: f64) -> Vec<InputEvent>;
+}
+
+/// a replay as input: its moves one at a time, `interval` seconds apart
+pub struct ReplaySource {
+    moves: VecDeque<Move>,
+    interval: f64,
+    /// when the last move came out
+    last: Option<f64>,
+}
+
+impl ReplaySource {
+    pub fn new(replay: &Replay, interval: f64) -> Self {
+        Self {
+            moves: replay.moves.iter().copied().collect(),
+            interval,
+            last: None,
+        }
+    }
+
+    /// whether every move has come out
+    pub fn is_done(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+impl InputSource for ReplaySource {
+    fn poll(&mut self, now: f64) -> Vec<InputEvent> {
+        let due = self.last.is_none_or(|last| now - last >= self.interval);
+        match self.moves.front() {
+            Some(&mv) if due => {
+                self.moves.pop_front();
+                self.last = Some(now);
+                vec![InputEvent::Play(mv)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// what the primary button did, with where the pointer was
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Press {
+        x: f32,
+        y: f32,
+    },
+    /// a press soon after the last one in about the same place. comes
+    /// instead of a `Press`, and a third press starts over.
+    DoubleClick {
+        x: f32,
+        y: f32,
+    },
+    Release {
+        x: f32,
+        y: f32,
+    },
+}
+
+/// turns pointer events into gestures
+#[derive(Debug, Default)]
+pub struct Gestures {
+    pointer: (f32, f32),
+    /// when and where the last press that wasn't a double click was
+    last_press: Option<(f64, (f32, f32))>,
+}
+
+impl Gestures {
+    /// how quickly a second press has to follow the first to be a double
+    /// click, in seconds
+    pub const DOUBLE_CLICK_TIME: f64 = 0.3;
+    /// how far the pointer can move between the presses of a double click
+    pub const DOUBLE_CLICK_SLOP: f32 = 6.0;
+
+    /// takes in an event that happened at `now`, returning the gesture it
+    /// makes if it makes one
+    pub fn feed(&mut self, event: InputEvent, now: f64) -> Option<Gesture> {
+        let (x, y) = self.pointer;
+        match event {
+            InputEvent::Pointer { x, y } => {
+                self.pointer = (x, y);
+                None
+            }
+            InputEvent::PointerDown(Button::Primary) => {
+                let double = self.last_press.is_some_and(|(at, (px, py))| {
+                    let distance = ((x - px).powi(2) + (y - py).powi(2)).sqrt();
+                    now - at < Self::DOUBLE_CLICK_TIME && distance < Self::DOUBLE_CLICK_SLOP
+                });
+                if double {
+                    self.last_press = None;
+                    Some(Gesture::DoubleClick { x, y })
+                } else {
+                    self.last_press = Some((now, (x, y)));
+                    Some(Gesture::Press { x, y })
+                }
+            }
+            InputEvent::PointerUp(Button::Primary) => Some(Gesture::Release { x, y }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! fullscreen, switched on and off with F11 or alt+enter from any screen.
+//! the layout works everything out from the size of the window each frame,
+//! so the board follows along by itself once the window's changed.
+
+use std::cell::Cell;
+
+use macroquad::prelude::{get_internal_gl, KeyCode};
+
+use crate::input::{is_key_down, is_key_pressed};
+
+thread_local! {
+    static FULLSCREEN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// whether alt+enter was pressed this frame. the enter belongs to
+/// fullscreen then, so menus and prompts leave it alone.
+pub fn is_shortcut_pressed() -> bool {
+    let alt = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+    alt && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter))
+}
+
+/// switches fullscreen if one of its keys was pressed. call once a frame.
+pub fn update() {
+    if is_key_pressed(KeyCode::F11) || is_shortcut_pressed() {
+        let on = !FULLSCREEN.with(Cell::get);
+        FULLSCREEN.with(|fullscreen| fullscreen.set(on));
+        // only asks the window to change, nothing is drawn through it
+        unsafe { get_internal_gl() }.quad_context.set_fullscreen(on);
+    }
+}
@@ -0,0 +1,1785 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::cards::{BitCard, CardId, CardStack, Column};
+use crate::rules::{Reveal, Rules, Topology};
+use crate::tableau::Tableau;
+use crate::world::World;
+
+/// the salt used for the rng that deals the first visible card of a column.
+/// reveals use the number of hidden cards left as the salt instead.
+const DEAL: u64 = u64::MAX;
+/// mixed into the salt for deciding whether a card gets turned over, when
+/// that's left to chance
+const CHANCE: u64 = u64::MAX - 1;
+/// mixed into the salt for the card a reshuffled column gets, along with
+/// how many reshuffles came before it
+const RESHUFFLE: u64 = u64::MAX - 2;
+/// mixed into the seed for the stock, which gets its own rng for each card
+/// like the columns do
+const STOCK: u64 = u64::MAX - 3;
+
+/// what kind of thing a zobrist key is for, in the top bits of the feature
+/// so different kinds never share keys
+const ZOBRIST_CARD: u64 = 1 << 60;
+const ZOBRIST_UNDER: u64 = 2 << 60;
+const ZOBRIST_FOUNDATION: u64 = 3 << 60;
+const ZOBRIST_RESHUFFLES: u64 = 4 << 60;
+const ZOBRIST_STOCK: u64 = 5 << 60;
+
+/// something the player did to the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// move the visible cards of column `from`, starting at `idx`, onto column `to`
+    Tableau { from: usize, idx: usize, to: usize },
+    /// move the last card of column `from` onto the foundation pile `foundation`
+    Foundation { from: usize, foundation: usize },
+    /// throw away the face up cards of `column` and deal it a fresh one, for
+    /// when it's stuck. uses up a reshuffle, see [`Game::reshuffles_left`].
+    Reshuffle { column: usize },
+    /// move the visible cards of column `from`, starting at `idx`, into a new
+    /// empty column put in at `at`. the columns from `at` on move along one
+    /// to make room, so it only works past every column that's been played
+    /// on, see [`Game::explored`].
+    Insert { from: usize, idx: usize, at: usize },
+    /// turn the next card of the stock over onto the waste, with
+    /// [`Rules::stock`]
+    Draw,
+    /// move the top card of the waste onto column `to`
+    Waste { to: usize },
+    /// move the top card of the waste onto the foundation pile `foundation`
+    WasteFoundation { foundation: usize },
+}
+
+/// something that happened because of a move, for frontends that want to
+/// react to what goes on without diffing the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Moved(Move),
+    /// `card` landed on foundation pile `foundation`
+    Founded {
+        foundation: usize,
+        card: BitCard,
+    },
+    /// a face down card in `column` was turned over
+    Revealed {
+        column: usize,
+        card: BitCard,
+    },
+    /// a card in `column` that had decayed face down was turned over again.
+    /// it was `Revealed` the first time, so it doesn't score again.
+    TurnedBack {
+        column: usize,
+        card: BitCard,
+    },
+    /// `column` was reshuffled and got `card`
+    Reshuffled {
+        column: usize,
+        card: BitCard,
+    },
+    /// `card` was turned over from the stock onto the waste
+    Drawn {
+        card: BitCard,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    NoSuchColumn,
+    NoSuchCard,
+    SameColumn,
+    CannotStack,
+    CannotFound,
+    NotConnected,
+    NoReshuffles,
+    Explored,
+    NoStock,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MoveError::NoSuchColumn => "column does not exist",
+            MoveError::NoSuchCard => "column does not have that card",
+            MoveError::SameColumn => "cards cannot be moved onto their own column",
+            MoveError::CannotStack => "cards do not stack on that column",
+            MoveError::CannotFound => "card does not go on that foundation",
+            MoveError::NotConnected => "those columns are not next to each other",
+            MoveError::NoReshuffles => "no reshuffles left",
+            MoveError::Explored => "new columns can only go past the ones that were played on",
+            MoveError::NoStock => "this game has no stock",
+        })
+    }
+}
+
+/// the rules side of the game, without any drawing or input.
+///
+/// every column gets its own rng derived from the seed and the column index,
+/// so the board only depends on the seed and the moves made, not on how far
+/// or in which order columns were generated.
+#[derive(Clone)]
+pub struct Game {
+    seed: u64,
+    rules: Rules,
+    tableau: Tableau,
+    /// changed columns whose chunk of the tableau hasn't been dealt, from a
+    /// save or from a chunk that was let go of. they're only moved into the
+    /// tableau once something needs them, so a save that went far doesn't
+    /// have to be built all at once.
+    stored: BTreeMap<usize, Column>,
+    /// where a streamed game writes its chunks, see [`Game::stream_to`]
+    world: Option<World>,
+    /// the face down and face up counts of the changed columns written to
+    /// `world`, for chunks whose file is up to date. the columns themselves
+    /// are read back when their chunk is dealt again.
+    on_disk: BTreeMap<usize, (u32, u8)>,
+    /// how many of the face down cards of the columns in `on_disk` decayed
+    /// there, for the ones that have any. they were turned over once
+    /// already, see [`Game::turned_over`].
+    decayed_on_disk: BTreeMap<usize, u32>,
+    foundations: HashMap<usize, BitCard>,
+    /// how many reshuffles have been used
+    reshuffles: u32,
+    /// where the columns made with [`Move::Insert`] are now, in order
+    inserted: Vec<usize>,
+    /// one past the last column that's been played on
+    explored: usize,
+    /// the cards turned over from the stock that haven't been played yet,
+    /// the top one last
+    waste: Vec<BitCard>,
+    /// how many cards have been turned over from the stock
+    drawn: u64,
+    /// how many cards went on foundations from each region, see
+    /// [`Game::REGION_COLUMNS`]
+    region_founded: BTreeMap<usize, u32>,
+    /// the ids of the cards on top of the foundations
+    foundation_ids: HashMap<usize, CardId>,
+    /// the ids of the cards on the waste, in the same order
+    waste_ids: Vec<CardId>,
+    /// how many cards have been put down by hand, for their ids
+    placed: u64,
+    /// emptied columns with cards still face down whose chance to turn one
+    /// over missed, see [`Reveal::Chance`]. they get another go every time
+    /// a card goes on a foundation.
+    missed: BTreeSet<usize>,
+}
+
+/// a board to go back to, from [`Game::snapshot`]
+#[derive(Clone)]
+pub struct Snapshot(Game);
+
+/// the card ids of a loaded game, in the order the cards are saved in
+pub(crate) struct CardIds {
+    pub placed: u64,
+    /// for each changed column, in order
+    pub columns: Vec<Vec<CardId>>,
+    /// for each foundation, in order of their index
+    pub foundations: Vec<CardId>,
+    pub waste: Vec<CardId>,
+}
+
+impl Game {
+    /// how many cards have to go on foundations to earn each reshuffle
+    pub const FOUNDED_PER_RESHUFFLE: usize = 50;
+    /// how many columns of the deal make up a region, for keeping track of
+    /// progress on a stretch of the endless board
+    pub const REGION_COLUMNS: usize = 50;
+
+    pub fn new(seed: u64, columns: usize) -> Self {
+        Self::with_rules(seed, columns, Rules::default())
+    }
+
+    pub fn with_rules(seed: u64, columns: usize, rules: Rules) -> Self {
+        let mut game = Self {
+            seed,
+            rules,
+            tableau: Tableau::default(),
+            stored: BTreeMap::new(),
+            world: None,
+            on_disk: BTreeMap::new(),
+            decayed_on_disk: BTreeMap::new(),
+            foundations: HashMap::new(),
+            reshuffles: 0,
+            inserted: Vec::new(),
+            explored: 0,
+            waste: Vec::new(),
+            drawn: 0,
+            region_founded: BTreeMap::new(),
+            foundation_ids: HashMap::new(),
+            waste_ids: Vec::new(),
+            placed: 0,
+            missed: BTreeSet::new(),
+        };
+        game.ensure_columns(columns);
+        game
+    }
+
+    /// puts a board back together, for loading saved games. `changed` only
+    /// needs the columns that differ from how they were dealt, out of the
+    /// first `columns`.
+    pub(crate) fn from_parts(
+        seed: u64,
+        rules: Rules,
+        columns: usize,
+        changed: BTreeMap<usize, Column>,
+        foundations: HashMap<usize, BitCard>,
+    ) -> Self {
+        let mut tableau = Tableau::default();
+        tableau.set_len(columns);
+        let mut game = Self {
+            seed,
+            rules,
+            tableau,
+            stored: changed,
+            world: None,
+            on_disk: BTreeMap::new(),
+            decayed_on_disk: BTreeMap::new(),
+            foundations,
+            reshuffles: 0,
+            inserted: Vec::new(),
+            explored: 0,
+            waste: Vec::new(),
+            drawn: 0,
+            region_founded: BTreeMap::new(),
+            foundation_ids: HashMap::new(),
+            waste_ids: Vec::new(),
+            placed: 0,
+            missed: BTreeSet::new(),
+        };
+        game.explored = game.find_explored();
+        game.missed = game.find_missed();
+        game
+    }
+
+    /// works out `explored` from the board, for loaded games. json saves
+    /// have every column, so it can't just take the last one.
+    fn find_explored(&self) -> usize {
+        let changed = (self.stored.iter().rev())
+            .find(|(x, column)| !self.is_as_dealt(**x, column))
+            .map_or(0, |(x, _)| x + 1);
+        // everything written out was changed
+        let streamed = self.on_disk.keys().next_back().map_or(0, |x| x + 1);
+        changed
+            .max(streamed)
+            .max(self.inserted.last().map_or(0, |x| x + 1))
+    }
+
+    /// works out `missed` from the board, for loaded games. a column only
+    /// gets emptied over face down cards by missing, and only changed
+    /// columns can have been.
+    fn find_missed(&self) -> BTreeSet<usize> {
+        let stored = (self.stored.iter())
+            .filter(|(_, column)| column.under > 0 && column.is_visible_empty())
+            .map(|(x, _)| *x);
+        let streamed = (self.on_disk.iter())
+            .filter(|(_, (under, visible))| *under > 0 && *visible == 0)
+            .map(|(x, _)| *x);
+        stored.chain(streamed).collect()
+    }
+
+    /// sets where a loaded game streams to, the counts of the columns
+    /// already there and how many of their face down cards decayed
+    pub(crate) fn with_world(
+        mut self,
+        world: Option<World>,
+        on_disk: BTreeMap<usize, (u32, u8)>,
+        decayed: BTreeMap<usize, u32>,
+    ) -> Self {
+        self.world = world;
+        self.on_disk = on_disk;
+        self.decayed_on_disk = decayed;
+        self.explored = self.find_explored();
+        self.missed = self.find_missed();
+        self
+    }
+
+    /// sets how many reshuffles a loaded game had used
+    pub(crate) fn with_reshuffles(mut self, used: u32) -> Self {
+        self.reshuffles = used;
+        self
+    }
+
+    /// sets where a loaded game's inserted columns are
+    pub(crate) fn with_inserted(mut self, mut inserted: Vec<usize>) -> Self {
+        inserted.sort_unstable();
+        inserted.dedup();
+        self.inserted = inserted;
+        self.explored = self.find_explored();
+        self
+    }
+
+    /// sets how many cards a loaded game put on foundations from each region
+    pub(crate) fn with_region_founded(mut self, founded: BTreeMap<usize, u32>) -> Self {
+        self.region_founded = founded;
+        self
+    }
+
+    /// which region column `x` is in. regions go by the deal, so inserting
+    /// columns doesn't move the others into the next region, and inserted
+    /// columns count as part of the region of the column after them.
+    pub fn region_of(&self, x: usize) -> usize {
+        let before = self.inserted.partition_point(|at| *at < x);
+        (x - before) / Self::REGION_COLUMNS
+    }
+
+    /// how many cards were dealt to `region`, face up and face down
+    pub fn region_dealt(&self, region: usize) -> u64 {
+        let start = region * Self::REGION_COLUMNS;
+        (start..start + Self::REGION_COLUMNS)
+            .map(|dealt| u64::from(self.rules.topology.hidden(dealt)) + 1)
+            .sum()
+    }
+
+    /// how many cards went on foundations from `region`
+    pub fn region_founded(&self, region: usize) -> u32 {
+        self.region_founded.get(&region).copied().unwrap_or(0)
+    }
+
+    /// how many cards went on foundations from each region that had any
+    pub fn regions_founded(&self) -> &BTreeMap<usize, u32> {
+        &self.region_founded
+    }
+
+    /// sets a loaded game's stock and waste
+    pub(crate) fn with_stock(mut self, drawn: u64, waste: Vec<BitCard>) -> Self {
+        self.drawn = drawn;
+        self.waste = waste;
+        self
+    }
+
+    /// sets which card is which in a loaded game. saves from before cards
+    /// had ids don't have any, so the ones that aren't as they were dealt
+    /// are numbered as unknown. this has to go last, after everything that
+    /// says where the cards are.
+    pub(crate) fn with_ids(mut self, ids: Option<CardIds>) -> Result<Self, &'static str> {
+        let mut foundations: Vec<usize> = self.foundations.keys().copied().collect();
+        foundations.sort_unstable();
+        let Some(ids) = ids else {
+            let mut unknown = (0..).map(CardId::unknown);
+            let stored = std::mem::take(&mut self.stored);
+            for (x, mut column) in stored {
+                let dealt = self.deal(x);
+                let as_dealt = column.under == dealt.under
+                    && column.visible().iter().eq(dealt.visible().iter());
+                if as_dealt {
+                    column = dealt;
+                } else {
+                    for id in column.visible_mut().ids_mut() {
+                        *id = unknown.next().unwrap();
+                    }
+                }
+                self.stored.insert(x, column);
+            }
+            self.foundation_ids = foundations.into_iter().zip(&mut unknown).collect();
+            self.waste_ids = unknown.by_ref().take(self.waste.len()).collect();
+            return Ok(self);
+        };
+        let fits = ids.columns.len() == self.stored.len()
+            && ids.foundations.len() == foundations.len()
+            && ids.waste.len() == self.waste.len();
+        if !fits {
+            return Err("there should be an id for every card");
+        }
+        for (column, column_ids) in self.stored.values_mut().zip(ids.columns) {
+            let visible = column.visible_mut().ids_mut();
+            if visible.len() != column_ids.len() {
+                return Err("there should be an id for every card");
+            }
+            visible.copy_from_slice(&column_ids);
+        }
+        self.foundation_ids = foundations.into_iter().zip(ids.foundations).collect();
+        self.waste_ids = ids.waste;
+        self.placed = ids.placed;
+        Ok(self)
+    }
+
+    /// the ids of the cards on top of the foundations
+    pub fn foundation_ids(&self) -> &HashMap<usize, CardId> {
+        &self.foundation_ids
+    }
+
+    /// the ids of the cards on the waste, in the same order as
+    /// [`Game::waste`]
+    pub fn waste_ids(&self) -> &[CardId] {
+        &self.waste_ids
+    }
+
+    /// how many cards have been put down by hand
+    pub fn placed(&self) -> u64 {
+        self.placed
+    }
+
+    /// the cards turned over from the stock and not played yet, the top one
+    /// last
+    pub fn waste(&self) -> &[BitCard] {
+        &self.waste
+    }
+
+    /// how many cards have been turned over from the stock
+    pub fn drawn(&self) -> u64 {
+        self.drawn
+    }
+
+    /// the card the stock will turn over next. like the face down cards of
+    /// the columns, it's decided by the seed from the start.
+    pub fn next_stock_card(&self) -> Option<BitCard> {
+        self.rules.stock.then(|| self.stock_card(self.drawn))
+    }
+
+    /// card number `n` of the stock
+    fn stock_card(&self, n: u64) -> BitCard {
+        let x = splitmix(splitmix(self.seed ^ STOCK) ^ n);
+        self.rules
+            .deck
+            .random_card(&mut ChaCha8Rng::seed_from_u64(x))
+    }
+
+    /// where the columns made with [`Move::Insert`] are, in order
+    pub fn inserted(&self) -> &[usize] {
+        &self.inserted
+    }
+
+    /// one past the last column that's been played on. everything from here
+    /// on is still how it was dealt, so new columns can go anywhere past it.
+    pub fn explored(&self) -> usize {
+        self.explored
+    }
+
+    /// how many reshuffles have been used so far
+    pub fn reshuffles_used(&self) -> u32 {
+        self.reshuffles
+    }
+
+    /// how many reshuffles can still be used. one is earned for every
+    /// [`Game::FOUNDED_PER_RESHUFFLE`] cards put on foundations.
+    pub fn reshuffles_left(&self) -> u32 {
+        let earned = (self.founded() / Self::FOUNDED_PER_RESHUFFLE) as u32;
+        earned.saturating_sub(self.reshuffles)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// the columns of the board. only the chunks of it something asked for
+    /// with [`Game::ensure_columns`] or [`Game::ensure_range`] are dealt,
+    /// the rest of the columns aren't there until then.
+    pub fn tableau(&self) -> &Tableau {
+        &self.tableau
+    }
+
+    /// how many columns the board has, dealt or not
+    pub fn columns(&self) -> usize {
+        self.tableau.len()
+    }
+
+    /// every column that differs from how it was dealt, in order, including
+    /// ones whose chunk hasn't been dealt. for a streamed game it leaves out
+    /// the ones that are up to date on disk, see [`Game::streamed_columns`].
+    pub fn changed_columns(&self) -> Vec<(usize, &Column)> {
+        let dealt = self
+            .tableau
+            .iter()
+            .filter(|(x, _)| !self.is_on_disk(x / Tableau::CHUNK))
+            .filter(|(x, column)| !self.is_as_dealt(*x, column));
+        let mut changed: Vec<_> = dealt
+            .chain(self.stored.iter().map(|(x, column)| (*x, column)))
+            .collect();
+        changed.sort_unstable_by_key(|(x, _)| *x);
+        changed
+    }
+
+    /// the face down and face up counts of the changed columns of a
+    /// streamed game that are up to date on disk instead of in the save, in
+    /// order
+    pub fn streamed_columns(&self) -> Vec<(usize, (u32, u8))> {
+        self.on_disk
+            .iter()
+            .filter(|(x, _)| self.is_on_disk(*x / Tableau::CHUNK))
+            .map(|(x, counts)| (*x, *counts))
+            .collect()
+    }
+
+    /// how many of the face down cards of each column in
+    /// [`Game::streamed_columns`] decayed there, for the ones with any, in
+    /// order
+    pub fn streamed_decayed(&self) -> Vec<(usize, u32)> {
+        self.decayed_on_disk
+            .iter()
+            .filter(|(x, _)| self.is_on_disk(*x / Tableau::CHUNK))
+            .map(|(x, decayed)| (*x, *decayed))
+            .collect()
+    }
+
+    /// whether chunk `chunk` is streamed and its file is up to date
+    fn is_on_disk(&self, chunk: usize) -> bool {
+        self.world.is_some() && !self.tableau.is_dirty(chunk)
+    }
+
+    /// the changed columns of chunk `chunk` that are on disk, read back.
+    /// one that can't be read is dealt again from the seed like it was
+    /// never played on, since there's nothing else left to go on.
+    fn read_chunk(&self, chunk: usize) -> BTreeMap<usize, Column> {
+        let start = chunk * Tableau::CHUNK;
+        match &self.world {
+            Some(world)
+                if self
+                    .on_disk
+                    .range(start..start + Tableau::CHUNK)
+                    .next()
+                    .is_some() =>
+            {
+                world.read(chunk).unwrap_or_default()
+            }
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// column `x` as it is now, whether its chunk has been dealt or not
+    pub(crate) fn column(&self, x: usize) -> Column {
+        match self.tableau.get(x).or_else(|| self.stored.get(&x)) {
+            Some(column) => column.clone(),
+            None if self.on_disk.contains_key(&x) => {
+                let mut read = self.read_chunk(x / Tableau::CHUNK);
+                read.remove(&x).unwrap_or_else(|| self.deal(x))
+            }
+            None => self.deal(x),
+        }
+    }
+
+    /// every column of the board as it is now, in order, going a chunk at a
+    /// time so streamed ones are only read once
+    pub(crate) fn all_columns(&self) -> impl Iterator<Item = Column> + '_ {
+        let chunks = self.columns().div_ceil(Tableau::CHUNK);
+        (0..chunks).flat_map(|chunk| {
+            let start = chunk * Tableau::CHUNK;
+            let end = (start + Tableau::CHUNK).min(self.columns());
+            let mut read = if self.tableau.has_chunk(chunk) {
+                BTreeMap::new()
+            } else {
+                self.read_chunk(chunk)
+            };
+            (start..end)
+                .map(
+                    |x| match self.tableau.get(x).or_else(|| self.stored.get(&x)) {
+                        Some(column) => column.clone(),
+                        None => read.remove(&x).unwrap_or_else(|| self.deal(x)),
+                    },
+                )
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// how many face down and face up cards column `x` has, whether its
+    /// chunk has been dealt or not. cheaper than dealing it just to look.
+    pub fn column_counts(&self, x: usize) -> (u32, u8) {
+        match self.tableau.get(x).or_else(|| self.stored.get(&x)) {
+            Some(column) => (column.under, column.visible().len()),
+            None => match self.on_disk.get(&x) {
+                Some(counts) => *counts,
+                None if self.dealt_as(x).is_some() => (self.hidden(x), 1),
+                None => (0, 0),
+            },
+        }
+    }
+
+    /// which column of the deal column `x` is, counting past the inserted
+    /// ones. `None` for an inserted column.
+    fn dealt_as(&self, x: usize) -> Option<usize> {
+        match self.inserted.binary_search(&x) {
+            Ok(_) => None,
+            Err(before) => Some(x - before),
+        }
+    }
+
+    /// how many face down cards column `x` was dealt
+    fn hidden(&self, x: usize) -> u32 {
+        self.dealt_as(x)
+            .map_or(0, |dealt| self.rules.topology.hidden(dealt))
+    }
+
+    fn deal(&self, x: usize) -> Column {
+        match self.dealt_as(x) {
+            Some(dealt) => Column::new(
+                &mut self.column_rng(x, DEAL),
+                self.hidden(x),
+                self.rules.deck,
+                CardId::dealt(dealt as u64),
+            ),
+            None => Column::from_parts(0, CardStack::empty()),
+        }
+    }
+
+    /// every changed column of the board, dealt, stored or streamed to
+    /// disk, in order. the rest are dealt again from the seed, so these and
+    /// [`Game::columns`] are the whole board.
+    pub(crate) fn all_changed_columns(&self) -> Vec<(usize, Column)> {
+        let mut changed: BTreeMap<usize, Column> = (self.changed_columns().into_iter())
+            .map(|(x, column)| (x, column.clone()))
+            .collect();
+        let mut chunks: Vec<usize> = (self.streamed_columns().into_iter())
+            .map(|(x, _)| x / Tableau::CHUNK)
+            .collect();
+        chunks.dedup();
+        for chunk in chunks {
+            changed.extend(self.read_chunk(chunk));
+        }
+        // json saves store every column, dealt or not
+        changed.retain(|x, column| !self.is_as_dealt(*x, column));
+        changed.into_iter().collect()
+    }
+
+    /// whether `column` has the same cards column `x` was dealt, whichever
+    /// ones they are
+    fn has_dealt_cards(&self, x: usize, column: &Column) -> bool {
+        if column.under != self.hidden(x) || !column.turned_under().is_empty() {
+            return false;
+        }
+        column.visible().iter().eq(self.deal(x).visible().iter())
+    }
+
+    fn is_as_dealt(&self, x: usize, column: &Column) -> bool {
+        // anything that was turned over has fewer face down cards, so this
+        // only deals the columns that might not have been touched
+        let hidden = self.hidden(x);
+        let turned_under = !column.turned_under().is_empty();
+        if column.under != hidden || column.visible().len() != 1 || turned_under {
+            return false;
+        }
+        // a card just like the one dealt might have been put back on it
+        let dealt = self.deal(x);
+        column.visible().iter().eq(dealt.visible().iter())
+            && column.visible().ids() == dealt.visible().ids()
+    }
+
+    /// whether column `x` has been dealt and is still exactly how it was
+    pub fn is_untouched(&self, x: usize) -> bool {
+        self.tableau
+            .get(x)
+            .is_some_and(|column| self.is_as_dealt(x, column))
+    }
+
+    /// takes the visible cards of `column` from `idx` on off the board without
+    /// it counting as a move, for frontends that let the player carry cards
+    /// around. they have to go back with [`Game::put_back`] before the next move.
+    pub fn pick_up(&mut self, column: usize, idx: usize, into: &mut CardStack) {
+        into.take_from(self.tableau[column].visible_mut(), idx)
+    }
+
+    /// puts cards taken with [`Game::pick_up`] back where they came from
+    pub fn put_back(&mut self, column: usize, from: &mut CardStack) {
+        self.tableau[column].append(from)
+    }
+
+    pub fn foundations(&self) -> &HashMap<usize, BitCard> {
+        &self.foundations
+    }
+
+    /// how many cards have been put on foundations in total
+    pub fn founded(&self) -> usize {
+        let deck = self.rules.deck;
+        self.foundations
+            .values()
+            .map(|card| deck.rank_index(card.number()) + 1)
+            .sum()
+    }
+
+    /// lets go of the chunks of the tableau with nothing in `keep`, holding
+    /// on to just the columns that were changed, or writing them out for a
+    /// streamed game. they all come back the same when
+    /// [`Game::ensure_range`] needs them again.
+    pub fn forget_outside(&mut self, keep: Range<usize>) {
+        for chunk in self.tableau.chunks() {
+            let start = chunk * Tableau::CHUNK;
+            if start < keep.end && keep.start < start + Tableau::CHUNK {
+                continue;
+            }
+            // if it can't be written it's held on to like any other game's
+            if self.world.is_some() && self.write_chunk(chunk).is_ok() {
+                self.tableau.take_chunk(chunk);
+                continue;
+            }
+            let Some(columns) = self.tableau.take_chunk(chunk) else {
+                continue;
+            };
+            let columns = (start..self.tableau.len()).zip(columns);
+            for (x, column) in columns {
+                if !self.is_as_dealt(x, &column) {
+                    self.stored.insert(x, column);
+                }
+            }
+        }
+    }
+
+    /// a copy of the board to go back to with [`Game::restore`], for bots
+    /// and tools trying moves out. only the columns that were played on are
+    /// copied and the rest are dealt again when they're needed, so it's
+    /// cheap however far the board goes. a streamed game's chunks on disk
+    /// can be written over after this, so those are read back in for it.
+    pub fn snapshot(&self) -> Snapshot {
+        let mut chunks: Vec<usize> = self.on_disk.keys().map(|x| x / Tableau::CHUNK).collect();
+        chunks.dedup();
+        let mut stored = BTreeMap::new();
+        for chunk in chunks {
+            if !self.tableau.has_chunk(chunk) {
+                stored.extend(self.read_chunk(chunk));
+            }
+        }
+        stored.extend(self.stored.clone());
+        let played = self.tableau.iter().take_while(|(x, _)| *x < self.explored);
+        stored.extend(played.map(|(x, column)| (x, column.clone())));
+        let mut tableau = Tableau::default();
+        tableau.set_len(self.columns());
+        Snapshot(Self {
+            seed: self.seed,
+            rules: self.rules,
+            tableau,
+            stored,
+            world: None,
+            on_disk: BTreeMap::new(),
+            decayed_on_disk: BTreeMap::new(),
+            foundations: self.foundations.clone(),
+            reshuffles: self.reshuffles,
+            inserted: self.inserted.clone(),
+            explored: self.explored,
+            waste: self.waste.clone(),
+            drawn: self.drawn,
+            region_founded: self.region_founded.clone(),
+            foundation_ids: self.foundation_ids.clone(),
+            waste_ids: self.waste_ids.clone(),
+            placed: self.placed,
+            missed: self.missed.clone(),
+        })
+    }
+
+    /// puts the board back how it was when `snapshot` was taken, with the
+    /// same chunks dealt as there are now. a streamed game keeps streaming.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let chunks = self.tableau.chunks();
+        let len = self.columns();
+        let world = self.world.take();
+        *self = snapshot.0.clone();
+        self.world = world;
+        self.tableau.set_len(self.columns().max(len));
+        for chunk in chunks {
+            self.deal_chunk(chunk);
+        }
+    }
+
+    /// how many of the face down cards the columns were dealt have been
+    /// turned over in total. cards that decayed face down are left out of
+    /// what's still face down, since they were counted when they first
+    /// came up, so turning them over again doesn't count twice.
+    pub fn turned_over(&self) -> u64 {
+        let dealt = self.tableau.iter();
+        let columns: u64 = dealt
+            .chain(self.stored.iter().map(|(x, column)| (*x, column)))
+            .map(|(x, column)| {
+                let under = column.under - column.turned_under().len() as u32;
+                u64::from(self.hidden(x).saturating_sub(under))
+            })
+            .sum();
+        let streamed = self.on_disk.iter();
+        let streamed: u64 = streamed
+            .filter(|(x, _)| !self.tableau.has_chunk(*x / Tableau::CHUNK))
+            .map(|(x, (under, _))| {
+                let decayed = self.decayed_on_disk.get(x).copied().unwrap_or(0);
+                u64::from(
+                    self.hidden(*x)
+                        .saturating_sub(under.saturating_sub(decayed)),
+                )
+            })
+            .sum();
+        columns + streamed
+    }
+
+    /// deals every column until there are at least `columns` of them
+    pub fn ensure_columns(&mut self, columns: usize) {
+        self.ensure_range(0..columns);
+    }
+
+    /// makes the board long enough for the columns in `range` and deals
+    /// them, leaving the ones before them alone
+    pub fn ensure_range(&mut self, range: Range<usize>) {
+        if range.end > self.tableau.len() {
+            self.tableau.set_len(range.end);
+        }
+        if range.is_empty() {
+            return;
+        }
+        for chunk in range.start / Tableau::CHUNK..=(range.end - 1) / Tableau::CHUNK {
+            self.deal_chunk(chunk);
+        }
+    }
+
+    /// deals chunk `chunk` of the tableau, if it hasn't been, with any
+    /// stored or streamed columns put back in it
+    fn deal_chunk(&mut self, chunk: usize) {
+        if self.tableau.has_chunk(chunk) {
+            return;
+        }
+        let start = chunk * Tableau::CHUNK;
+        let mut read = self.read_chunk(chunk);
+        let mut stored = false;
+        let columns = (start..start + Tableau::CHUNK)
+            .map(|x| match self.stored.remove(&x) {
+                Some(column) => {
+                    stored = true;
+                    column
+                }
+                None => read.remove(&x).unwrap_or_else(|| self.deal(x)),
+            })
+            .collect();
+        self.tableau.put_chunk(chunk, columns);
+        // stored columns aren't on disk yet
+        self.tableau.set_dirty(chunk, stored);
+    }
+
+    /// streams the board into `world` from now on. chunks that get let go of
+    /// are written there instead of being kept in memory, and binary saves
+    /// only name the ones that are there.
+    pub fn stream_to(&mut self, world: World) {
+        self.world = Some(world);
+        for chunk in self.tableau.chunks() {
+            self.tableau.set_dirty(chunk, true);
+        }
+    }
+
+    /// where the board is streamed to, if it is
+    pub fn world(&self) -> Option<&World> {
+        self.world.as_ref()
+    }
+
+    /// writes every chunk that changed since it was last written to the
+    /// world, returning how many there were
+    pub fn flush(&mut self) -> io::Result<usize> {
+        if self.world.is_none() {
+            return Ok(0);
+        }
+        let dirty = self.tableau.dirty();
+        for &chunk in &dirty {
+            self.write_chunk(chunk)?;
+        }
+        Ok(dirty.len())
+    }
+
+    /// writes the changed columns of dealt chunk `chunk` to the world, if
+    /// anything changed since it was last written
+    fn write_chunk(&mut self, chunk: usize) -> io::Result<()> {
+        let Some(world) = &self.world else {
+            return Ok(());
+        };
+        if !self.tableau.is_dirty(chunk) {
+            return Ok(());
+        }
+        let start = chunk * Tableau::CHUNK;
+        let end = (start + Tableau::CHUNK).min(self.columns());
+        let changed: Vec<(usize, &Column)> = (start..end)
+            .filter_map(|x| Some((x, self.tableau.get(x)?)))
+            .filter(|(x, column)| !self.is_as_dealt(*x, column))
+            .collect();
+        let written = self.on_disk.range(start..end).next().is_some();
+        if !changed.is_empty() || written {
+            world.write(chunk, &changed)?;
+        }
+        let counts: Vec<(usize, (u32, u8))> = changed
+            .iter()
+            .map(|(x, column)| (*x, (column.under, column.visible().len())))
+            .collect();
+        let decayed: Vec<(usize, u32)> = (changed.iter())
+            .filter(|(_, column)| !column.turned_under().is_empty())
+            .map(|(x, column)| (*x, column.turned_under().len() as u32))
+            .collect();
+        self.on_disk.retain(|x, _| !(start..end).contains(x));
+        self.on_disk.extend(counts);
+        self.decayed_on_disk
+            .retain(|x, _| !(start..end).contains(x));
+        self.decayed_on_disk.extend(decayed);
+        self.tableau.set_dirty(chunk, false);
+        Ok(())
+    }
+
+    /// the rng for column `column`, which follows it when columns are
+    /// inserted before it. inserted columns all share one, they never have
+    /// face down cards and reshuffles are salted apart anyway.
+    fn column_rng(&self, column: usize, salt: u64) -> ChaCha8Rng {
+        let column = self.dealt_as(column).map_or(u64::MAX, |dealt| dealt as u64);
+        let mut x = splitmix(self.seed ^ splitmix(column));
+        x = splitmix(x ^ salt);
+        ChaCha8Rng::seed_from_u64(x)
+    }
+
+    /// can `card` be put on foundation pile `foundation`?
+    pub fn can_found(&self, card: BitCard, foundation: usize) -> bool {
+        match self.foundations.get(&foundation) {
+            Some(under) => under.same_suit(card) && card.is_next_card(*under, self.rules.deck),
+            None => card.is_ace(),
+        }
+    }
+
+    /// the first foundation pile with nothing on it
+    pub fn free_foundation(&self) -> usize {
+        (0..)
+            .find(|idx| !self.foundations.contains_key(idx))
+            .unwrap()
+    }
+
+    /// the foundation pile `card` can go on, if any. aces start a new pile.
+    pub fn foundation_for(&self, card: BitCard) -> Option<usize> {
+        if card.is_ace() {
+            Some(self.free_foundation())
+        } else {
+            self.foundations
+                .keys()
+                .copied()
+                .filter(|idx| self.can_found(card, *idx))
+                .min()
+        }
+    }
+
+    /// every legal move between the columns in `window`, and from the waste
+    /// to them. aces only get one move, to the first free foundation pile,
+    /// since the piles are all the same. drawing from the stock is always
+    /// legal when there is one, so it's left out.
+    pub fn legal_moves(&self, window: Range<usize>) -> Vec<Move> {
+        let window = window.start..window.end.min(self.tableau.len());
+        let mut moves = Vec::new();
+        for from in window.clone() {
+            let Some(column) = self.tableau.get(from) else {
+                continue;
+            };
+            let visible = column.visible();
+            if let Some(foundation) = visible.last().and_then(|card| self.foundation_for(card)) {
+                moves.push(Move::Foundation { from, foundation });
+            }
+            for idx in 0..visible.len().into() {
+                for to in window.clone() {
+                    let mv = Move::Tableau { from, idx, to };
+                    if self.check(mv).is_ok() {
+                        moves.push(mv);
+                    }
+                }
+            }
+        }
+        if let Some(&card) = self.waste.last() {
+            if let Some(foundation) = self.foundation_for(card) {
+                moves.push(Move::WasteFoundation { foundation });
+            }
+            let onto = window.map(|to| Move::Waste { to });
+            moves.extend(onto.filter(|mv| self.check(*mv).is_ok()));
+        }
+        moves
+    }
+
+    /// the card a move would cover up that could have gone on a foundation
+    /// straight away, if there is one
+    pub fn buries(&self, mv: Move) -> Option<BitCard> {
+        let Move::Tableau { to, .. } = mv else {
+            return None;
+        };
+        let card = self.tableau.get(to)?.visible().last()?;
+        let needed = card.is_ace()
+            || self
+                .foundations
+                .keys()
+                .any(|idx| self.can_found(card, *idx));
+        needed.then_some(card)
+    }
+
+    /// checks a move without doing it
+    pub fn check(&self, mv: Move) -> Result<(), MoveError> {
+        match mv {
+            Move::Tableau { from, idx, to } => {
+                let source = self.tableau.get(from).ok_or(MoveError::NoSuchColumn)?;
+                self.check_run(from, source.visible(), idx, to)
+            }
+            Move::Reshuffle { column } => {
+                let stack = self.tableau.get(column).ok_or(MoveError::NoSuchColumn)?;
+                if stack.is_visible_empty() {
+                    Err(MoveError::NoSuchCard)
+                } else if self.reshuffles_left() == 0 {
+                    Err(MoveError::NoReshuffles)
+                } else {
+                    Ok(())
+                }
+            }
+            Move::Foundation { from, foundation } => {
+                let source = self.tableau.get(from).ok_or(MoveError::NoSuchColumn)?;
+                let card = source.visible().last().ok_or(MoveError::NoSuchCard)?;
+                if self.can_found(card, foundation) {
+                    Ok(())
+                } else {
+                    Err(MoveError::CannotFound)
+                }
+            }
+            Move::Insert { from, idx, at } => {
+                let source = self.tableau.get(from).ok_or(MoveError::NoSuchColumn)?;
+                self.check_insert_run(source.visible(), idx, at)
+            }
+            Move::Draw if self.rules.stock => Ok(()),
+            Move::Draw => Err(MoveError::NoStock),
+            Move::Waste { to } => {
+                let target = self.tableau.get(to).ok_or(MoveError::NoSuchColumn)?;
+                let card = self.waste.last().copied().ok_or(MoveError::NoSuchCard)?;
+                let fits = if self.rules.sandbox {
+                    target.visible().len() < 13
+                } else {
+                    target.visible().can_stack(card, self.rules.deck)
+                };
+                if fits {
+                    Ok(())
+                } else {
+                    Err(MoveError::CannotStack)
+                }
+            }
+            Move::WasteFoundation { foundation } => {
+                let card = self.waste.last().copied().ok_or(MoveError::NoSuchCard)?;
+                if self.can_found(card, foundation) {
+                    Ok(())
+                } else {
+                    Err(MoveError::CannotFound)
+                }
+            }
+        }
+    }
+
+    /// checks putting cards picked up from `from` with [`Game::pick_up`] on
+    /// `to`, while they're still being carried
+    pub fn check_carried(
+        &self,
+        from: usize,
+        carried: &CardStack,
+        to: usize,
+    ) -> Result<(), MoveError> {
+        if from >= self.tableau.len() {
+            return Err(MoveError::NoSuchColumn);
+        }
+        self.check_run(from, carried, 0, to)
+    }
+
+    /// checks putting cards picked up from `from` with [`Game::pick_up`] in a
+    /// new column at `at`, while they're still being carried
+    pub fn check_insert_carried(
+        &self,
+        from: usize,
+        carried: &CardStack,
+        at: usize,
+    ) -> Result<(), MoveError> {
+        if from >= self.tableau.len() {
+            return Err(MoveError::NoSuchColumn);
+        }
+        self.check_insert_run(carried, 0, at)
+    }
+
+    /// checks moving the cards from `idx` up in `run` into a new column at
+    /// `at`
+    fn check_insert_run(&self, run: &CardStack, idx: usize, at: usize) -> Result<(), MoveError> {
+        if self.rules.topology != Topology::Line {
+            // there's no room between the piles of a grid
+            return Err(MoveError::NotConnected);
+        }
+        if at < self.explored || at > self.tableau.len() {
+            return Err(MoveError::Explored);
+        }
+        let card = run.iter().nth(idx).ok_or(MoveError::NoSuchCard)?;
+        if self.rules.sandbox {
+            return Ok(());
+        }
+        if run.is_run_from(idx, self.rules.deck)
+            && CardStack::empty().can_stack(card, self.rules.deck)
+        {
+            Ok(())
+        } else {
+            Err(MoveError::CannotStack)
+        }
+    }
+
+    /// checks moving the cards from `idx` up in `run` from column `from` to
+    /// column `to`
+    fn check_run(
+        &self,
+        from: usize,
+        run: &CardStack,
+        idx: usize,
+        to: usize,
+    ) -> Result<(), MoveError> {
+        if from == to {
+            return Err(MoveError::SameColumn);
+        }
+        let target = self.tableau.get(to).ok_or(MoveError::NoSuchColumn)?;
+        let card = run.iter().nth(idx).ok_or(MoveError::NoSuchCard)?;
+        if self.rules.sandbox {
+            // anything goes, as long as it fits
+            let moved = run.len() as usize - idx;
+            return if target.visible().len() as usize + moved <= 13 {
+                Ok(())
+            } else {
+                Err(MoveError::CannotStack)
+            };
+        }
+        if !self.rules.topology.connected(from, to) {
+            return Err(MoveError::NotConnected);
+        }
+        if !run.is_run_from(idx, self.rules.deck) {
+            // only when several cards were turned over at once
+            return Err(MoveError::CannotStack);
+        }
+        if target.visible().can_stack(card, self.rules.deck) {
+            Ok(())
+        } else {
+            Err(MoveError::CannotStack)
+        }
+    }
+
+    /// does a move, revealing a new card in the source column if needed
+    pub fn apply(&mut self, mv: Move) -> Result<Vec<Event>, MoveError> {
+        self.check(mv)?;
+        let mut events = vec![Event::Moved(mv)];
+        // the column cards came off, which might need a card turned over
+        let from = match mv {
+            Move::Tableau { from, idx, to } => {
+                let mut taken = CardStack::empty();
+                taken.take_from(self.tableau[from].visible_mut(), idx);
+                self.tableau[to].append(&mut taken);
+                Some(from)
+            }
+            Move::Foundation { from, foundation } => {
+                let (card, id) = self.tableau[from].visible_mut().pop().unwrap();
+                self.foundations.insert(foundation, card);
+                self.foundation_ids.insert(foundation, id);
+                *self.region_founded.entry(self.region_of(from)).or_default() += 1;
+                events.push(Event::Founded { foundation, card });
+                Some(from)
+            }
+            Move::Reshuffle { column } => {
+                let salt = splitmix(RESHUFFLE ^ u64::from(self.reshuffles));
+                let card = self
+                    .rules
+                    .deck
+                    .random_card(&mut self.column_rng(column, salt));
+                let visible = self.tableau[column].visible_mut();
+                *visible = CardStack::empty();
+                visible.push(card, CardId::reshuffled(self.reshuffles.into()));
+                self.reshuffles += 1;
+                events.push(Event::Reshuffled { column, card });
+                Some(column)
+            }
+            Move::Insert { from, idx, at } => {
+                let mut column = Column::from_parts(0, CardStack::empty());
+                column
+                    .visible_mut()
+                    .take_from(self.tableau[from].visible_mut(), idx);
+                self.insert_column(at, column);
+                Some(if from >= at { from + 1 } else { from })
+            }
+            Move::Draw => {
+                for _ in 0..self.rules.cards_per_draw() {
+                    let card = self.stock_card(self.drawn);
+                    self.waste.push(card);
+                    self.waste_ids.push(CardId::drawn(self.drawn));
+                    self.drawn += 1;
+                    events.push(Event::Drawn { card });
+                }
+                None
+            }
+            Move::Waste { to } => {
+                let card = self.waste.pop().unwrap();
+                let id = self.waste_ids.pop().unwrap();
+                self.tableau[to].visible_mut().push(card, id);
+                None
+            }
+            Move::WasteFoundation { foundation } => {
+                let card = self.waste.pop().unwrap();
+                self.foundations.insert(foundation, card);
+                self.foundation_ids
+                    .insert(foundation, self.waste_ids.pop().unwrap());
+                events.push(Event::Founded { foundation, card });
+                None
+            }
+        };
+        let touched = match mv {
+            Move::Tableau { from, to, .. } => Some(from.max(to)),
+            Move::Foundation { from, .. } => Some(from),
+            Move::Reshuffle { column } | Move::Waste { to: column } => Some(column),
+            Move::Insert { at, .. } => from.map(|from| from.max(at)),
+            Move::Draw | Move::WasteFoundation { .. } => None,
+        };
+        if let Some(touched) = touched {
+            self.explored = self.explored.max(touched + 1);
+        }
+        if let Some(from) = from {
+            events.extend(self.reveal(from));
+        }
+        let founded = matches!(mv, Move::Foundation { .. } | Move::WasteFoundation { .. });
+        if founded && matches!(self.rules.reveal, Reveal::Chance(_)) {
+            // the founded count is in the chance, so it's a new roll
+            for column in self.missed.clone() {
+                self.ensure_range(column..column + 1);
+                events.extend(self.reveal(column));
+            }
+        }
+        Ok(events)
+    }
+
+    /// puts `column` in at `at`, moving the columns from there on along one.
+    /// they're all untouched, so none of them are stored, and the dealt ones
+    /// can just be dealt again one further along.
+    fn insert_column(&mut self, at: usize, column: Column) {
+        for x in &mut self.inserted {
+            if *x >= at {
+                *x += 1;
+            }
+        }
+        let before = self.inserted.partition_point(|x| *x < at);
+        self.inserted.insert(before, at);
+        self.tableau.set_len(self.tableau.len() + 1);
+        self.deal_chunk(at / Tableau::CHUNK);
+        for chunk in self.tableau.chunks() {
+            let end = (chunk + 1) * Tableau::CHUNK;
+            for x in (chunk * Tableau::CHUNK).max(at + 1)..end {
+                let dealt = self.deal(x);
+                *self.tableau.slot(x).unwrap() = dealt;
+            }
+        }
+        self.tableau[at] = column;
+    }
+
+    /// turns over new cards if the column's visible stack is empty and there
+    /// are hidden cards left, as many as the rules say. returns them in the
+    /// order they were turned over, which is bottom to top.
+    fn reveal(&mut self, column: usize) -> Vec<Event> {
+        let stack = &self.tableau[column];
+        let under = stack.under;
+        if under == 0 || !stack.is_visible_empty() {
+            // a king went on it since it missed, or it was never empty
+            self.missed.remove(&column);
+            return Vec::new();
+        }
+        let count = match self.rules.reveal {
+            Reveal::One => 1,
+            Reveal::Chance(percent) => {
+                // the cards founded so far go in the salt, so a column that
+                // missed gets a new roll when the next card is founded
+                let salt = splitmix(CHANCE ^ self.founded() as u64) ^ u64::from(under);
+                let roll = self.column_rng(column, salt).gen_range(0..100);
+                let hit = roll < percent;
+                if hit {
+                    self.missed.remove(&column);
+                } else {
+                    self.missed.insert(column);
+                }
+                u32::from(hit)
+            }
+            Reveal::Many(count) => u32::from(count).min(under),
+        };
+        let mut events = Vec::new();
+        for _ in 0..count {
+            // each card is salted with how many are left face down, like
+            // `hidden_card` expects
+            let under = self.tableau[column].under;
+            let card = self
+                .rules
+                .deck
+                .random_card(&mut self.column_rng(column, under.into()));
+            let id = self.revealed_id(column, under - 1);
+            let decayed = !self.tableau[column].turned_under().is_empty();
+            let card = self.tableau[column].turn_over(card, id);
+            events.push(if decayed {
+                Event::TurnedBack { column, card }
+            } else {
+                Event::Revealed { column, card }
+            });
+        }
+        events
+    }
+
+    /// the id of the card turned over on `column` that leaves `left` face
+    /// down cards under it
+    fn revealed_id(&self, column: usize, left: u32) -> CardId {
+        // only dealt columns have face down cards
+        let dealt = self.dealt_as(column).unwrap_or(column);
+        CardId::revealed(dealt as u64, left)
+    }
+
+    /// puts `card` on top of `column` without checking the rules, for
+    /// debugging. returns false if there's no such column or it's full.
+    pub fn place(&mut self, column: usize, card: BitCard) -> bool {
+        match self.tableau.get_mut(column) {
+            Some(stack) if stack.visible().len() < 13 && self.rules.deck.contains(card) => {
+                stack.visible_mut().push(card, CardId::placed(self.placed));
+                self.placed += 1;
+                self.explored = self.explored.max(column + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// the face down card `row` cards up from the bottom of `column`. every
+    /// reveal is decided by the deal, so this is the card that will come up
+    /// when it's turned over, whatever happens before then.
+    pub fn hidden_card(&self, column: usize, row: u32) -> Option<BitCard> {
+        let stack = self.tableau.get(column)?;
+        if row >= stack.under {
+            return None;
+        }
+        // reveals are salted with how many cards are left face down
+        Some(stack.turned_under_at(row).unwrap_or_else(|| {
+            self.rules
+                .deck
+                .random_card(&mut self.column_rng(column, (row + 1).into()))
+        }))
+    }
+
+    /// takes the last face up card off `column` for good, for the sandbox
+    pub fn remove(&mut self, column: usize) -> Option<BitCard> {
+        let (card, _) = self.tableau.get_mut(column)?.visible_mut().pop()?;
+        self.reveal(column);
+        self.explored = self.explored.max(column + 1);
+        Some(card)
+    }
+
+    /// turns the bottom face up card of `column` back face down, for
+    /// [`Rules::decay`] games. the last face up card always stays, so the
+    /// column is never left with nothing to play on. the card is the next
+    /// one turned over there, the same card with the same id. returns the
+    /// card that went, if one did.
+    pub fn decay(&mut self, column: usize) -> Option<BitCard> {
+        let stack = self.tableau.get_mut(column)?;
+        if stack.visible().len() < 2 {
+            return None;
+        }
+        let card = stack.turn_under();
+        self.explored = self.explored.max(column + 1);
+        card
+    }
+
+    /// turns over the next face down card of `column` onto its face up cards,
+    /// even when there are some, for debugging. it's the same card a normal
+    /// reveal would have turned over.
+    pub fn force_reveal(&mut self, column: usize) -> Option<BitCard> {
+        let under = self.tableau.get(column)?.under;
+        if under == 0 || self.tableau[column].visible().len() >= 13 {
+            return None;
+        }
+        let card = self
+            .rules
+            .deck
+            .random_card(&mut self.column_rng(column, under.into()));
+        let id = self.revealed_id(column, under - 1);
+        let card = self.tableau[column].turn_over(card, id);
+        self.explored = self.explored.max(column + 1);
+        Some(card)
+    }
+
+    /// a hash of everything on the board, stable across platforms and
+    /// versions since replay version 2. it goes over the changed columns and
+    /// how many there are in all, so it's as cheap however far the board
+    /// goes.
+    pub fn state_hash(&self) -> u64 {
+        self.hash_board(false)
+    }
+
+    /// the hash replays from before version 2 were checked with, going over
+    /// every column of the board one by one. as slow as the board is long.
+    pub(crate) fn full_state_hash(&self) -> u64 {
+        self.hash_board(true)
+    }
+
+    /// [`Game::state_hash`], or [`Game::full_state_hash`] with `every_column`
+    fn hash_board(&self, every_column: bool) -> u64 {
+        let mut hash = Fnv::new();
+        hash.write(&self.seed.to_le_bytes());
+        // only written for other rules, so hashes of normal games stay the same
+        if self.rules.topology != Topology::Line {
+            hash.write(self.rules.topology.to_string().as_bytes());
+        }
+        if self.rules.sandbox {
+            hash.write(b"sandbox");
+        }
+        if self.rules.practice {
+            hash.write(b"practice");
+        }
+        if self.rules.decay {
+            hash.write(b"decay");
+        }
+        if self.rules.reveal != Reveal::One {
+            hash.write(self.rules.reveal.to_string().as_bytes());
+        }
+        if self.reshuffles > 0 {
+            hash.write(b"reshuffles");
+            hash.write(&self.reshuffles.to_le_bytes());
+        }
+        if self.rules.stock {
+            hash.write(b"stock");
+            hash.write(&self.drawn.to_le_bytes());
+            hash.write(&(self.waste.len() as u64).to_le_bytes());
+            for card in &self.waste {
+                hash.write(&[card.bits()]);
+            }
+        }
+        if !self.inserted.is_empty() {
+            hash.write(b"inserted");
+            for x in &self.inserted {
+                hash.write(&(*x as u64).to_le_bytes());
+            }
+        }
+        hash.write(&(self.columns() as u64).to_le_bytes());
+        // the changed columns say where they are, every column doesn't need to
+        let columns: Box<dyn Iterator<Item = (Option<usize>, Column)>> = if every_column {
+            Box::new(self.all_columns().map(|column| (None, column)))
+        } else {
+            // a column with the cards it was dealt counts as untouched, even
+            // if they're different cards of the same rank and suit
+            let changed = (self.all_changed_columns().into_iter())
+                .filter(|(x, column)| !self.has_dealt_cards(*x, column))
+                .map(|(x, column)| (Some(x), column));
+            Box::new(changed)
+        };
+        for (x, column) in columns {
+            if let Some(x) = x {
+                hash.write(&(x as u64).to_le_bytes());
+            }
+            hash.write(&column.under.to_le_bytes());
+            hash.write(&[column.visible().len()]);
+            for card in column.visible().iter() {
+                hash.write(&[card.bits()]);
+            }
+            // only decay games have any
+            if !column.turned_under().is_empty() {
+                hash.write(b"turned under");
+                for (card, _) in column.turned_under() {
+                    hash.write(&[card.bits()]);
+                }
+            }
+        }
+        let mut foundations: Vec<_> = self.foundations.iter().collect();
+        foundations.sort_by_key(|(idx, _)| **idx);
+        for (idx, card) in foundations {
+            hash.write(&(*idx as u64).to_le_bytes());
+            hash.write(&[card.bits()]);
+        }
+        hash.finish()
+    }
+
+    /// a zobrist hash of the columns in `window`, the foundations and the
+    /// reshuffles used. every card in its place gets its own random key and
+    /// they're all xored together, so it's much cheaper than `state_hash` on
+    /// a big board. only for telling apart positions of the same game that
+    /// differ inside the window, it doesn't include the seed or rules.
+    pub fn window_hash(&self, window: impl IntoIterator<Item = usize>) -> u64 {
+        let mut hash = zobrist(0, ZOBRIST_RESHUFFLES | self.reshuffles as u64);
+        // the waste is all that matters of the stock, every card is new
+        for (idx, card) in self.waste.iter().enumerate() {
+            hash ^= zobrist(idx as u64, ZOBRIST_STOCK | u64::from(card.bits()));
+        }
+        for x in window {
+            let column = self.column(x);
+            hash ^= zobrist(x as u64, ZOBRIST_UNDER | u64::from(column.under));
+            for (idx, card) in column.visible().iter().enumerate() {
+                let feature = ZOBRIST_CARD | (idx as u64) << 8 | u64::from(card.bits());
+                hash ^= zobrist(x as u64, feature);
+            }
+        }
+        for (idx, card) in &self.foundations {
+            hash ^= zobrist(*idx as u64, ZOBRIST_FOUNDATION | u64::from(card.bits()));
+        }
+        hash
+    }
+}
+
+/// the zobrist key for `feature` of column or foundation `place`
+fn zobrist(place: u64, feature: u64) -> u64 {
+    splitmix(splitmix(place) ^ feature)
+}
+
+fn splitmix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a, since `DefaultHasher` makes no promises about staying the same
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cards::Suit;
+
+    /// plays the first legal moves in `window`, putting cards on foundations
+    /// and turning cards over when it can
+    pub(crate) fn play(game: &mut Game, window: Range<usize>, moves: usize) -> Vec<Move> {
+        let mut played = Vec::new();
+        for _ in 0..moves {
+            let legal = game.legal_moves(window.clone());
+            let pick = (legal.iter())
+                .find(|mv| matches!(mv, Move::Foundation { .. }))
+                .or_else(|| (legal.iter()).find(|mv| matches!(mv, Move::Tableau { idx: 0, .. })))
+                .or(legal.first());
+            let Some(&mv) = pick else {
+                break;
+            };
+            game.apply(mv).unwrap();
+            played.push(mv);
+        }
+        played
+    }
+
+    fn decay_game(columns: usize) -> Game {
+        let rules = Rules {
+            decay: true,
+            ..Rules::default()
+        };
+        Game::with_rules(1, columns, rules)
+    }
+
+    /// moves the only card of column `x` onto the next one, returning the
+    /// cards that turned over under it
+    fn empty_column(game: &mut Game, x: usize) -> Vec<Event> {
+        let events = game.apply(Move::Tableau {
+            from: x,
+            idx: 0,
+            to: x + 1,
+        });
+        (events.unwrap().into_iter())
+            .filter(|event| matches!(event, Event::Revealed { .. }))
+            .collect()
+    }
+
+    fn reveal_game(reveal: Reveal) -> Game {
+        let rules = Rules {
+            sandbox: true,
+            reveal,
+            ..Rules::default()
+        };
+        Game::with_rules(3, 100, rules)
+    }
+
+    #[test]
+    fn reveal_one_turns_one_card_over() {
+        let mut game = reveal_game(Reveal::One);
+        assert_eq!(empty_column(&mut game, 5).len(), 1);
+        assert_eq!(game.tableau()[5].under, 4);
+        assert_eq!(game.tableau()[5].visible().len(), 1);
+    }
+
+    #[test]
+    fn reveal_many_turns_that_many_over() {
+        let mut game = reveal_game(Reveal::Many(3));
+        assert_eq!(empty_column(&mut game, 5).len(), 3);
+        assert_eq!(game.tableau()[5].under, 2);
+        assert_eq!(game.tableau()[5].visible().len(), 3);
+        // or all of them if there aren't that many
+        assert_eq!(empty_column(&mut game, 2).len(), 2);
+        assert_eq!(game.tableau()[2].under, 0);
+    }
+
+    #[test]
+    fn reveal_chance_sometimes_misses() {
+        let mut game = reveal_game(Reveal::Chance(100));
+        assert_eq!(empty_column(&mut game, 5).len(), 1);
+
+        let mut game = reveal_game(Reveal::Chance(50));
+        let mut hits = 0;
+        for x in (2..82).step_by(2) {
+            let under = game.tableau()[x].under;
+            match empty_column(&mut game, x).len() {
+                1 => hits += 1,
+                0 => {
+                    assert!(game.tableau()[x].is_visible_empty());
+                    assert_eq!(game.tableau()[x].under, under);
+                    assert!(game.missed.contains(&x));
+                }
+                n => panic!("turned {} cards over", n),
+            }
+        }
+        assert!(hits > 0 && hits < 40, "{} hits", hits);
+    }
+
+    fn sandbox_game(columns: usize) -> Game {
+        let rules = Rules {
+            sandbox: true,
+            ..Rules::default()
+        };
+        Game::with_rules(1, columns, rules)
+    }
+
+    /// moves column 63 onto column 64, the first column of the next chunk
+    fn move_across_chunks(game: &mut Game) {
+        let last = Tableau::CHUNK - 1;
+        let card = game.tableau()[last].visible().last();
+        let mv = Move::Tableau {
+            from: last,
+            idx: 0,
+            to: Tableau::CHUNK,
+        };
+        game.apply(mv).unwrap();
+        assert_eq!(game.tableau()[Tableau::CHUNK].visible().last(), card);
+        assert_eq!(game.tableau()[Tableau::CHUNK].visible().len(), 2);
+    }
+
+    #[test]
+    fn moves_across_a_chunk_boundary() {
+        let mut game = sandbox_game(2 * Tableau::CHUNK);
+        let before = game.state_hash();
+        move_across_chunks(&mut game);
+        assert_ne!(game.state_hash(), before);
+        let changed: Vec<usize> = (game.changed_columns().into_iter())
+            .map(|(x, _)| x)
+            .collect();
+        assert_eq!(changed, [Tableau::CHUNK - 1, Tableau::CHUNK]);
+    }
+
+    #[test]
+    fn evicted_chunks_come_back_the_same() {
+        let window = Tableau::CHUNK - 4..Tableau::CHUNK + 4;
+        let mut game = sandbox_game(3 * Tableau::CHUNK);
+        move_across_chunks(&mut game);
+        let hash = game.window_hash(window.clone());
+        let columns: Vec<Column> = window.clone().map(|x| game.column(x)).collect();
+
+        game.forget_outside(2 * Tableau::CHUNK..3 * Tableau::CHUNK);
+        assert!(!game.tableau.has_chunk(0) && !game.tableau.has_chunk(1));
+        // the hash reads evicted columns without dealing them
+        assert_eq!(game.window_hash(window.clone()), hash);
+        game.ensure_range(window.clone());
+        assert!(game.tableau.has_chunk(0) && game.tableau.has_chunk(1));
+        assert_eq!(game.window_hash(window.clone()), hash);
+        for (x, column) in window.clone().zip(&columns) {
+            assert_eq!(game.tableau()[x].visible().ids(), column.visible().ids());
+        }
+
+        // and the same through the disk, where only changed columns are written
+        let dir = std::env::temp_dir().join(format!("ik-chunk-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        game.stream_to(World::open(&dir).unwrap());
+        game.forget_outside(2 * Tableau::CHUNK..3 * Tableau::CHUNK);
+        assert!(!game.tableau.has_chunk(0) && !game.tableau.has_chunk(1));
+        let streamed: Vec<usize> = (game.streamed_columns().into_iter())
+            .map(|(x, _)| x)
+            .collect();
+        assert_eq!(streamed, [Tableau::CHUNK - 1, Tableau::CHUNK]);
+        assert_eq!(game.window_hash(window.clone()), hash);
+        game.ensure_range(window.clone());
+        assert_eq!(game.window_hash(window.clone()), hash);
+        for (x, column) in window.clone().zip(&columns) {
+            assert_eq!(game.tableau()[x].visible().ids(), column.visible().ids());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// `window_hash` worked out again from nothing but the dealt columns
+    fn recomputed_hash(game: &Game, window: Range<usize>) -> u64 {
+        let mut hash = zobrist(0, ZOBRIST_RESHUFFLES | game.reshuffles_used() as u64);
+        for x in window {
+            let column = &game.tableau()[x];
+            hash ^= zobrist(x as u64, ZOBRIST_UNDER | u64::from(column.under));
+            for (idx, card) in column.visible().iter().enumerate() {
+                let feature = ZOBRIST_CARD | (idx as u64) << 8 | u64::from(card.bits());
+                hash ^= zobrist(x as u64, feature);
+            }
+        }
+        for (idx, card) in game.foundations() {
+            hash ^= zobrist(*idx as u64, ZOBRIST_FOUNDATION | u64::from(card.bits()));
+        }
+        hash
+    }
+
+    #[test]
+    fn window_hash_matches_a_recompute() {
+        let mut game = Game::new(7, 20);
+        let start = game.window_hash(0..20);
+        assert_eq!(start, recomputed_hash(&game, 0..20));
+        for _ in 0..10 {
+            if play(&mut game, 0..20, 1).is_empty() {
+                break;
+            }
+            assert_eq!(game.window_hash(0..20), recomputed_hash(&game, 0..20));
+        }
+        assert_ne!(game.window_hash(0..20), start);
+    }
+
+    #[test]
+    fn window_hash_is_the_same_whatever_order_moves_came_in() {
+        let first = Move::Tableau {
+            from: 2,
+            idx: 0,
+            to: 3,
+        };
+        let second = Move::Tableau {
+            from: 5,
+            idx: 0,
+            to: 6,
+        };
+        let mut one = sandbox_game(10);
+        one.apply(first).unwrap();
+        one.apply(second).unwrap();
+        let mut other = sandbox_game(10);
+        other.apply(second).unwrap();
+        other.apply(first).unwrap();
+        assert_eq!(one.window_hash(0..10), other.window_hash(0..10));
+        assert_eq!(one.window_hash(0..10), recomputed_hash(&other, 0..10));
+    }
+
+    #[test]
+    fn decaying_past_the_deal_turns_nothing_over() {
+        let mut game = decay_game(10);
+        // column 0 is dealt without face down cards
+        for number in (0..6).rev() {
+            assert!(game.place(0, BitCard::new(number, Suit::Heart)));
+        }
+        while game.decay(0).is_some() {}
+        assert!(game.tableau()[0].under > game.hidden(0));
+        assert_eq!(game.turned_over(), 0);
+    }
+
+    #[test]
+    fn decayed_cards_count_as_turned_over_once() {
+        let mut game = decay_game(2 * Tableau::CHUNK);
+        assert_eq!(game.hidden(3), 3);
+        game.force_reveal(3).unwrap();
+        game.force_reveal(3).unwrap();
+        assert_eq!(game.turned_over(), 2);
+        game.decay(3).unwrap();
+        assert_eq!(game.tableau()[3].under, 2);
+        assert_eq!(game.turned_over(), 2);
+
+        // and the same once it's streamed out to disk
+        let dir = std::env::temp_dir().join(format!("ik-game-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        game.stream_to(World::open(&dir).unwrap());
+        game.forget_outside(Tableau::CHUNK..2 * Tableau::CHUNK);
+        assert!(!game.tableau.has_chunk(0));
+        assert_eq!(game.streamed_decayed(), [(3, 1)]);
+        assert_eq!(game.turned_over(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
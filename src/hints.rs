@@ -0,0 +1,142 @@
+//! hints. shift+h lights up a move on screen, the card to move and where it
+//! can go, and pressing it again moves on to the next one. while carrying
+//! cards it lights up every place on screen they can go instead, which is
+//! easy to miss when it's a column far across the window.
+
+use infiniteklondike::game::{Game, Move};
+use macroquad::prelude::*;
+
+use crate::style::Style;
+
+/// something on the board a hint points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spot {
+    /// the face up card `idx` of `column`
+    Card {
+        column: usize,
+        idx: usize,
+    },
+    /// the end of `column`, where cards put on it would go
+    End(usize),
+    Foundation(usize),
+    /// the top card of the waste
+    Waste,
+}
+
+impl Spot {
+    /// where `mv` takes cards from and where it puts them
+    pub fn of(game: &Game, mv: Move) -> [Spot; 2] {
+        let top = |column: usize| {
+            let len: usize = game.tableau()[column].visible().len().into();
+            Spot::Card {
+                column,
+                idx: len.saturating_sub(1),
+            }
+        };
+        match mv {
+            Move::Tableau { from, idx, to } => [Spot::Card { column: from, idx }, Spot::End(to)],
+            Move::Insert { from, idx, at } => [Spot::Card { column: from, idx }, Spot::End(at)],
+            Move::Foundation { from, foundation } => [top(from), Spot::Foundation(foundation)],
+            Move::Waste { to } => [Spot::Waste, Spot::End(to)],
+            Move::WasteFoundation { foundation } => [Spot::Waste, Spot::Foundation(foundation)],
+            Move::Reshuffle { column } => [top(column), top(column)],
+            Move::Draw => [Spot::Waste, Spot::Waste],
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Hints {
+    /// what's lit up right now
+    spots: Vec<Spot>,
+    /// when they were lit up, from `get_time`
+    since: f64,
+    /// how many moves had been made then, since any move makes them stale
+    moves: usize,
+    /// which move on screen the next press shows
+    next: usize,
+}
+
+impl Hints {
+    /// how long a hint stays lit up, in seconds
+    const SHOW_FOR: f64 = 3.0;
+
+    /// lights up the next of `moves`, the ones on screen, with `made` being
+    /// how many moves have been made so far
+    pub fn next_move(&mut self, game: &Game, mut moves: Vec<Move>, made: usize) {
+        if moves.is_empty() {
+            self.next = 0;
+            self.show(Vec::new(), made);
+            return;
+        }
+        moves.sort_by_key(|mv| usefulness(game, *mv));
+        let mv = moves[self.next % moves.len()];
+        self.next = (self.next + 1) % moves.len();
+        self.show(Spot::of(game, mv).to_vec(), made);
+    }
+
+    /// lights up everywhere carried cards can go
+    pub fn places(&mut self, spots: Vec<Spot>, made: usize) {
+        self.show(spots, made);
+    }
+
+    fn show(&mut self, spots: Vec<Spot>, made: usize) {
+        self.spots = spots;
+        self.since = get_time();
+        self.moves = made;
+    }
+
+    /// what to light up now, given how many moves have been made. nothing
+    /// once the hint is old or a move has been made since.
+    pub fn spots(&self, made: usize) -> &[Spot] {
+        if made != self.moves || get_time() - self.since > Self::SHOW_FOR {
+            return &[];
+        }
+        &self.spots
+    }
+
+    /// whether the last press found nothing to show, so it can say so
+    pub fn is_empty(&self) -> bool {
+        self.spots.is_empty()
+    }
+}
+
+/// lower comes first: moves that put cards on foundations, then ones that
+/// turn a card over, then playing off the waste, then the rest
+fn usefulness(game: &Game, mv: Move) -> u8 {
+    match mv {
+        Move::Foundation { .. } | Move::WasteFoundation { .. } => 0,
+        Move::Tableau { from, idx: 0, .. } if game.tableau()[from].under > 0 => 1,
+        Move::Waste { .. } => 2,
+        _ => 3,
+    }
+}
+
+/// a glow around something the size of a card at `pos`, in layout coordinates
+pub fn draw_glow(pos: Vec2, size: Vec2, style: Style) {
+    if style.monochrome {
+        draw_rectangle_lines(
+            pos.x - 3.0,
+            pos.y - 3.0,
+            size.x + 6.0,
+            size.y + 6.0,
+            3.0,
+            WHITE,
+        );
+        return;
+    }
+    // pulses so it catches the eye
+    let pulse = 0.5 + 0.5 * (get_time() * 6.0).sin() as f32;
+    for ring in 1..=3 {
+        let grow = ring as f32 * 2.0;
+        let color = Color::new(1.0, 0.85, 0.2, (0.3 + 0.4 * pulse) / ring as f32);
+        draw_rectangle_lines(
+            pos.x - grow,
+            pos.y - grow,
+            size.x + 2.0 * grow,
+            size.y + 2.0 * grow,
+            2.0,
+            color,
+        );
+    }
+}
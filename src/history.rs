@@ -0,0 +1,180 @@
+//! where the cards on the board came from and where they've been this
+//! session, shown by holding alt over a card. the deck never runs out, so
+//! there can be any number of the same card around, but every one has an id
+//! that says where it came from and follows it through every move.
+
+use std::collections::HashMap;
+
+use infiniteklondike::cards::{BitCard, CardId, Origin};
+use infiniteklondike::game::{Event, Game, Move};
+use macroquad::prelude::*;
+
+/// something a card did, with which move it was
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Moved {
+        from: usize,
+        to: usize,
+        at: usize,
+    },
+    Founded {
+        from: usize,
+        foundation: usize,
+        at: usize,
+    },
+    /// played from the waste onto column `to`
+    Played {
+        to: usize,
+        at: usize,
+    },
+    /// played from the waste onto a foundation
+    PlayedFounded {
+        foundation: usize,
+        at: usize,
+    },
+}
+
+#[derive(Default)]
+pub struct History {
+    /// keeps nothing, for low memory mode
+    off: bool,
+    /// what every card that's moved this session did
+    steps: HashMap<CardId, Vec<Step>>,
+    /// the cards the move about to be made moves
+    moving: Vec<CardId>,
+}
+
+impl History {
+    /// a history that doesn't keep anything when `low_memory` is set
+    pub fn new(low_memory: bool) -> Self {
+        Self {
+            off: low_memory,
+            ..Self::default()
+        }
+    }
+
+    /// call right before `mv` is made, while the board is still as it was
+    pub fn prepare(&mut self, game: &Game, mv: Move) {
+        self.moving.clear();
+        if self.off {
+            return;
+        }
+        let ids = |column: usize| {
+            game.tableau()
+                .get(column)
+                .map_or(&[][..], |stack| stack.visible().ids())
+        };
+        match mv {
+            Move::Tableau { from, idx, .. } | Move::Insert { from, idx, .. } => {
+                self.moving.extend(ids(from).get(idx..).unwrap_or_default());
+            }
+            Move::Foundation { from, .. } => self.moving.extend(ids(from).last()),
+            Move::Waste { .. } | Move::WasteFoundation { .. } => {
+                self.moving.extend(game.waste_ids().last());
+            }
+            Move::Reshuffle { .. } | Move::Draw => {}
+        }
+    }
+
+    /// follows the cards through a move, which was move number `at`
+    pub fn record(&mut self, events: &[Event], at: usize) {
+        if self.off {
+            return;
+        }
+        let Some(Event::Moved(mv)) = events.first() else {
+            return;
+        };
+        let step = match *mv {
+            Move::Tableau { from, to, .. } | Move::Insert { from, at: to, .. } => {
+                Step::Moved { from, to, at }
+            }
+            Move::Foundation { from, foundation } => Step::Founded {
+                from,
+                foundation,
+                at,
+            },
+            Move::Waste { to } => Step::Played { to, at },
+            Move::WasteFoundation { foundation } => Step::PlayedFounded { foundation, at },
+            Move::Reshuffle { .. } | Move::Draw => return,
+        };
+        for id in self.moving.drain(..) {
+            self.steps.entry(id).or_default().push(step);
+        }
+    }
+
+    /// what's known about the face up card `idx` cards up `column`, a line
+    /// at a time
+    pub fn inspect(&self, game: &Game, column: usize, idx: usize) -> Option<Vec<String>> {
+        let visible = game.tableau().get(column)?.visible();
+        let card = visible.iter().nth(idx)?;
+        Some(self.describe(card, visible.ids()[idx]))
+    }
+
+    /// what's known about the card on top of `foundation`
+    pub fn inspect_foundation(&self, game: &Game, foundation: usize) -> Option<Vec<String>> {
+        let card = *game.foundations().get(&foundation)?;
+        Some(self.describe(card, game.foundation_ids()[&foundation]))
+    }
+
+    fn describe(&self, card: BitCard, id: CardId) -> Vec<String> {
+        let mut lines = vec![format!("{} (card #{})", card, id)];
+        lines.push(match id.origin() {
+            Origin::Dealt(column) => format!("dealt face up on column {}", column),
+            Origin::Revealed { column, left } => {
+                format!("turned over on column {}, {} left under it", column, left)
+            }
+            Origin::Reshuffled(n) => format!("dealt by reshuffle {}", n + 1),
+            Origin::Drawn(n) => format!("card {} of the stock", n + 1),
+            Origin::Placed(_) => "put down by hand".to_owned(),
+            Origin::Unknown(_) => "already there when the game was loaded".to_owned(),
+        });
+        if self.off {
+            return lines;
+        }
+        let steps = self.steps.get(&id).map_or(&[][..], Vec::as_slice);
+        if steps.is_empty() {
+            lines.push("hasn't moved".to_owned());
+        }
+        for step in steps {
+            lines.push(match *step {
+                Step::Moved { from, to, at } => {
+                    format!("move {}: column {} to column {}", at + 1, from, to)
+                }
+                Step::Founded {
+                    from,
+                    foundation,
+                    at,
+                } => format!(
+                    "move {}: column {} to foundation {}",
+                    at + 1,
+                    from,
+                    foundation
+                ),
+                Step::Played { to, at } => format!("move {}: waste to column {}", at + 1, to),
+                Step::PlayedFounded { foundation, at } => {
+                    format!("move {}: waste to foundation {}", at + 1, foundation)
+                }
+            });
+        }
+        lines
+    }
+}
+
+/// draws what `inspect` found next to the mouse
+pub fn draw(lines: &[String], (x, y): (f32, f32)) {
+    const LINE_HEIGHT: f32 = 18.0;
+    let width = lines
+        .iter()
+        .map(|line| measure_text(line, None, 18, 1.0).width)
+        .fold(0.0, f32::max)
+        + 16.0;
+    let height = LINE_HEIGHT * lines.len() as f32 + 10.0;
+    // keep it on screen near the edges
+    let x = (x + 16.0).min(screen_width() - width).max(0.0);
+    let y = (y + 16.0).min(screen_height() - height).max(0.0);
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.85));
+    for (n, line) in lines.iter().enumerate() {
+        let color = if n == 0 { WHITE } else { GRAY };
+        draw_text(line, x + 8.0, y + LINE_HEIGHT * (n + 1) as f32, 18.0, color);
+    }
+}
@@ -0,0 +1,111 @@
+//! two players taking turns on the same board. each turn runs on a clock,
+//! and when it runs out a banner says whose turn it is, with the board
+//! locked for a moment so the last player can't sneak another move in.
+//! cards founded and points scored go to whoever's turn it was.
+
+use macroquad::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Player {
+    founded: usize,
+    score: i64,
+}
+
+pub struct HotSeat {
+    players: [Player; 2],
+    /// whose turn it is
+    turn: usize,
+    /// how long each turn is, in seconds
+    turn_length: f64,
+    /// seconds left in this turn. it only runs down while the game is
+    /// being played, not while the menu is open.
+    left: f64,
+    /// seconds left of the banner, and of the lockout with it
+    banner: f64,
+}
+
+impl HotSeat {
+    /// how long a turn is unless picked with `--turn-length`
+    pub const TURN_LENGTH: f64 = 60.0;
+    /// how long the turn change banner stays up, with the board locked
+    const BANNER: f64 = 2.0;
+
+    pub fn new(turn_length: f64) -> Self {
+        Self {
+            players: [Player::default(); 2],
+            turn: 0,
+            turn_length,
+            left: turn_length,
+            banner: Self::BANNER,
+        }
+    }
+
+    pub fn turn_length(&self) -> f64 {
+        self.turn_length
+    }
+
+    /// runs the clocks for a frame. returns true on the frame the turn
+    /// changes, so whatever the last player was holding can be let go of.
+    pub fn update(&mut self, elapsed: f64) -> bool {
+        if self.banner > 0.0 {
+            self.banner -= elapsed;
+            return false;
+        }
+        self.left -= elapsed;
+        if self.left > 0.0 {
+            return false;
+        }
+        self.turn = 1 - self.turn;
+        self.left = self.turn_length;
+        self.banner = Self::BANNER;
+        true
+    }
+
+    /// whether the board is locked while the banner is up
+    pub fn is_changing(&self) -> bool {
+        self.banner > 0.0
+    }
+
+    /// gives what a move did to whoever's turn it is
+    pub fn record(&mut self, founded: usize, points: i64) {
+        let player = &mut self.players[self.turn];
+        player.founded += founded;
+        player.score += points;
+    }
+
+    /// the turn line along the top, `top` pixels down so it can go under a
+    /// challenge countdown, and the banner when the turn changes
+    pub fn draw(&self, top: f32) {
+        let left = self.left.max(0.0).ceil() as u64;
+        let [one, two] = self.players;
+        let label = format!(
+            "player {}'s turn, {}:{:02} left   player 1: {} cards, {} points   player 2: {} cards, {} points",
+            self.turn + 1,
+            left / 60,
+            left % 60,
+            one.founded,
+            one.score,
+            two.founded,
+            two.score
+        );
+        let width = measure_text(&label, None, 20, 1.0).width + 16.0;
+        let x = (screen_width() - width) / 2.0;
+        draw_rectangle(x, top, width, 24.0, Color::new(0.0, 0.0, 0.0, 0.8));
+        draw_text(&label, x + 8.0, top + 17.0, 20.0, WHITE);
+
+        if !self.is_changing() {
+            return;
+        }
+        let banner = format!("player {}'s turn", self.turn + 1);
+        let size = measure_text(&banner, None, 48, 1.0);
+        let y = screen_height() / 2.0;
+        draw_rectangle(
+            0.0,
+            y - 48.0,
+            screen_width(),
+            72.0,
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        draw_text(&banner, (screen_width() - size.width) / 2.0, y, 48.0, WHITE);
+    }
+}
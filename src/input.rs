@@ -0,0 +1,657 @@
+//! the input layer. everything reads the keyboard and mouse through here
+//! instead of straight from macroquad, so that a script of recorded input
+//! can be played back on top of the real thing for testing.
+//!
+//! a script is a text file with one event per line, each starting with the
+//! time in seconds since the game started:
+//!
+//! ```text
+//! # grab the second card of column 2 and drop it on column 5
+//! 0.5 move 120 160
+//! 0.6 press left
+//! 0.7 move 260 100
+//! 0.8 release left
+//! 1.0 key escape
+//! 1.2 keydown lshift
+//! 1.3 keyup lshift
+//! 1.5 type my bookmark
+//! 2.0 quit
+//! ```
+//!
+//! `key` presses a key and lets go of it on the next frame. every event
+//! happens on the first frame at or after its time.
+//!
+//! the real input and the script's are also handed out together as
+//! [`InputEvent`]s through [`Window`], for the parts shared with other
+//! frontends.
+//!
+//! touch screens go through here too, turned into mouse input. a tap is a
+//! click, a finger that moves before it's been down long is a drag with the
+//! left button, and a long press holds down the right button instead, so
+//! moving the finger after it pans the board from anywhere.
+//!
+//! the mouse can also be slowed down, for placing cards with an unsteady
+//! hand. while it is, the pointer the game sees drifts away from the real
+//! one, and catches back up with it as the mouse moves once it isn't. the
+//! system pointer is hidden, so only the slow one is ever seen.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use infiniteklondike::frontend::{Button, InputEvent, InputSource, Key};
+use macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use macroquad::miniquad::{Context, EventHandler, KeyMods, TouchPhase};
+use macroquad::prelude as mq;
+use macroquad::prelude::{get_time, vec2, KeyCode, MouseButton, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    Move(Vec2),
+    Press(MouseButton),
+    Release(MouseButton),
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
+    /// down now, up next frame
+    Key(KeyCode),
+    Char(char),
+    Quit,
+}
+
+/// a parsed script, ready to play
+pub struct Script {
+    events: VecDeque<(f64, Event)>,
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut events = VecDeque::new();
+        for (idx, line) in text.lines().enumerate() {
+            let err = |reason: String| ScriptError {
+                line: idx + 1,
+                reason,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.splitn(3, ' ');
+            let time: f64 = words
+                .next()
+                .and_then(|time| time.parse().ok())
+                .ok_or_else(|| err("lines start with a time in seconds".to_owned()))?;
+            if let Some((last, _)) = events.back() {
+                if time < *last {
+                    return Err(err("events have to be in order".to_owned()));
+                }
+            }
+            let kind = words.next().unwrap_or("");
+            let rest = words.next().unwrap_or("").trim();
+            let key = || key_code(rest).ok_or_else(|| err(format!("unknown key `{}`", rest)));
+            let button = || match rest {
+                "left" => Ok(MouseButton::Left),
+                "right" => Ok(MouseButton::Right),
+                "middle" => Ok(MouseButton::Middle),
+                _ => Err(err(format!("unknown mouse button `{}`", rest))),
+            };
+            match kind {
+                "move" => {
+                    let point = rest
+                        .split_once(' ')
+                        .and_then(|(x, y)| Some(vec2(x.parse().ok()?, y.trim().parse().ok()?)))
+                        .ok_or_else(|| err("move needs an x and a y".to_owned()))?;
+                    events.push_back((time, Event::Move(point)));
+                }
+                "press" => events.push_back((time, Event::Press(button()?))),
+                "release" => events.push_back((time, Event::Release(button()?))),
+                "keydown" => events.push_back((time, Event::KeyDown(key()?))),
+                "keyup" => events.push_back((time, Event::KeyUp(key()?))),
+                "key" => events.push_back((time, Event::Key(key()?))),
+                "type" => events.extend(rest.chars().map(|c| (time, Event::Char(c)))),
+                "quit" => events.push_back((time, Event::Quit)),
+                _ => return Err(err(format!("unknown event `{}`", kind))),
+            }
+        }
+        Ok(Self { events })
+    }
+
+    pub fn load(path: &str) -> Result<Self, ScriptError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ScriptError {
+            line: 0,
+            reason: e.to_string(),
+        })?;
+        Self::parse(&text)
+    }
+}
+
+/// the input a script has made up so far
+#[derive(Default)]
+struct Player {
+    events: VecDeque<(f64, Event)>,
+    /// when the script started, from `get_time`
+    start: Option<f64>,
+    keys_down: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    /// keys from `key` events, to let go of next frame
+    tapped: Vec<KeyCode>,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    /// where the script last put the mouse
+    mouse: Option<Vec2>,
+    chars: VecDeque<char>,
+    quit: bool,
+}
+
+/// the finger on a touch screen, as a mouse
+#[derive(Default)]
+struct Finger {
+    /// which touch it's following, while one is down
+    id: Option<u64>,
+    /// when it went down, from `get_time`
+    since: f64,
+    /// where it went down
+    start: Vec2,
+    /// where it is, or was last. `None` once the real mouse moves.
+    at: Option<Vec2>,
+    /// the button it's holding down, once it's been decided whether it's a
+    /// drag or a long press
+    holding: Option<MouseButton>,
+    /// a tap to let go of next frame
+    tapped: bool,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    /// whether a finger has touched the screen yet, and whether it was
+    /// this frame that one first did
+    seen: bool,
+    first: bool,
+}
+
+impl Finger {
+    /// how long a finger has to stay put to be a long press, in seconds
+    const LONG_PRESS: f64 = 0.5;
+    /// how far it can wander, in pixels, and still be a tap or long press
+    const SLOP: f32 = 12.0;
+
+    fn press(&mut self, button: MouseButton, out: &mut Vec<InputEvent>) {
+        self.buttons_down.insert(button);
+        self.buttons_pressed.insert(button);
+        out.extend(normal_button(button).map(InputEvent::PointerDown));
+    }
+
+    fn release(&mut self, button: MouseButton, out: &mut Vec<InputEvent>) {
+        self.buttons_down.remove(&button);
+        self.buttons_released.insert(button);
+        out.extend(normal_button(button).map(InputEvent::PointerUp));
+    }
+
+    /// follows the finger for a frame, returning whether it did anything
+    fn update(&mut self, out: &mut Vec<InputEvent>) -> bool {
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.first = false;
+        if std::mem::take(&mut self.tapped) {
+            self.release(MouseButton::Left, out);
+        }
+        let touches = mq::touches();
+        // only one finger, the first one down
+        let Some(touch) = touches
+            .iter()
+            .find(|touch| Some(touch.id) == self.id)
+            .or_else(|| touches.iter().find(|_| self.id.is_none()))
+        else {
+            return false;
+        };
+        let now = get_time();
+        // touches come in real pixels, not window pixels like the mouse
+        let pos = touch.position / dpi_scale();
+        if self.id.is_none() {
+            self.id = Some(touch.id);
+            self.since = now;
+            self.start = pos;
+            self.holding = None;
+            self.first = !self.seen;
+            self.seen = true;
+        }
+        self.at = Some(pos);
+        out.push(InputEvent::Pointer { x: pos.x, y: pos.y });
+        match touch.phase {
+            mq::TouchPhase::Ended | mq::TouchPhase::Cancelled => {
+                self.id = None;
+                match self.holding.take() {
+                    Some(button) => self.release(button, out),
+                    None => {
+                        // down now and up next frame, like a click
+                        self.press(MouseButton::Left, out);
+                        self.tapped = true;
+                    }
+                }
+            }
+            _ if self.holding.is_some() => {}
+            _ if pos.distance(self.start) > Self::SLOP => {
+                self.holding = Some(MouseButton::Left);
+                self.press(MouseButton::Left, out);
+            }
+            _ if now - self.since >= Self::LONG_PRESS => {
+                self.holding = Some(MouseButton::Right);
+                self.press(MouseButton::Right, out);
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+/// the mouse, slowed down while `set_precision` says so
+#[derive(Default)]
+struct Precise {
+    /// how fast the pointer goes compared to the mouse, while it's slowed
+    factor: Option<f32>,
+    /// how far the pointer is from the real mouse
+    offset: Vec2,
+    /// where the real mouse was last frame
+    last: Option<Vec2>,
+}
+
+impl Precise {
+    /// follows the real mouse for a frame
+    fn update(&mut self) {
+        let real = Vec2::from(mq::mouse_position());
+        let moved = real - self.last.unwrap_or(real);
+        self.last = Some(real);
+        match self.factor {
+            Some(factor) => self.offset += moved * (factor - 1.0),
+            // as fast as the mouse moves, so it never jumps
+            None => self.offset -= self.offset.clamp_length_max(moved.length()),
+        }
+        // never off the window, where it couldn't be seen
+        let window = vec2(mq::screen_width(), mq::screen_height());
+        self.offset = (real + self.offset).clamp(Vec2::ZERO, window) - real;
+    }
+}
+
+thread_local! {
+    static FINGER: RefCell<Finger> = RefCell::new(Finger::default());
+    static PRECISE: RefCell<Precise> = RefCell::new(Precise::default());
+    static PLAYER: RefCell<Option<Player>> = const { RefCell::new(None) };
+    /// which of macroquad's input queues is used to notice input
+    static SUBSCRIBER: Cell<Option<usize>> = const { Cell::new(None) };
+    /// whether there was any input this frame
+    static HAD_INPUT: Cell<bool> = const { Cell::new(false) };
+    /// this frame's input, for `Window`
+    static EVENTS: RefCell<Vec<InputEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+/// the window's mouse and keyboard, with any script playing on top, as an
+/// `InputSource`. `update` has to be called every frame for it to see
+/// anything, and each frame's events only come out once.
+pub struct Window;
+
+impl InputSource for Window {
+    fn poll(&mut self, _now: f64) -> Vec<InputEvent> {
+        EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()))
+    }
+}
+
+fn with_player<T>(f: impl FnOnce(&mut Player) -> T) -> Option<T> {
+    PLAYER.with(|player| player.borrow_mut().as_mut().map(f))
+}
+
+/// starts playing `script` on top of the real input
+pub fn play(script: Script) {
+    PLAYER.with(|player| {
+        *player.borrow_mut() = Some(Player {
+            events: script.events,
+            ..Default::default()
+        })
+    });
+}
+
+/// whether a script still has events left to play
+pub fn is_playing() -> bool {
+    with_player(|player| !player.events.is_empty()).unwrap_or(false)
+}
+
+/// whether anything was pressed, moved or typed this frame, for real or by
+/// a script
+pub fn had_input() -> bool {
+    HAD_INPUT.with(Cell::get)
+}
+
+/// moves the script along and notes whether there was any input. call once
+/// at the start of every frame.
+pub fn update() {
+    let subscriber = SUBSCRIBER.with(|subscriber| {
+        let id = subscriber.get().unwrap_or_else(|| {
+            // fingers are turned into mouse input here instead
+            mq::simulate_mouse_with_touch(false);
+            register_input_subscriber()
+        });
+        subscriber.set(Some(id));
+        id
+    });
+    let mut activity = Activity::default();
+    repeat_all_miniquad_input(&mut activity, subscriber);
+    let touched = FINGER.with(|finger| {
+        let mut finger = finger.borrow_mut();
+        if activity.mouse_moved {
+            finger.at = None;
+        }
+        finger.update(&mut activity.events)
+    });
+    PRECISE.with(|precise| precise.borrow_mut().update());
+    let scripted = play_script(&mut activity.events);
+    HAD_INPUT.with(|had_input| had_input.set(activity.any || touched || scripted));
+    EVENTS.with(|events| *events.borrow_mut() = activity.events);
+}
+
+/// plays the events that are due, returning whether there were any. the
+/// ones that make `InputEvent`s go on the end of `out`.
+fn play_script(out: &mut Vec<InputEvent>) -> bool {
+    with_player(|player| {
+        let mut played = false;
+        let now = get_time();
+        let start = *player.start.get_or_insert(now);
+        player.keys_pressed.clear();
+        player.buttons_pressed.clear();
+        player.buttons_released.clear();
+        for key in player.tapped.drain(..) {
+            player.keys_down.remove(&key);
+            out.extend(normal_key(key).map(InputEvent::KeyUp));
+        }
+        while let Some((time, event)) = player.events.front().copied() {
+            if start + time > now {
+                break;
+            }
+            player.events.pop_front();
+            played = true;
+            match event {
+                Event::Move(point) => {
+                    player.mouse = Some(point);
+                    out.push(InputEvent::Pointer {
+                        x: point.x,
+                        y: point.y,
+                    });
+                }
+                Event::Press(button) => {
+                    player.buttons_down.insert(button);
+                    player.buttons_pressed.insert(button);
+                    out.extend(normal_button(button).map(InputEvent::PointerDown));
+                }
+                Event::Release(button) => {
+                    player.buttons_down.remove(&button);
+                    player.buttons_released.insert(button);
+                    out.extend(normal_button(button).map(InputEvent::PointerUp));
+                }
+                Event::KeyDown(key) => {
+                    player.keys_down.insert(key);
+                    player.keys_pressed.insert(key);
+                    out.extend(normal_key(key).map(InputEvent::KeyDown));
+                }
+                Event::KeyUp(key) => {
+                    player.keys_down.remove(&key);
+                    out.extend(normal_key(key).map(InputEvent::KeyUp));
+                }
+                Event::Key(key) => {
+                    player.keys_down.insert(key);
+                    player.keys_pressed.insert(key);
+                    player.tapped.push(key);
+                    out.extend(normal_key(key).map(InputEvent::KeyDown));
+                }
+                Event::Char(c) => {
+                    player.chars.push_back(c);
+                    out.push(InputEvent::KeyDown(Key::Char(c)));
+                }
+                Event::Quit => {
+                    player.quit = true;
+                    out.push(InputEvent::KeyDown(Key::Quit));
+                }
+            }
+        }
+        played
+    })
+    .unwrap_or(false)
+}
+
+pub fn is_key_pressed(key: KeyCode) -> bool {
+    mq::is_key_pressed(key) || with_player(|p| p.keys_pressed.contains(&key)).unwrap_or(false)
+}
+
+pub fn is_key_down(key: KeyCode) -> bool {
+    mq::is_key_down(key) || with_player(|p| p.keys_down.contains(&key)).unwrap_or(false)
+}
+
+pub fn is_mouse_button_pressed(button: MouseButton) -> bool {
+    mq::is_mouse_button_pressed(button)
+        || FINGER.with(|f| f.borrow().buttons_pressed.contains(&button))
+        || with_player(|p| p.buttons_pressed.contains(&button)).unwrap_or(false)
+}
+
+pub fn is_mouse_button_down(button: MouseButton) -> bool {
+    mq::is_mouse_button_down(button)
+        || FINGER.with(|f| f.borrow().buttons_down.contains(&button))
+        || with_player(|p| p.buttons_down.contains(&button)).unwrap_or(false)
+}
+
+pub fn is_mouse_button_released(button: MouseButton) -> bool {
+    mq::is_mouse_button_released(button)
+        || FINGER.with(|f| f.borrow().buttons_released.contains(&button))
+        || with_player(|p| p.buttons_released.contains(&button)).unwrap_or(false)
+}
+
+pub fn mouse_position() -> (f32, f32) {
+    with_player(|p| p.mouse)
+        .flatten()
+        .or_else(|| FINGER.with(|f| f.borrow().at))
+        .unwrap_or_else(|| Vec2::from(mq::mouse_position()) + PRECISE.with(|p| p.borrow().offset))
+        .into()
+}
+
+/// slows the mouse down to `factor` times its speed from the next frame
+/// on, or lets it go at full speed again with `None`
+pub fn set_precision(factor: Option<f32>) {
+    PRECISE.with(|precise| precise.borrow_mut().factor = factor);
+}
+
+/// how many real pixels there are to a window pixel. it's more than 1 on
+/// high dpi screens, where macroquad counts everything but touches and
+/// viewports in window pixels so things come out the same size as on any
+/// other screen.
+pub fn dpi_scale() -> f32 {
+    // only reads the scale, nothing is drawn through the context
+    unsafe { mq::get_internal_gl() }.quad_context.dpi_scale()
+}
+
+/// whether the screen has been touched, so the game can play nicely with
+/// fingers
+pub fn is_touch() -> bool {
+    FINGER.with(|f| f.borrow().seen)
+}
+
+/// whether this is the frame the screen was first touched
+pub fn first_touch() -> bool {
+    FINGER.with(|f| f.borrow().first)
+}
+
+pub fn get_char_pressed() -> Option<char> {
+    with_player(|p| p.chars.pop_front())
+        .flatten()
+        .or_else(mq::get_char_pressed)
+}
+
+pub fn is_quit_requested() -> bool {
+    mq::is_quit_requested() || with_player(|p| p.quit).unwrap_or(false)
+}
+
+/// notes whether any input happened, and what it was
+#[derive(Default)]
+struct Activity {
+    any: bool,
+    /// whether the real mouse moved, as opposed to a finger
+    mouse_moved: bool,
+    events: Vec<InputEvent>,
+}
+
+impl EventHandler for Activity {
+    fn update(&mut self, _ctx: &mut Context) {}
+    fn draw(&mut self, _ctx: &mut Context) {}
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32) {
+        self.any = true;
+        self.mouse_moved = true;
+        self.events.push(InputEvent::Pointer { x, y });
+    }
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) {
+        self.any = true;
+    }
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, _: f32, _: f32) {
+        self.any = true;
+        self.events
+            .extend(normal_button(button).map(InputEvent::PointerDown));
+    }
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, _: f32, _: f32) {
+        self.any = true;
+        self.events
+            .extend(normal_button(button).map(InputEvent::PointerUp));
+    }
+    fn touch_event(&mut self, _ctx: &mut Context, _: TouchPhase, _id: u64, _: f32, _: f32) {
+        // `Finger` makes the events, this is only here so the default
+        // doesn't make mouse ones as well
+        self.any = true;
+    }
+    fn char_event(&mut self, _ctx: &mut Context, c: char, _: KeyMods, _repeat: bool) {
+        self.any = true;
+        if !c.is_control() {
+            self.events.push(InputEvent::KeyDown(Key::Char(c)));
+        }
+    }
+    fn key_down_event(&mut self, _ctx: &mut Context, key: KeyCode, _: KeyMods, _repeat: bool) {
+        self.any = true;
+        self.events.extend(normal_key(key).map(InputEvent::KeyDown));
+    }
+    fn key_up_event(&mut self, _ctx: &mut Context, key: KeyCode, _: KeyMods) {
+        self.any = true;
+        self.events.extend(normal_key(key).map(InputEvent::KeyUp));
+    }
+}
+
+fn normal_button(button: MouseButton) -> Option<Button> {
+    match button {
+        MouseButton::Left => Some(Button::Primary),
+        MouseButton::Right => Some(Button::Secondary),
+        MouseButton::Middle => Some(Button::Middle),
+        MouseButton::Unknown => None,
+    }
+}
+
+/// the keys that mean the same thing in every frontend. letters come
+/// through as typed characters instead.
+fn normal_key(key: KeyCode) -> Option<Key> {
+    match key {
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Enter | KeyCode::KpEnter | KeyCode::Space => Some(Key::Select),
+        KeyCode::Escape => Some(Key::Cancel),
+        _ => None,
+    }
+}
+
+/// the names keys go by in scripts
+fn key_code(name: &str) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::A,
+        KeyCode::B,
+        KeyCode::C,
+        KeyCode::D,
+        KeyCode::E,
+        KeyCode::F,
+        KeyCode::G,
+        KeyCode::H,
+        KeyCode::I,
+        KeyCode::J,
+        KeyCode::K,
+        KeyCode::L,
+        KeyCode::M,
+        KeyCode::N,
+        KeyCode::O,
+        KeyCode::P,
+        KeyCode::Q,
+        KeyCode::R,
+        KeyCode::S,
+        KeyCode::T,
+        KeyCode::U,
+        KeyCode::V,
+        KeyCode::W,
+        KeyCode::X,
+        KeyCode::Y,
+        KeyCode::Z,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Key0,
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ];
+    const FUNCTION: [KeyCode; 12] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+    ];
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_lowercase() {
+            return Some(LETTERS[(c as u8 - b'a') as usize]);
+        }
+        if let Some(digit) = c.to_digit(10) {
+            return Some(DIGITS[digit as usize]);
+        }
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<usize>().ok()) {
+        return FUNCTION.get(n.checked_sub(1)?).copied();
+    }
+    Some(match name {
+        "escape" => KeyCode::Escape,
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Space,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "lshift" => KeyCode::LeftShift,
+        "rshift" => KeyCode::RightShift,
+        "lctrl" => KeyCode::LeftControl,
+        "rctrl" => KeyCode::RightControl,
+        _ => return None,
+    })
+}
@@ -0,0 +1,654 @@
+//! just enough json to talk to other programs, without pulling in a
+//! serialization framework for a card game
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cards::{BitCard, CardId, Column};
+use crate::game::{Event, Game, Move};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// keys keep the order they were added in
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// starts an empty object, to be filled with [`Value::with`]
+    pub fn object() -> Self {
+        Value::Object(Vec::new())
+    }
+
+    /// adds a key to an object
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        if let Value::Object(fields) = &mut self {
+            fields.push((key.to_owned(), value.into()));
+        }
+        self
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+macro_rules! number_from {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(n: $t) -> Self {
+                Value::Number(n as f64)
+            }
+        })*
+    };
+}
+
+number_from!(u8, u16, u32, u64, usize, i32, i64, f32, f64);
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// compact json, all on one line. `{:#}` pretty prints it instead, with
+/// arrays of plain values kept on one line so card lists stay readable.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = if f.alternate() { Some(0) } else { None };
+        write_value(f, self, indent)
+    }
+}
+
+fn write_value(f: &mut fmt::Formatter<'_>, value: &Value, indent: Option<usize>) -> fmt::Result {
+    let newline = |f: &mut fmt::Formatter<'_>, depth: usize| write!(f, "\n{:1$}", "", depth * 2);
+    match value {
+        Value::Null => f.write_str("null"),
+        Value::Bool(b) => write!(f, "{}", b),
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+        Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+        Value::Number(_) => f.write_str("null"),
+        Value::String(s) => write_string(f, s),
+        Value::Array(values) => {
+            let flat = values
+                .iter()
+                .all(|value| !matches!(value, Value::Array(_) | Value::Object(_)));
+            let indent = indent.filter(|_| !flat);
+            f.write_str("[")?;
+            for (n, value) in values.iter().enumerate() {
+                if n > 0 {
+                    f.write_str(",")?;
+                }
+                match indent {
+                    Some(depth) => newline(f, depth + 1)?,
+                    None if n > 0 && f.alternate() => f.write_str(" ")?,
+                    None => {}
+                }
+                write_value(f, value, indent.map(|depth| depth + 1))?;
+            }
+            if let (Some(depth), false) = (indent, values.is_empty()) {
+                newline(f, depth)?;
+            }
+            f.write_str("]")
+        }
+        Value::Object(fields) => {
+            f.write_str("{")?;
+            for (n, (key, value)) in fields.iter().enumerate() {
+                if n > 0 {
+                    f.write_str(",")?;
+                }
+                if let Some(depth) = indent {
+                    newline(f, depth + 1)?;
+                }
+                write_string(f, key)?;
+                f.write_str(if indent.is_some() { ": " } else { ":" })?;
+                write_value(f, value, indent.map(|depth| depth + 1))?;
+            }
+            if let (Some(depth), false) = (indent, fields.is_empty()) {
+                newline(f, depth)?;
+            }
+            f.write_str("}")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// how far into the text things went wrong, in bytes
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl FromStr for Value {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { text: s, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), ParseError> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+        if self.text[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                if self.eat(b']') {
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    if self.eat(b']') {
+                        return Ok(Value::Array(values));
+                    }
+                    self.expect(b',', "expected , or ]")?;
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if self.eat(b'}') {
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a key"));
+                    }
+                    let key = self.string()?;
+                    self.expect(b':', "expected :")?;
+                    fields.push((key, self.value()?));
+                    if self.eat(b'}') {
+                        return Ok(Value::Object(fields));
+                    }
+                    self.expect(b',', "expected , or }")?;
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        self.text[start..self.pos]
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| ParseError {
+                offset: start,
+                message: "not a number",
+            })
+    }
+
+    /// reads a string, starting at its opening quote
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let c = rest
+                .chars()
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    out.push(match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("unknown escape")),
+                    });
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        // from_str_radix would let a sign through, like `\u+041`
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("bad unicode escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    /// the four hex digits after `\u`, plus the second half of a surrogate
+    /// pair if there is one
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let first = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&first) {
+            if !self.text[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let second = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&second) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)
+        } else {
+            first
+        };
+        char::from_u32(code).ok_or_else(|| self.error("bad unicode escape"))
+    }
+}
+
+impl Value {
+    /// looks up a key in an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// the number, if it's a whole number that isn't negative
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_f64()
+            .filter(|n| n.fract() == 0.0 && *n >= 0.0 && *n <= u64::MAX as f64)
+            .map(|n| n as u64)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<BitCard> for Value {
+    fn from(card: BitCard) -> Self {
+        Value::String(card.to_string())
+    }
+}
+
+/// ids are strings like cards, they don't all fit in a json number
+impl From<CardId> for Value {
+    fn from(id: CardId) -> Self {
+        Value::String(id.to_string())
+    }
+}
+
+impl From<Move> for Value {
+    fn from(mv: Move) -> Self {
+        match mv {
+            Move::Tableau { from, idx, to } => Value::object()
+                .with("kind", "tableau")
+                .with("from", from)
+                .with("idx", idx)
+                .with("to", to),
+            Move::Foundation { from, foundation } => Value::object()
+                .with("kind", "foundation")
+                .with("from", from)
+                .with("foundation", foundation),
+            Move::Reshuffle { column } => Value::object()
+                .with("kind", "reshuffle")
+                .with("column", column),
+            Move::Insert { from, idx, at } => Value::object()
+                .with("kind", "insert")
+                .with("from", from)
+                .with("idx", idx)
+                .with("at", at),
+            Move::Draw => Value::object().with("kind", "draw"),
+            Move::Waste { to } => Value::object().with("kind", "waste").with("to", to),
+            Move::WasteFoundation { foundation } => Value::object()
+                .with("kind", "waste_foundation")
+                .with("foundation", foundation),
+        }
+    }
+}
+
+impl From<&Event> for Value {
+    fn from(event: &Event) -> Self {
+        match *event {
+            Event::Moved(mv) => Value::object().with("type", "moved").with("move", mv),
+            Event::Founded { foundation, card } => Value::object()
+                .with("type", "founded")
+                .with("foundation", foundation)
+                .with("card", card),
+            Event::Revealed { column, card } => Value::object()
+                .with("type", "revealed")
+                .with("column", column)
+                .with("card", card),
+            Event::TurnedBack { column, card } => Value::object()
+                .with("type", "turned_back")
+                .with("column", column)
+                .with("card", card),
+            Event::Reshuffled { column, card } => Value::object()
+                .with("type", "reshuffled")
+                .with("column", column)
+                .with("card", card),
+            Event::Drawn { card } => Value::object().with("type", "drawn").with("card", card),
+        }
+    }
+}
+
+impl From<&Column> for Value {
+    fn from(column: &Column) -> Self {
+        let value = Value::object()
+            .with("under", column.under)
+            .with("visible", column.visible().iter().collect::<Vec<_>>())
+            .with("ids", column.visible().ids().to_vec());
+        // only decay games have these, the rest are left as they were
+        if column.turned_under().is_empty() {
+            return value;
+        }
+        let (cards, ids): (Vec<_>, Vec<_>) = column.turned_under().iter().copied().unzip();
+        value
+            .with("turned_under", cards)
+            .with("turned_under_ids", ids)
+    }
+}
+
+/// the whole board. the seed is a string since json numbers are only exact
+/// up to 2^53. only the columns that aren't as they were dealt are there,
+/// each with its `x`, like binary saves, and `column_count` says how many
+/// there are in all. the rest are dealt again from the seed.
+impl From<&Game> for Value {
+    fn from(game: &Game) -> Self {
+        board(game, game.all_changed_columns().into_iter().collect())
+    }
+}
+
+/// `game` with just `columns` of the tableau, by where they are
+fn board(game: &Game, columns: BTreeMap<usize, Column>) -> Value {
+    let mut foundations: Vec<_> = game.foundations().iter().collect();
+    foundations.sort_by_key(|(idx, _)| **idx);
+    let foundations: Vec<Value> = foundations
+        .into_iter()
+        .map(|(idx, card)| {
+            Value::object()
+                .with("index", *idx)
+                .with("card", *card)
+                .with("id", game.foundation_ids()[idx])
+        })
+        .collect();
+    let columns: Vec<Value> = (columns.iter())
+        .map(|(x, column)| Value::from(column).with("x", *x))
+        .collect();
+    let regions: Vec<Value> = game
+        .regions_founded()
+        .iter()
+        .map(|(region, founded)| {
+            Value::object()
+                .with("region", *region)
+                .with("founded", *founded)
+        })
+        .collect();
+    Value::object()
+        .with("seed", game.seed().to_string())
+        .with("topology", game.rules().topology.to_string())
+        .with("sandbox", game.rules().sandbox)
+        .with("practice", game.rules().practice)
+        .with("reveal", game.rules().reveal.to_string())
+        .with("reshuffles", game.reshuffles_used())
+        .with("inserted", game.inserted().to_vec())
+        .with("stock", game.rules().stock)
+        .with("draw_three", game.rules().draw_three)
+        .with("deck", game.rules().deck.to_string())
+        .with("decay", game.rules().decay)
+        .with("drawn", game.drawn())
+        .with("waste", game.waste().to_vec())
+        .with("waste_ids", game.waste_ids().to_vec())
+        .with("placed", game.placed())
+        .with("founded", game.founded())
+        .with("regions", regions)
+        .with("foundations", foundations)
+        .with("column_count", game.columns())
+        .with("columns", columns)
+}
+
+/// the board as an observer gets to see it, for serving to spectators and
+/// overlays while someone might be racing the player. it's the same as the
+/// whole board but without the seed, since every face down card and the
+/// rest of the stock can be dealt again from it, and with `"redacted":
+/// true` so readers can tell. face down cards are only ever counts anyway.
+/// without the seed the untouched columns can't be dealt again, so the
+/// columns in the second field, the ones on screen, are put in as well.
+pub struct Observed<'a>(pub &'a Game, pub &'a [usize]);
+
+impl From<Observed<'_>> for Value {
+    fn from(Observed(game, on_screen): Observed<'_>) -> Self {
+        let mut columns: BTreeMap<usize, Column> = game.all_changed_columns().into_iter().collect();
+        for &x in on_screen.iter().filter(|x| **x < game.columns()) {
+            columns.entry(x).or_insert_with(|| game.column(x));
+        }
+        let mut value = board(game, columns);
+        if let Value::Object(fields) = &mut value {
+            fields.retain(|(key, _)| key != "seed");
+        }
+        value.with("redacted", true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Value, ParseError> {
+        text.parse()
+    }
+
+    fn error(text: &str) -> &'static str {
+        parse(text).unwrap_err().message
+    }
+
+    #[test]
+    fn parses_values() {
+        assert_eq!(parse(" null ").unwrap(), Value::Null);
+        assert_eq!(parse("true").unwrap(), Value::Bool(true));
+        assert_eq!(parse("-12.5e1").unwrap(), Value::Number(-125.0));
+        assert_eq!(
+            parse(r#"{"a": [1, "b", {}], "c": []}"#).unwrap(),
+            Value::object()
+                .with("a", vec![Value::from(1u32), "b".into(), Value::object()])
+                .with("c", Vec::<Value>::new())
+        );
+    }
+
+    #[test]
+    fn parses_escapes() {
+        assert_eq!(
+            parse(r#""\"\\\/\b\f\n\r\t""#).unwrap(),
+            Value::from("\"\\/\u{8}\u{c}\n\r\t")
+        );
+        assert_eq!(parse(r#""Aé""#).unwrap(), Value::from("Aé"));
+        assert_eq!(parse(r#""🂡""#).unwrap(), Value::from("🂡"));
+    }
+
+    #[test]
+    fn rejects_bad_escapes() {
+        assert_eq!(error(r#""\u+041""#), "bad unicode escape");
+        assert_eq!(error(r#""\u-041""#), "bad unicode escape");
+        assert_eq!(error(r#""\u00g1""#), "bad unicode escape");
+        assert_eq!(error(r#""\u00""#), "bad unicode escape");
+        assert_eq!(error(r#""\x""#), "unknown escape");
+        // a high surrogate needs a low one after it, and a low one can't
+        // come first
+        assert_eq!(error(r#""\ud83c""#), "unpaired surrogate");
+        assert_eq!(error(r#""\ud83cA""#), "unpaired surrogate");
+        assert_eq!(error(r#""\ud83c\ud83c""#), "unpaired surrogate");
+        assert_eq!(error(r#""\udca1""#), "bad unicode escape");
+    }
+
+    #[test]
+    fn rejects_bad_json() {
+        assert_eq!(error(""), "unexpected end of input");
+        assert_eq!(error("nul"), "unexpected character");
+        assert_eq!(error("[1 2]"), "expected , or ]");
+        assert_eq!(error("{1: 2}"), "expected a key");
+        assert_eq!(error(r#"{"a" 2}"#), "expected :");
+        assert_eq!(error("\"a"), "unterminated string");
+        assert_eq!(error("\"\n\""), "control character in string");
+        assert_eq!(error("1 2"), "trailing characters");
+        assert_eq!(error("1e"), "not a number");
+        assert_eq!(parse("[1, 2").unwrap_err().offset, 5);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let value = Value::object()
+            .with("name", "tab\t\"quoted\" \u{1} é 🂡")
+            .with("numbers", vec![0.5, -3.0, 1e20])
+            .with(
+                "nested",
+                vec![Value::object().with("empty", Vec::<Value>::new())],
+            )
+            .with("missing", Value::Null)
+            .with("yes", true);
+        assert_eq!(parse(&value.to_string()).unwrap(), value);
+        assert_eq!(parse(&format!("{:#}", value)).unwrap(), value);
+    }
+}
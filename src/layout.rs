@@ -0,0 +1,523 @@
+//! where the tableau and foundations go on screen.
+//!
+//! the tableau is laid out along a "main" axis, the one that goes on forever,
+//! and each column fans its cards out along the other one. normally columns
+//! go left to right and cards fan downwards, the vertical layout swaps them
+//! so columns go top to bottom and cards fan to the right, which suits
+//! portrait screens better. the grid layout puts piles on a grid going right
+//! and down for the grid variant of the rules. everything else only deals in
+//! column indices.
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use infiniteklondike::rules::{grid_coords, grid_index};
+use macroquad::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// columns go left to right
+    #[default]
+    Horizontal,
+    /// columns go top to bottom
+    Vertical,
+    /// piles on a grid going right and down, for the grid variant. the
+    /// foundations are always pinned and face down cards are stacked up
+    /// under a single card back.
+    Grid,
+}
+
+/// which kind of screen the layout is meant for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    /// mobile when the window is taller than it is wide, desktop otherwise
+    #[default]
+    Auto,
+    Desktop,
+    /// bigger cards so fewer columns fit, the foundations docked along the
+    /// bottom where thumbs can reach them, and dragging the background to
+    /// scroll
+    Mobile,
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Profile::Auto => "auto",
+            Profile::Desktop => "desktop",
+            Profile::Mobile => "mobile",
+        })
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Profile::Auto),
+            "desktop" => Ok(Profile::Desktop),
+            "mobile" => Ok(Profile::Mobile),
+            _ => Err(format!(
+                "unknown layout `{}`, try auto, desktop or mobile",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    pub profile: Profile,
+    pub orientation: Orientation,
+    /// the foundations sit in a bar fixed to the window instead of out in
+    /// the world
+    pub pin_foundations: bool,
+    /// the foundations go along the far edge of the window (the bottom, or
+    /// the right in the vertical layout) instead of before the tableau
+    pub foundations_at_bottom: bool,
+    /// how many steps zoomed out from the usual size, for looking over a
+    /// long stretch of the board at once
+    pub zoomed_out: u8,
+    /// how much bigger than it picks for the window everything is drawn
+    pub card_scale: f32,
+    /// how fast the arrow keys and the window edges scroll, in window
+    /// pixels a second
+    pub pan_speed: f32,
+    /// dragging moves the view the way the mouse goes, like a scroll bar,
+    /// instead of dragging the board along with it
+    pub invert_pan: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            profile: Profile::default(),
+            orientation: Orientation::default(),
+            pin_foundations: false,
+            foundations_at_bottom: false,
+            zoomed_out: 0,
+            card_scale: 1.0,
+            pan_speed: Self::SCROLL_SPEED,
+            invert_pan: false,
+        }
+    }
+}
+
+impl Layout {
+    pub const CARD_WIDTH: f32 = 44.0;
+    pub const CARD_HEIGHT: f32 = 64.0;
+    /// room left between neighbouring cards
+    const GAP: f32 = 4.0;
+    /// how much of each card shows when they're fanned out
+    pub const FAN: f32 = 16.0;
+    /// the world foundations start above this column
+    const FOUNDATION_COLUMN: usize = 3;
+    /// where the pinned foundation bar starts
+    const PINNED_START: f32 = 4.0;
+    /// how fast the arrow keys and the window edges scroll unless the
+    /// settings say otherwise, in window pixels a second
+    const SCROLL_SPEED: f32 = 900.0;
+    /// how near the edge of the window the mouse has to be to scroll while
+    /// carrying cards
+    pub const SCROLL_EDGE: f32 = 24.0;
+    /// the fewest columns that should fit on screen, small windows get
+    /// smaller cards to fit this many
+    const MIN_COLUMNS: f32 = 5.0;
+    /// the most columns that should fit on screen, wide windows get bigger
+    /// cards instead of more columns than anyone can take in
+    const MAX_COLUMNS: f32 = 24.0;
+    /// how far the stock stays up from the bottom of the window, in window
+    /// pixels, to keep clear of the finish button
+    const STOCK_CLEARANCE: f32 = 96.0;
+    /// how much smaller each step of zooming out makes everything
+    const ZOOM_STEP: f32 = 0.75;
+    /// how many steps out it goes, enough for a few hundred columns
+    pub const MAX_ZOOMED_OUT: u8 = 10;
+    /// how wide cards have to be on screen, in window pixels, to be drawn
+    /// properly. smaller ones are just blocks of colour.
+    const OVERVIEW_WIDTH: f32 = 20.0;
+
+    /// which way the columns go, as a unit vector
+    pub fn main_axis(self) -> Vec2 {
+        match self.orientation {
+            Orientation::Horizontal | Orientation::Grid => vec2(1.0, 0.0),
+            Orientation::Vertical => vec2(0.0, 1.0),
+        }
+    }
+
+    /// which way cards in a column fan out, as a unit vector
+    pub fn fan_axis(self) -> Vec2 {
+        match self.orientation {
+            Orientation::Horizontal | Orientation::Grid => vec2(0.0, 1.0),
+            Orientation::Vertical => vec2(1.0, 0.0),
+        }
+    }
+
+    fn is_grid(self) -> bool {
+        self.orientation == Orientation::Grid
+    }
+
+    /// whether the mobile layout is in use right now
+    pub fn is_mobile(self) -> bool {
+        match self.profile {
+            Profile::Auto => screen_height() > screen_width(),
+            Profile::Desktop => false,
+            Profile::Mobile => true,
+        }
+    }
+
+    /// how much bigger than the atlas' usual 2x everything is drawn, picked
+    /// so a sensible number of columns fits in the window, times the card
+    /// scale from the settings
+    pub fn scale(self) -> f32 {
+        let screen = vec2(screen_width(), screen_height()).dot(self.main_axis());
+        let spacing = self.spacing();
+        let fits = screen / spacing;
+        let wanted = if self.is_mobile() {
+            // big enough for thumbs, and never shrunk below the usual size
+            Self::MIN_COLUMNS.min(fits)
+        } else {
+            fits.clamp(Self::MIN_COLUMNS, Self::MAX_COLUMNS)
+        };
+        fits / wanted * self.card_scale * Self::ZOOM_STEP.powi(self.zoomed_out.into())
+    }
+
+    /// whether cards are too small to make out, blown up `zoom` times, so
+    /// they should be drawn as plain blocks of colour
+    pub fn is_overview(self, zoom: f32) -> bool {
+        Self::CARD_WIDTH * self.scale() * zoom < Self::OVERVIEW_WIDTH
+    }
+
+    /// the camera to draw the world with, so everything is scaled up
+    pub fn camera2d(self) -> Camera2D {
+        let size = self.screen_size();
+        Camera2D::from_display_rect(Rect::new(0.0, 0.0, size.x, size.y))
+    }
+
+    /// turns a point in window pixels into layout coordinates
+    pub fn screen_to_layout(self, point: Vec2) -> Vec2 {
+        point / self.scale()
+    }
+
+    /// whether the foundations are in a bar fixed to the window
+    pub fn pinned(self) -> bool {
+        self.pin_foundations || self.is_grid() || self.is_mobile()
+    }
+
+    /// whether the foundations are at the far edge of the window
+    fn at_bottom(self) -> bool {
+        self.foundations_at_bottom || self.is_mobile()
+    }
+
+    /// whether pressing on an empty part of the board starts scrolling
+    pub fn drag_to_scroll(self) -> bool {
+        self.is_mobile()
+    }
+
+    /// how many rows of cards `under` face down cards take up
+    pub fn hidden_rows(self, under: u32) -> usize {
+        if self.is_grid() {
+            under.min(1) as usize
+        } else {
+            under as usize
+        }
+    }
+
+    /// the distance from one row of the grid to the next, enough for a card
+    /// back and a full run from king to ace
+    fn cell_height(self) -> f32 {
+        Self::CARD_HEIGHT + Self::FAN * 13.0 + Self::GAP
+    }
+
+    fn card_size(self) -> Vec2 {
+        vec2(Self::CARD_WIDTH, Self::CARD_HEIGHT)
+    }
+
+    /// how much of the board fits in the window, in layout coordinates
+    pub fn screen_size(self) -> Vec2 {
+        vec2(screen_width(), screen_height()) / self.scale()
+    }
+
+    /// the distance from one column to the next
+    pub fn spacing(self) -> f32 {
+        self.card_size().dot(self.main_axis()) + Self::GAP
+    }
+
+    /// how much room the foundation row takes up
+    pub fn band(self) -> f32 {
+        self.card_size().dot(self.fan_axis()) + Self::GAP
+    }
+
+    /// how far along the fan axis the tableau starts from the camera
+    fn tableau_offset(self) -> f32 {
+        if self.at_bottom() {
+            Self::GAP
+        } else {
+            self.band()
+        }
+    }
+
+    /// a camera that shows the first few columns at the far end of the screen
+    pub fn initial_camera(self) -> Vec2 {
+        if self.is_grid() {
+            return vec2(self.spacing() + Self::GAP, 2.0);
+        }
+        let screen = self.screen_size().dot(self.main_axis());
+        // as many columns as fit, with the first one at the near edge
+        let shown = (screen / self.spacing()).floor().max(1.0);
+        let main = screen - (shown - 1.0) * self.spacing();
+        self.main_axis() * main + self.fan_axis() * 2.0
+    }
+
+    /// a camera showing `column` where the first column starts out
+    pub fn camera_at(self, column: usize) -> Vec2 {
+        if self.is_grid() {
+            let (x, y) = grid_coords(column);
+            let offset = vec2(self.spacing() * x as f32, self.cell_height() * y as f32);
+            return self.initial_camera() - offset;
+        }
+        self.initial_camera() - self.main_axis() * self.spacing() * column as f32
+    }
+
+    /// the top left corner of card `n` in `column`, counting the rows taken
+    /// up by face down cards too
+    pub fn card_pos(self, camera: Vec2, column: usize, n: usize) -> Vec2 {
+        if self.is_grid() {
+            let (x, y) = grid_coords(column);
+            return camera
+                + vec2(
+                    self.spacing() * (x as f32 - 1.0),
+                    self.tableau_offset() + self.cell_height() * y as f32 + Self::FAN * n as f32,
+                );
+        }
+        camera
+            + self.main_axis() * self.spacing() * (column as f32 - 1.0)
+            + self.fan_axis() * (self.tableau_offset() + Self::FAN * n as f32)
+    }
+
+    /// the strip `column` takes up, from its first card to the edge of the
+    /// screen, or to the end of its cell on the grid
+    pub fn column_rect(self, camera: Vec2, column: usize) -> Rect {
+        let start = self.card_pos(camera, column, 0);
+        let length = if self.is_grid() {
+            self.cell_height() - Self::GAP
+        } else {
+            (self.screen_size() - start).dot(self.fan_axis())
+        };
+        let size =
+            self.main_axis() * self.card_size().dot(self.main_axis()) + self.fan_axis() * length;
+        Rect::new(start.x, start.y, size.x, size.y)
+    }
+
+    /// whether any of a card with its top left corner at `pos` is on screen
+    pub fn is_on_screen(self, pos: Vec2) -> bool {
+        let size = self.card_size();
+        Rect::new(
+            -size.x,
+            -size.y,
+            self.screen_size().x + size.x,
+            self.screen_size().y + size.y,
+        )
+        .contains(pos)
+    }
+
+    /// the column under a point on screen
+    pub fn column_at(self, camera: Vec2, point: Vec2) -> Option<usize> {
+        let main = (point - camera).dot(self.main_axis()) + self.spacing();
+        if main < 0.0 {
+            return None;
+        }
+        let x = (main / self.spacing()) as usize;
+        if !self.is_grid() {
+            return Some(x);
+        }
+        let y = (point.y - camera.y - self.tableau_offset()) / self.cell_height();
+        (y >= 0.0).then(|| grid_index(x, y as usize))
+    }
+
+    /// how many rows down its column a point on screen is
+    pub fn depth_at(self, camera: Vec2, point: Vec2) -> usize {
+        let mut fan = (point - camera).dot(self.fan_axis()) - self.tableau_offset();
+        if self.is_grid() {
+            fan = fan.rem_euclid(self.cell_height());
+        }
+        (fan.max(0.0) / Self::FAN) as usize
+    }
+
+    /// whether a point on screen is on one of the cards of `column`, which
+    /// takes up `rows` rows
+    pub fn on_pile(self, camera: Vec2, column: usize, rows: usize, point: Vec2) -> bool {
+        let start = self.card_pos(camera, column, 0);
+        let end = self.card_pos(camera, column, rows.max(1) - 1) + self.card_size();
+        Rect::new(start.x, start.y, end.x - start.x, end.y - start.y).contains(point)
+    }
+
+    /// the range of columns (or grid columns) along the main axis that are at
+    /// least partly on screen
+    fn visible_range(self, camera: Vec2) -> Range<usize> {
+        let main = camera.dot(self.main_axis());
+        let start = (-main / self.spacing()).max(0.0) as usize;
+        let end = (self.screen_size().dot(self.main_axis()) - main).max(0.0) as usize
+            / self.spacing() as usize
+            + 2;
+        start..end
+    }
+
+    /// the grid rows that are at least partly on screen
+    fn visible_rows(self, camera: Vec2) -> Range<usize> {
+        let top = -camera.y - self.tableau_offset();
+        let start = (top / self.cell_height()).max(0.0) as usize;
+        let end = ((top + self.screen_size().y) / self.cell_height()).max(0.0) as usize + 1;
+        start..end
+    }
+
+    /// every column that's at least partly on screen
+    pub fn visible_columns(self, camera: Vec2) -> Vec<usize> {
+        let range = self.visible_range(camera);
+        if !self.is_grid() {
+            return range.collect();
+        }
+        let rows = self.visible_rows(camera);
+        rows.flat_map(|y| range.clone().map(move |x| grid_index(x, y)))
+            .collect()
+    }
+
+    /// how many columns have to exist to draw everything on screen
+    pub fn columns_needed(self, camera: Vec2) -> usize {
+        let range = self.visible_range(camera);
+        if !self.is_grid() {
+            return range.end;
+        }
+        // the bottom right corner is the furthest along the diagonals
+        grid_index(range.end, self.visible_rows(camera).end) + 1
+    }
+
+    /// the leftmost (or topmost) column on screen
+    pub fn first_column(self, camera: Vec2) -> usize {
+        let range = self.visible_range(camera);
+        if !self.is_grid() {
+            return range.start;
+        }
+        grid_index(range.start, self.visible_rows(camera).start)
+    }
+
+    /// how far along the fan axis the foundations are
+    fn foundation_offset(self, camera: Vec2) -> f32 {
+        if self.at_bottom() {
+            self.screen_size().dot(self.fan_axis()) - self.band() + 2.0
+        } else if self.pinned() {
+            2.0
+        } else {
+            camera.dot(self.fan_axis())
+        }
+    }
+
+    /// the top left corner of world foundation pile `foundation`
+    pub fn foundation_pos(self, camera: Vec2, foundation: usize) -> Vec2 {
+        let column = foundation + Self::FOUNDATION_COLUMN;
+        let main = camera.dot(self.main_axis()) + self.spacing() * (column as f32 - 1.0);
+        self.main_axis() * main + self.fan_axis() * self.foundation_offset(camera)
+    }
+
+    /// the world foundations that are at least partly on screen
+    pub fn visible_foundations(self, camera: Vec2) -> Range<usize> {
+        let columns = self.visible_range(camera);
+        columns.start.saturating_sub(Self::FOUNDATION_COLUMN)
+            ..columns.end.saturating_sub(Self::FOUNDATION_COLUMN)
+    }
+
+    /// the world foundation under a point on screen
+    pub fn foundation_at(self, camera: Vec2, point: Vec2) -> Option<usize> {
+        self.column_at(camera, point)?
+            .checked_sub(Self::FOUNDATION_COLUMN)
+    }
+
+    /// the top left corner of slot `slot` in the pinned foundation bar
+    pub fn pinned_pos(self, camera: Vec2, slot: usize) -> Vec2 {
+        self.main_axis() * (Self::PINNED_START + self.spacing() * slot as f32)
+            + self.fan_axis() * self.foundation_offset(camera)
+    }
+
+    /// the slot of the pinned foundation bar under a point on screen
+    pub fn pinned_slot_at(self, point: Vec2) -> Option<usize> {
+        let slot = ((point.dot(self.main_axis()) - Self::PINNED_START) / self.spacing()).floor();
+        (slot >= 0.0).then_some(slot as usize)
+    }
+
+    /// whether a point on screen is over the foundations
+    pub fn over_foundations(self, camera: Vec2, point: Vec2) -> bool {
+        let fan = point.dot(self.fan_axis());
+        if self.at_bottom() {
+            fan > self.screen_size().dot(self.fan_axis()) - self.band()
+        } else if self.pinned() {
+            fan < self.band()
+        } else {
+            fan - camera.dot(self.fan_axis()) < self.band()
+        }
+    }
+
+    /// whether the foundations are drawn over the tableau, so they need a
+    /// background and clicks there shouldn't reach the cards underneath
+    pub fn foundations_cover_tableau(self) -> bool {
+        self.pinned() || self.at_bottom()
+    }
+
+    /// where the stock is drawn, fixed to the bottom right corner of the
+    /// window (clear of the foundations when they're along the far edge)
+    pub fn stock_pos(self) -> Vec2 {
+        let corner = self.screen_size()
+            - vec2(
+                Self::CARD_WIDTH + Self::GAP,
+                Self::CARD_HEIGHT + Self::STOCK_CLEARANCE / self.scale(),
+            );
+        if self.at_bottom() {
+            corner - self.fan_axis() * self.band()
+        } else {
+            corner
+        }
+    }
+
+    /// where the top card of the waste is drawn, just left of the stock
+    pub fn waste_pos(self) -> Vec2 {
+        self.stock_pos() - vec2(Self::CARD_WIDTH + Self::GAP, 0.0)
+    }
+
+    /// where the waste card `n` cards down from the top is drawn, when the
+    /// waste is fanned out to the left
+    pub fn waste_fan_pos(self, n: usize) -> Vec2 {
+        self.waste_pos() - vec2(Self::FAN * n as f32, 0.0)
+    }
+
+    /// the patch behind the stock and the waste, which covers the tableau,
+    /// with room for `fanned` waste cards
+    pub fn stock_area(self, fanned: usize) -> Rect {
+        let pos = self.waste_fan_pos(fanned.saturating_sub(1)) - vec2(Self::GAP, Self::GAP);
+        Rect::new(
+            pos.x,
+            pos.y,
+            self.stock_pos().x + Self::CARD_WIDTH + Self::GAP - pos.x,
+            Self::CARD_HEIGHT + 2.0 * Self::GAP,
+        )
+    }
+
+    /// whether a point on screen is over the stock
+    pub fn over_stock(self, point: Vec2) -> bool {
+        let pos = self.stock_pos();
+        Rect::new(pos.x, pos.y, Self::CARD_WIDTH, Self::CARD_HEIGHT).contains(point)
+    }
+
+    /// whether a point on screen is over the waste
+    pub fn over_waste(self, point: Vec2) -> bool {
+        let pos = self.waste_pos();
+        Rect::new(pos.x, pos.y, Self::CARD_WIDTH, Self::CARD_HEIGHT).contains(point)
+    }
+
+    /// the strip behind the foundations when they cover the tableau
+    pub fn foundation_band(self, camera: Vec2) -> Rect {
+        let start = self.fan_axis() * (self.foundation_offset(camera) - 2.0);
+        let size = self.main_axis() * self.screen_size() + self.fan_axis() * self.band();
+        Rect::new(start.x, start.y, size.x, size.y)
+    }
+}
@@ -0,0 +1,70 @@
+//! the rules of infinite klondike without any of the graphics, for building
+//! other frontends or tools on top of the game.
+//! with `default-features = false` nothing for the window (macroquad and
+//! image) gets built either.
+//!
+//! a board is a [`game::Game`]: an endless row of columns where column `n`
+//! starts with `n` face down cards and one face up card, plus an endless row of
+//! foundation piles. everything dealt comes from the seed, so the same seed and
+//! the same moves always give the same board.
+//!
+//! ```
+//! use infiniteklondike::game::{Game, Move};
+//!
+//! let mut game = Game::new(42, 50);
+//! if let Some(mv) = game.legal_moves(0..50).first().copied() {
+//!     game.apply(mv).unwrap();
+//! }
+//! println!("{:016x}", game.state_hash());
+//! ```
+//!
+//! nothing here draws anything, so bots and tools can play as many games as
+//! they like as fast as the moves can be made. a [`game::Snapshot`] goes back
+//! to an earlier board to try something else:
+//!
+//! ```
+//! use infiniteklondike::game::Game;
+//!
+//! let mut game = Game::new(7, 20);
+//! let start = game.snapshot();
+//! for _ in 0..100 {
+//!     let moves = game.legal_moves(0..20);
+//!     let Some(mv) = moves.last() else {
+//!         break;
+//!     };
+//!     game.apply(*mv).unwrap();
+//! }
+//! game.restore(&start);
+//! assert_eq!(game.state_hash(), Game::new(7, 20).state_hash());
+//! ```
+//!
+//! - [`challenge`] has the daily and weekly challenges and their leaderboards
+//! - [`cards`] has the cards and stacks themselves
+//! - [`frontend`] has the input events every frontend turns its input into
+//! - [`game`] has the rules, moves and generation
+//! - [`json`] turns boards and events into json for other programs
+//! - [`records`] keeps the best times
+//! - [`replay`] reads, writes and verifies recorded games
+//! - [`rules`] has the parts of the rules that can be changed, like the grid
+//!   variant
+//! - [`save`] reads and writes saved games
+//! - [`score`] has the ways of scoring a game
+//! - [`solver`] searches a window of the board for the best moves
+//! - [`simulate`] measures what deals look like over lots of seeds
+//! - [`tableau`] keeps the columns of the board in chunks
+//! - [`world`] streams a board to disk as it's played
+
+pub mod cards;
+pub mod challenge;
+pub mod frontend;
+pub mod game;
+pub mod json;
+pub mod records;
+pub mod replay;
+pub mod rules;
+pub mod save;
+pub mod score;
+pub mod simulate;
+pub mod solver;
+pub mod tableau;
+pub mod world;
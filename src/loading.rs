@@ -0,0 +1,45 @@
+//! the screen shown while the game starts up, so the window isn't just
+//! black while things load
+
+use macroquad::prelude::*;
+
+/// a progress bar through a known number of steps
+pub struct Loading {
+    done: usize,
+    total: usize,
+}
+
+impl Loading {
+    const BAR_WIDTH: f32 = 240.0;
+    const BAR_HEIGHT: f32 = 12.0;
+
+    pub fn new(total: usize) -> Self {
+        Self { done: 0, total }
+    }
+
+    /// shows a frame saying `what` is loading next. await it right before
+    /// doing the work, so the bar is on screen while it happens.
+    pub async fn step(&mut self, what: &str) {
+        self.draw(what);
+        next_frame().await;
+        self.done = (self.done + 1).min(self.total);
+    }
+
+    fn draw(&self, what: &str) {
+        clear_background(BLACK);
+        let x = (screen_width() - Self::BAR_WIDTH) / 2.0;
+        let y = screen_height() / 2.0;
+        draw_text("infinite klondike", x, y - 24.0, 32.0, WHITE);
+        draw_rectangle_lines(x, y, Self::BAR_WIDTH, Self::BAR_HEIGHT, 2.0, WHITE);
+        let progress = self.done as f32 / self.total.max(1) as f32;
+        draw_rectangle(
+            x + 2.0,
+            y + 2.0,
+            (Self::BAR_WIDTH - 4.0) * progress,
+            Self::BAR_HEIGHT - 4.0,
+            WHITE,
+        );
+        let label = format!("loading {}", what);
+        draw_text(&label, x, y + Self::BAR_HEIGHT + 20.0, 20.0, WHITE);
+    }
+}
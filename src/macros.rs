@@ -0,0 +1,240 @@
+//! macros for doing the same few moves over and over. m starts recording
+//! from the column under the mouse, and F1 to F4 stop and keep what was
+//! recorded under that key. the moves are kept relative to where recording
+//! started, so pressing the key plays them back from whatever column is
+//! under the mouse then, each one checked like any other move. m again
+//! while recording throws it away.
+
+use std::fmt;
+
+use infiniteklondike::game::{Game, Move, MoveError};
+use macroquad::prelude::*;
+
+use crate::input::is_key_pressed;
+
+/// a move, with its columns counted from the column the macro is played from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// the last `cards` face up cards of `from` onto `to`
+    Tableau {
+        from: isize,
+        cards: usize,
+        to: isize,
+    },
+    /// the last `cards` face up cards of `from` into a new column at `at`
+    Insert {
+        from: isize,
+        cards: usize,
+        at: isize,
+    },
+    /// the top card of `from` onto whichever foundation takes it
+    Found {
+        from: isize,
+    },
+    Reshuffle {
+        column: isize,
+    },
+    Draw,
+    /// the top card of the waste onto `to`
+    Waste {
+        to: isize,
+    },
+    /// the top card of the waste onto whichever foundation takes it
+    WasteFound,
+}
+
+impl Action {
+    /// `mv`, made on `game` before it happened, relative to `anchor`
+    fn relative(game: &Game, mv: Move, anchor: usize) -> Self {
+        let offset = |column: usize| column as isize - anchor as isize;
+        let cards = |from: usize, idx: usize| {
+            let len: usize = game.tableau()[from].visible().len().into();
+            len - idx
+        };
+        match mv {
+            Move::Tableau { from, idx, to } => Action::Tableau {
+                from: offset(from),
+                cards: cards(from, idx),
+                to: offset(to),
+            },
+            Move::Insert { from, idx, at } => Action::Insert {
+                from: offset(from),
+                cards: cards(from, idx),
+                at: offset(at),
+            },
+            Move::Foundation { from, .. } => Action::Found { from: offset(from) },
+            Move::Reshuffle { column } => Action::Reshuffle {
+                column: offset(column),
+            },
+            Move::Draw => Action::Draw,
+            Move::Waste { to } => Action::Waste { to: offset(to) },
+            Move::WasteFoundation { .. } => Action::WasteFound,
+        }
+    }
+
+    /// the move this is when played from `anchor` on `game`. it still has
+    /// to be checked, this only works out which columns and cards it means.
+    pub fn resolve(self, game: &Game, anchor: usize) -> Result<Move, MoveError> {
+        let column = |offset: isize| {
+            anchor
+                .checked_add_signed(offset)
+                .filter(|column| *column < game.tableau().len())
+                .ok_or(MoveError::NoSuchColumn)
+        };
+        let idx = |from: usize, cards: usize| {
+            let len: usize = game.tableau()[from].visible().len().into();
+            len.checked_sub(cards).ok_or(MoveError::NoSuchCard)
+        };
+        let foundation = |card| game.foundation_for(card).ok_or(MoveError::CannotFound);
+        Ok(match self {
+            Action::Tableau { from, cards, to } => {
+                let from = column(from)?;
+                Move::Tableau {
+                    from,
+                    idx: idx(from, cards)?,
+                    to: column(to)?,
+                }
+            }
+            Action::Insert { from, cards, at } => {
+                let from = column(from)?;
+                Move::Insert {
+                    from,
+                    idx: idx(from, cards)?,
+                    // a new column can go just past the last one
+                    at: anchor
+                        .checked_add_signed(at)
+                        .ok_or(MoveError::NoSuchColumn)?,
+                }
+            }
+            Action::Found { from } => {
+                let from = column(from)?;
+                let card = game.tableau()[from].visible().last();
+                Move::Foundation {
+                    from,
+                    foundation: foundation(card.ok_or(MoveError::NoSuchCard)?)?,
+                }
+            }
+            Action::Reshuffle { column: offset } => Move::Reshuffle {
+                column: column(offset)?,
+            },
+            Action::Draw => Move::Draw,
+            Action::Waste { to } => Move::Waste { to: column(to)? },
+            Action::WasteFound => {
+                let top = game.waste().last().copied();
+                Move::WasteFoundation {
+                    foundation: foundation(top.ok_or(MoveError::NoSuchCard)?)?,
+                }
+            }
+        })
+    }
+}
+
+/// a column counted from the one the macro is played from, like `+2`
+struct Offset(isize);
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => f.write_str("this column"),
+            offset => write!(f, "column {:+}", offset),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Action::Tableau { from, cards, to } => {
+                write!(f, "{} cards from {} to {}", cards, Offset(from), Offset(to))
+            }
+            Action::Insert { from, cards, at } => write!(
+                f,
+                "{} cards from {} into a new {}",
+                cards,
+                Offset(from),
+                Offset(at)
+            ),
+            Action::Found { from } => write!(f, "top card of {} to a foundation", Offset(from)),
+            Action::Reshuffle { column } => write!(f, "reshuffle {}", Offset(column)),
+            Action::Draw => f.write_str("draw from the stock"),
+            Action::Waste { to } => write!(f, "top of the waste to {}", Offset(to)),
+            Action::WasteFound => f.write_str("top of the waste to a foundation"),
+        }
+    }
+}
+
+/// the keys macros are kept under
+const KEYS: [KeyCode; 4] = [KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4];
+
+/// which macro key was pressed this frame, if any
+pub fn key_pressed() -> Option<usize> {
+    KEYS.iter().position(|key| is_key_pressed(*key))
+}
+
+#[derive(Debug, Default)]
+pub struct Macros {
+    /// what's kept under each key
+    slots: [Vec<Action>; KEYS.len()],
+    /// the column recording started from and what's been recorded so far
+    recording: Option<(usize, Vec<Action>)>,
+}
+
+impl Macros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// starts recording from `anchor`
+    pub fn start(&mut self, anchor: usize) {
+        self.recording = Some((anchor, Vec::new()));
+    }
+
+    /// stops recording without keeping anything
+    pub fn cancel(&mut self) {
+        self.recording = None;
+    }
+
+    /// stops recording and keeps it under key `slot`, returning how many
+    /// moves it has
+    pub fn keep(&mut self, slot: usize) -> usize {
+        let actions = self.recording.take().map(|(_, actions)| actions);
+        self.slots[slot] = actions.unwrap_or_default();
+        self.slots[slot].len()
+    }
+
+    /// `mv` as it would be recorded, if there's a recording going. `game`
+    /// is the board from before the move.
+    pub fn relative(&self, game: &Game, mv: Move) -> Option<Action> {
+        let (anchor, _) = self.recording.as_ref()?;
+        Some(Action::relative(game, mv, *anchor))
+    }
+
+    /// adds a move that was made to the recording
+    pub fn record(&mut self, action: Action) {
+        if let Some((_, actions)) = &mut self.recording {
+            actions.push(action);
+        }
+    }
+
+    /// what's kept under key `slot`
+    pub fn get(&self, slot: usize) -> &[Action] {
+        &self.slots[slot]
+    }
+
+    /// says a macro is being recorded, in the bottom left corner
+    pub fn draw(&self) {
+        let Some((_, actions)) = &self.recording else {
+            return;
+        };
+        let label = format!(
+            "recording a macro, {} moves so far (F1-F4 keeps it, m drops it)",
+            actions.len()
+        );
+        draw_text(&label, 8.0, screen_height() - 156.0, 18.0, RED);
+    }
+}
+
+/// the name of key `slot`, for messages
+pub fn key_name(slot: usize) -> String {
+    format!("F{}", slot + 1)
+}
@@ -0,0 +1,55 @@
+//! a magnifying glass for reading small cards. holding z draws the board
+//! around the mouse again at twice the size, in a square that follows the
+//! mouse around. it's separate from the layout's own scale, so nothing else
+//! on screen moves.
+
+use macroquad::prelude::*;
+
+use crate::input;
+use crate::layout::Layout;
+
+/// how much bigger things are under the glass
+pub const ZOOM: f32 = 2.0;
+/// how big the glass is, in window pixels
+const SIZE: f32 = 240.0;
+
+/// whether the magnifier key is held down
+pub fn is_held() -> bool {
+    is_key_down(KeyCode::Z)
+}
+
+/// the square of the window the glass covers
+fn glass() -> Rect {
+    let (x, y) = mouse_position();
+    Rect::new(x - SIZE / 2.0, y - SIZE / 2.0, SIZE, SIZE)
+}
+
+/// the part of the layout that shows under the glass, in layout coordinates
+pub fn area(layout: Layout) -> Rect {
+    let size = SIZE / layout.scale() / ZOOM;
+    let center = layout.screen_to_layout(mouse_position().into());
+    Rect::new(center.x - size / 2.0, center.y - size / 2.0, size, size)
+}
+
+/// the camera to draw the world with under the glass. it only draws inside
+/// the glass, and blows up the `area` around the mouse to fill it.
+pub fn camera(layout: Layout) -> Camera2D {
+    let glass = glass();
+    // viewports count up from the bottom of the window, in real pixels
+    let dpi = input::dpi_scale();
+    Camera2D {
+        viewport: Some((
+            (glass.x * dpi) as i32,
+            ((screen_height() - glass.bottom()) * dpi) as i32,
+            (SIZE * dpi) as i32,
+            (SIZE * dpi) as i32,
+        )),
+        ..Camera2D::from_display_rect(area(layout))
+    }
+}
+
+/// the edge of the glass, in window pixels
+pub fn draw_frame(color: Color) {
+    let glass = glass();
+    draw_rectangle_lines(glass.x, glass.y, glass.w, glass.h, 2.0, color);
+}
@@ -1,27 +1,125 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
-use ::rand::{distributions::Standard, prelude::Distribution};
-use cards::{BitCard, CardStack, Column};
+use ::rand::{Rng, RngCore, SeedableRng};
+use achievements::Achievements;
+use analysis::Analysis;
+use assist::{Assists, Autoplay};
+use atlas::Atlas;
+use audio::Effect;
+use autosave::Autosave;
+use bookmarks::Bookmarks;
+use bot::Bot;
+use cli::Options;
+use console::Console;
+use cursor::Cursor;
+use decay::Decay;
+use hints::{Hints, Spot};
+use history::History;
+use hotseat::HotSeat;
+use infiniteklondike::cards::{BitCard, CardStack, Column};
+use infiniteklondike::challenge::{Challenge, Period};
+use infiniteklondike::frontend::{Gesture, Gestures, InputSource};
+use infiniteklondike::game::{Event, Game, Move, MoveError};
+use infiniteklondike::json::{Observed, Value};
+use infiniteklondike::replay::Replay;
+use infiniteklondike::rules::{Rules, Topology};
+use infiniteklondike::save::{SaveData, SaveFormat};
+use infiniteklondike::score::Scoring;
+use infiniteklondike::solver;
+use infiniteklondike::world::World;
+use input::{
+    is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed,
+    is_mouse_button_released, is_quit_requested, mouse_position,
+};
+use layout::{Layout, Orientation};
+use loading::Loading;
 use macroquad::prelude::*;
+use macros::Macros;
+use options::{Choice, Page};
+use particles::Particles;
+use power::Power;
+use rand_chacha::ChaCha8Rng;
+use sandbox::{Edit, Sandbox};
+use scoreboard::Run;
+use screen::Screen;
+use server::Server;
+use settings::{Grab, Settings};
+use stats::Session;
+use style::Style;
+use tabs::{tab_path, Tabs};
+use timer::Bests;
+use title::Start;
+use tween::Tweens;
+use ui::{Answer, Menu, Prompt, Radial};
+use undo::{Step, Undo};
 
-mod cards;
+mod achievements;
+mod analysis;
+mod assist;
+mod atlas;
+mod audio;
+mod autosave;
+mod bookmarks;
+mod bot;
+mod cli;
+mod config;
+mod console;
+mod cursor;
+mod decay;
+mod fullscreen;
+mod hints;
+mod history;
+mod hotseat;
+mod input;
+mod layout;
+mod loading;
+mod macros;
+mod magnifier;
+mod minimap;
+mod options;
+mod particles;
+mod power;
+mod preview;
+mod sandbox;
+mod scoreboard;
+mod screen;
+mod server;
+mod settings;
+mod stats;
+mod style;
+mod tabs;
+mod timer;
+mod title;
+mod toast;
+mod tween;
+mod ui;
+mod undo;
+mod worldmap;
+
+/// where the replay of the last game is written when the window closes,
+/// before the extension
+const REPLAY_PATH: &str = "last";
+/// where F5 saves to and F9 loads from, before the extension
+const QUICKSAVE_PATH: &str = "quicksave";
 
 fn window_conf() -> Conf {
     Conf {
         window_title: "infinite klondike".to_owned(),
-        high_dpi: false,
+        high_dpi: true,
         ..Default::default()
     }
 }
 
-fn draw_texture_box(texture: Texture2D, x: f32, y: f32, color: Color, src: Rect) {
+/// draws `src` from the atlas the size of a card
+fn draw_texture_box(atlas: Atlas, x: f32, y: f32, color: Color, src: Rect) {
     draw_texture_ex(
-        texture,
+        atlas.texture,
         x,
         y,
         color,
         DrawTextureParams {
-            dest_size: Some(Vec2::new(44.0, 64.0)),
+            dest_size: Some(Vec2::new(Layout::CARD_WIDTH, Layout::CARD_HEIGHT)),
             source: Some(src),
             rotation: 0.0,
             flip_x: false,
@@ -31,303 +129,2555 @@ fn draw_texture_box(texture: Texture2D, x: f32, y: f32, color: Color, src: Rect)
     );
 }
 
-fn draw_atlas_item(atlas: Texture2D, x: f32, y: f32, offset: f32) {
-    draw_texture_box(
-        atlas,
-        x,
-        y,
-        WHITE,
-        Rect {
-            x: offset,
-            y: 0.0,
-            w: 22.0,
-            h: 32.0,
-        },
-    )
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum Suit {
-    Club = 0b10,
-    Diamond = 0b00,
-    Heart = 0b01,
-    Spade = 0b11,
-}
-
-impl Suit {
-    const fn get_x(&self) -> f32 {
-        match self {
-            Suit::Club => 330.0,
-            Suit::Diamond => 352.0,
-            Suit::Heart => 374.0,
-            Suit::Spade => 396.0,
-        }
-    }
+fn draw_card_back(atlas: Atlas, style: Style, x: f32, y: f32) {
+    let src = atlas.sprites.back(style.card_back);
+    draw_texture_box(atlas, x, y, WHITE, src)
 }
 
-impl Distribution<Suit> for Standard {
-    fn sample<R: ::rand::Rng + ?Sized>(&self, rng: &mut R) -> Suit {
-        match rng.gen_range(0..=3) {
-            0 => Suit::Club,
-            1 => Suit::Diamond,
-            2 => Suit::Heart,
-            _ => Suit::Spade,
-        }
+fn draw_card(card: BitCard, atlas: Atlas, style: Style, x: f32, y: f32) {
+    let color = style.suit_color(card);
+    let sprites = atlas.sprites;
+    draw_texture_box(atlas, x, y, WHITE, sprites.face());
+    draw_texture_box(atlas, x, y, color, sprites.suit(card.suit()));
+    draw_texture_box(atlas, x, y, color, sprites.rank(card.number()));
+    if card.is_red() {
+        style.draw_red_pattern(x, y);
     }
-}
-
-fn draw_card(card: BitCard, atlas: Texture2D, x: f32, y: f32) {
-    let color = if card.is_red() { RED } else { WHITE };
-    draw_texture_box(atlas, x, y, WHITE, Rect::new(0.0, 0.0, 22.0, 32.0));
-    draw_texture_box(
-        atlas,
-        x,
-        y,
-        color,
-        Rect::new(card.suit().get_x(), 0.0, 22.0, 32.0),
-    );
-    draw_texture_box(
-        atlas,
-        x,
-        y,
-        color,
-        Rect::new(44.0 + 22.0 * card.number() as f32, 0.0, 22.0, 32.0),
-    );
+    style.draw_suit_mark(card.suit(), color, x, y);
+    style.draw_corner_badge(card.suit(), color, x, y);
 }
 
 struct State {
     grabbed_stack: CardStack,
+    /// the column the grabbed cards came from, and go back to if they're let
+    /// go of anywhere they can't go. no chunks of the board are let go of
+    /// while cards are carried, see `generate_new`, so it stays dealt however
+    /// far the camera goes. left over from the last grab while `from_waste`.
     grabbed_stack_row: usize,
-    tableau: Vec<Column>,
-    foundations: HashMap<usize, BitCard>,
+    /// the grabbed card is the top of the waste, which stays on the waste
+    /// until it's put somewhere
+    from_waste: bool,
+    game: Game,
+    moves: Vec<Move>,
     camera: Vec2,
+    /// how much of the board the window showed last frame, in layout
+    /// coordinates, to notice it changing
+    view: Vec2,
+    server: Option<Server>,
+    bookmarks: Bookmarks,
+    sandbox: Sandbox,
+    layout: Layout,
+    style: Style,
+    assists: Assists,
+    session: Session,
+    history: History,
+    /// the challenge being played, if this game is one
+    challenge: Option<Run>,
+    /// a move waiting for the player to confirm it, with the card it buries
+    confirm_bury: Option<(Move, BitCard)>,
+    /// cards going on foundations by themselves, and when the last one went
+    autoplay: Option<(Autoplay, f64)>,
+    /// how many times each position on screen has come up since anything
+    /// last went on a foundation or got turned over, by `Game::window_hash`
+    positions: HashMap<u64, u32>,
+    grab: Grab,
+    scoring: Scoring,
+    score: i64,
+    /// keeps fewer columns dealt and no card history, particles or slides,
+    /// see [`Settings::low_memory`]
+    low_memory: bool,
+    /// the directory games get streamed under, with `--stream`
+    stream: Option<PathBuf>,
+    /// the best times this game is up against
+    bests: Bests,
+    achievements: Achievements,
+    hints: Hints,
+    /// the game playing itself, when it's been asked to
+    bot: Option<Bot>,
+    /// a look at whether the columns on screen are dead, while it runs
+    analysis: Option<Analysis>,
+    /// whose turn it is and how they're doing, when two are taking turns
+    hot_seat: Option<HotSeat>,
+    macros: Macros,
+    /// how long each column on screen has been left alone, in decay games
+    decay: Decay,
+    /// a region this game just cleared, to say well done for
+    cleared: Option<usize>,
+    /// the rules picked in the options screen for the next game, when
+    /// they're not the ones this game has
+    next_rules: Option<Rules>,
+    /// cards sliding to where they've been moved, always empty in low
+    /// memory mode
+    tweens: Tweens,
+    /// sparks over the cards, none at all in low memory mode
+    particles: Option<Particles>,
+    /// moves to take back
+    undo: Undo,
+    /// which tab this is, for naming its quicksave and replay. picked by
+    /// `Tabs` when the tab opens and kept when tabs before it close.
+    id: usize,
 }
 
 impl State {
-    const ROW_WIDTH: f32 = 48.0;
-    const TABLEAU_Y_OFFSET: f32 = 68.0;
-    const FOUNDATION_X_OFFSET: f32 = Self::ROW_WIDTH * 3.0;
-    fn new() -> Self {
-        let mut tableau = Vec::new();
-        let mut rng = ::rand::thread_rng();
-        for x in 0..50 {
-            tableau.push(Column::new(&mut rng, x))
-        }
-
-        let w = screen_width();
-        let shown_cards = 7.0;
-        let camera = Vec2::new(w - (shown_cards - 1.0) * 48.0, 2.0);
-        State {
+    /// how many columns a new game is dealt up front
+    const DEALT_COLUMNS: usize = 50;
+    /// how many columns past the screen low memory mode keeps generated, so
+    /// scrolling back and forth a little doesn't deal them over and over
+    const SPARE_COLUMNS: usize = 8;
+    /// how many columns either side of the screen stay dealt otherwise.
+    /// anything further is dealt again from the seed when it's needed.
+    const KEPT_COLUMNS: usize = 4096;
+
+    /// a game with `settings`, dealt from a seed picked with `seeds` unless
+    /// the settings have one
+    fn new(settings: Settings, seeds: &mut dyn RngCore) -> Self {
+        let Settings {
+            layout,
+            rules,
+            style,
+            assists,
+            idle_after,
+            challenge,
+            seed,
+            grab,
+            scoring,
+            low_memory,
+            hot_seat,
+            // the same for every tab, so they're kept by `audio`
+            volume: _,
+            music: _,
+        } = settings;
+        let mut layout = layout;
+        // the rest get dealt as they come on screen
+        let dealt = if low_memory { 0 } else { Self::DEALT_COLUMNS };
+        let (game, challenge) = match challenge {
+            Some(period) => {
+                let challenge = Challenge::current(period);
+                if layout.orientation == Orientation::Grid {
+                    // challenges are always played on a line
+                    layout.orientation = Orientation::Horizontal;
+                }
+                let game = Game::with_rules(challenge.seed, dealt, challenge.rules());
+                (game, Some(Run::new(challenge)))
+            }
+            None => (
+                Game::with_rules(seed.unwrap_or_else(|| seeds.gen()), dealt, rules),
+                None,
+            ),
+        };
+        let bests = Bests::new(&game);
+        let mut state = State {
             grabbed_stack: CardStack::empty(),
-            tableau,
-            foundations: HashMap::new(),
+            game,
+            moves: Vec::new(),
             grabbed_stack_row: 0,
-            camera,
+            from_waste: false,
+            camera: layout.initial_camera(),
+            view: layout.screen_size(),
+            server: None,
+            bookmarks: Bookmarks::default(),
+            sandbox: Sandbox::default(),
+            layout,
+            style,
+            assists,
+            session: Session::new(idle_after),
+            history: History::new(low_memory),
+            challenge,
+            confirm_bury: None,
+            autoplay: None,
+            positions: HashMap::new(),
+            scoring,
+            score: scoring.start(),
+            grab,
+            low_memory,
+            stream: None,
+            bests,
+            achievements: Achievements::load(),
+            hints: Hints::default(),
+            bot: None,
+            analysis: None,
+            hot_seat: hot_seat.map(HotSeat::new),
+            macros: Macros::default(),
+            decay: Decay::default(),
+            cleared: None,
+            next_rules: None,
+            tweens: Tweens::default(),
+            particles: (!low_memory).then(Particles::default),
+            undo: Undo::default(),
+            id: 0,
+        };
+        state.generate_new();
+        state
+    }
+
+    /// what this game was set up with, for starting another one like it
+    fn settings(&self) -> Settings {
+        Settings {
+            layout: self.layout,
+            rules: self.next_rules.unwrap_or_else(|| self.game.rules()),
+            style: self.style,
+            assists: self.assists,
+            idle_after: self.session.idle_after(),
+            challenge: None,
+            seed: None,
+            grab: self.grab,
+            scoring: self.scoring,
+            low_memory: self.low_memory,
+            hot_seat: self.hot_seat.as_ref().map(HotSeat::turn_length),
+            volume: audio::volume(),
+            music: audio::music_volume(),
+        }
+    }
+    /// takes on what was picked in the options screen. the rules are kept
+    /// for the next game.
+    fn set_options(&mut self, settings: Settings) {
+        let Layout {
+            card_scale,
+            pan_speed,
+            invert_pan,
+            ..
+        } = settings.layout;
+        self.rescale(|layout| layout.card_scale = card_scale);
+        self.layout.pan_speed = pan_speed;
+        self.layout.invert_pan = invert_pan;
+        self.style = settings.style;
+        self.assists = settings.assists;
+        self.grab = settings.grab;
+        self.next_rules = (settings.rules != self.game.rules()).then_some(settings.rules);
+        audio::set_volume(settings.volume);
+        audio::set_music_volume(settings.music);
+    }
+    /// whether a bookmark name or a card is being typed in
+    fn is_typing(&self) -> bool {
+        self.bookmarks.is_naming() || self.sandbox.is_typing()
+    }
+    /// moves the camera to show `column` where the first one starts out
+    fn jump_to(&mut self, column: usize) {
+        self.camera = self.layout.camera_at(column);
+        self.generate_new();
+        self.publish(&[]);
+    }
+    /// draws the map of the board along the bottom, `bottom` pixels up
+    fn draw_minimap(&self, bottom: f32) {
+        let visible = self.layout.visible_columns(self.camera);
+        let (Some(&first), Some(&last)) = (visible.iter().min(), visible.iter().max()) else {
+            return;
+        };
+        minimap::draw(&self.game, first..last + 1, bottom, self.style);
+    }
+    /// what the world map shows for this board
+    fn world_map(&self) -> Vec<worldmap::Tile> {
+        let column = |camera| self.layout.first_column(camera);
+        let marks: Vec<usize> = self
+            .bookmarks
+            .marks
+            .iter()
+            .map(|mark| column(mark.camera))
+            .collect();
+        worldmap::summarize(&self.game, column(self.camera), &marks)
+    }
+    /// goes to the region clicked on the world map, returning whether one was
+    fn world_map_click(&mut self) -> bool {
+        let Some(region) = worldmap::region_at(self.world_map().len()) else {
+            return false;
+        };
+        self.jump_to(worldmap::first_column(&self.game, region));
+        true
+    }
+    /// where the mouse is, in layout coordinates
+    fn mouse(&self) -> Vec2 {
+        self.layout.screen_to_layout(mouse_position().into())
+    }
+    /// whether the mouse is over empty space, with no cards or foundations
+    fn is_mouse_on_background(&self) -> bool {
+        if self.is_mouse_on_foundation() || self.is_mouse_on_stock() {
+            return false;
+        }
+        let Some(column) = self.get_row_over_mouse() else {
+            return true;
+        };
+        let Some(stack) = self.game.tableau().get(column) else {
+            return true;
+        };
+        let rows = self.layout.hidden_rows(stack.under) + stack.visible().len() as usize;
+        !self.layout.on_pile(self.camera, column, rows, self.mouse())
+    }
+    /// moves the camera by `delta` window pixels
+    fn pan(&mut self, delta: Vec2) {
+        self.camera += delta / self.layout.scale();
+        self.generate_new();
+    }
+    /// scrolls with the arrow keys, and by holding the mouse near the edge of
+    /// the window while carrying cards, so cards can be carried as far as
+    /// you like. returns whether the camera moved.
+    fn scroll(&mut self) -> bool {
+        let keys = [
+            (KeyCode::Left, Vec2::X),
+            (KeyCode::Right, -Vec2::X),
+            (KeyCode::Up, Vec2::Y),
+            (KeyCode::Down, -Vec2::Y),
+        ];
+        let mut direction = Vec2::ZERO;
+        if !self.is_typing() {
+            for (key, towards) in keys {
+                if is_key_down(key) {
+                    direction += towards;
+                }
+            }
+        }
+        if !self.grabbed_stack.is_empty() {
+            let (x, y) = mouse_position();
+            let edge = Layout::SCROLL_EDGE;
+            let mut towards = Vec2::ZERO;
+            if x < edge {
+                towards += Vec2::X;
+            } else if x > screen_width() - edge {
+                towards -= Vec2::X;
+            }
+            if y < edge {
+                towards += Vec2::Y;
+            } else if y > screen_height() - edge {
+                towards -= Vec2::Y;
+            }
+            if self.layout.orientation != Orientation::Grid {
+                // only along the columns, so docked foundations and the tab
+                // strip can still be reached without the board running off
+                let axis = self.layout.main_axis();
+                towards = axis * towards.dot(axis);
+            }
+            direction += towards;
         }
+        if direction == Vec2::ZERO {
+            return false;
+        }
+        self.pan(direction * self.layout.pan_speed * get_frame_time());
+        true
     }
     fn get_row_over_mouse(&self) -> Option<usize> {
-        let (x, _) = mouse_position();
-        let x = x - self.camera.x + Self::ROW_WIDTH;
-        if x < 0.0 {
-            return None;
+        self.layout.column_at(self.camera, self.mouse())
+    }
+    /// how fast the mouse should go, when it's slowed down for carrying
+    /// cards over the columns
+    fn precision(&self) -> Option<f32> {
+        let over_columns = !self.is_mouse_on_foundation()
+            && !self.is_mouse_on_stock()
+            && self.get_row_over_mouse().is_some();
+        self.assists
+            .precision
+            .filter(|_| !self.grabbed_stack.is_empty() && over_columns)
+    }
+    fn draw(&self, atlas: Atlas) {
+        set_camera(&self.layout.camera2d());
+        self.draw_world(atlas, self.layout.is_overview(1.0));
+        if let Some(particles) = &self.particles {
+            particles.draw();
         }
-        Some((x / Self::ROW_WIDTH) as usize)
+        set_default_camera();
+        let reshuffles = self.game.reshuffles_left();
+        if reshuffles > 0 {
+            let label = format!("reshuffles: {} (r over a stuck column)", reshuffles);
+            draw_text(&label, 8.0, screen_height() - 56.0, 18.0, WHITE);
+        }
+        let score = format!("score: {}", self.score);
+        draw_text(&score, 8.0, screen_height() - 76.0, 18.0, WHITE);
+        self.bests.draw(&self.game, &self.session);
+        self.draw_region();
+        self.macros.draw();
+        if let Some(rect) = self.finish_button() {
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, DARKGRAY);
+            draw_text("finish (f)", rect.x + 10.0, rect.y + 17.0, 20.0, WHITE);
+        }
+        // debug!("{:?}", Self::get_row_over_mouse());
     }
-    fn draw(&self, atlas: Texture2D) {
-        let min = -self.camera.x / 48.0;
-        let w = screen_width();
-        let visible = (w - self.camera.x) as usize / 48 + 2;
-        let tableau_slice = &self.tableau[min as usize..visible];
-        let camera_offset_x = if self.camera.x > 0.0 {
-            self.camera.x
-        } else {
-            self.camera.x % 48.0
-        };
-        for (x, stack) in tableau_slice.iter().enumerate() {
-            for y in 0..stack.under {
-                draw_atlas_item(
-                    atlas,
-                    48.0 * (x as f32 - 1.0) + camera_offset_x,
-                    16.0 * y as f32 + Self::TABLEAU_Y_OFFSET + self.camera.y,
-                    22.0,
-                )
+    /// the region in the middle of the screen
+    fn middle_region(&self) -> Option<usize> {
+        let visible = self.layout.visible_columns(self.camera);
+        let middle = visible.get(visible.len() / 2)?;
+        Some(self.game.region_of(*middle))
+    }
+    /// how much of the region in the middle of the screen has gone on the
+    /// foundations, above the clock
+    fn draw_region(&self) {
+        let Some(region) = self.middle_region() else {
+            return;
+        };
+        let founded = self.game.region_founded(region);
+        let dealt = self.game.region_dealt(region);
+        let line = format!(
+            "region {}: {:.0}% founded ({} of {})",
+            region + 1,
+            100.0 * f64::from(founded) / dealt as f64,
+            founded,
+            dealt
+        );
+        draw_text(&line, 8.0, screen_height() - 136.0, 18.0, WHITE);
+    }
+    /// everything that's drawn in layout coordinates, with whichever camera
+    /// is set. `flat` draws the columns as blocks of colour, for when
+    /// they're too small to make out anyway.
+    fn draw_world(&self, atlas: Atlas, flat: bool) {
+        for x in self.layout.visible_columns(self.camera) {
+            let Some(stack) = self.game.tableau().get(x) else {
+                continue;
+            };
+            if flat {
+                self.draw_flat_column(x, stack);
+                continue;
             }
-            if stack.under == 0 && stack.is_visible_empty() {
-                // draw empty
-                draw_card_outline(
-                    atlas,
-                    48.0 * (x as f32 - 1.0) + camera_offset_x,
-                    Self::TABLEAU_Y_OFFSET + self.camera.y,
-                )
+            let hidden_rows = self.layout.hidden_rows(stack.under);
+            for y in 0..hidden_rows {
+                let pos = self.layout.card_pos(self.camera, x, y);
+                if self.game.rules().practice && self.layout.is_on_screen(pos) {
+                    // squashed piles show the card that's next to come up
+                    let row = y as u32 + stack.under - hidden_rows as u32;
+                    if let Some(card) = self.game.hidden_card(x, row) {
+                        draw_card(card, atlas, self.style, pos.x, pos.y);
+                        self.style
+                            .fade(pos.x, pos.y, Layout::CARD_WIDTH, Layout::CARD_HEIGHT);
+                        continue;
+                    }
+                }
+                draw_card_back(atlas, self.style, pos.x, pos.y)
+            }
+            if hidden_rows == 1 && stack.under > 1 {
+                // face down cards squashed into one card back get a count
+                let pos = self.layout.card_pos(self.camera, x, 0);
+                draw_text(
+                    &stack.under.to_string(),
+                    pos.x + 24.0,
+                    pos.y + 14.0,
+                    16.0,
+                    WHITE,
+                );
+            }
+            if stack.is_finished() {
+                // dimmed and stamped, so played out stretches stand out
+                // when scanning along the board
+                let pos = self.layout.card_pos(self.camera, x, 0);
+                draw_card_outline(atlas, pos.x, pos.y);
+                self.style
+                    .fade(pos.x, pos.y, Layout::CARD_WIDTH, Layout::CARD_HEIGHT);
+                let color = if self.style.monochrome { WHITE } else { GRAY };
+                draw_text("done", pos.x + 7.0, pos.y + 36.0, 18.0, color);
             } else {
                 for (n, card) in stack.visible().iter().enumerate() {
-                    draw_card(
-                        card,
-                        atlas,
-                        48.0 * (x as f32 - 1.0) + camera_offset_x,
-                        16.0 * (n + stack.under as usize) as f32
-                            + Self::TABLEAU_Y_OFFSET
-                            + self.camera.y,
-                    );
+                    if self.tweens.is_sliding(Spot::Card { column: x, idx: n }) {
+                        // drawn over everything else on its way
+                        continue;
+                    }
+                    let pos = self.layout.card_pos(self.camera, x, n + hidden_rows);
+                    draw_card(card, atlas, self.style, pos.x, pos.y);
+                }
+                let len: usize = stack.visible().len().into();
+                if len > 0 {
+                    let pos = self.layout.card_pos(self.camera, x, len - 1 + hidden_rows);
+                    let run = stack.visible().run_len(self.game.rules().deck);
+                    self.style.draw_run_badge(run, pos.x, pos.y);
+                }
+                if let Some(left) = self.decay.countdown(x) {
+                    // on the card that's about to turn back over
+                    let pos = self.layout.card_pos(self.camera, x, hidden_rows);
+                    self.style.draw_countdown(left, pos.x, pos.y);
                 }
             }
         }
-        // draw foundation
-        let foundation_min = ((Self::FOUNDATION_X_OFFSET - self.camera.x) / 48.0 - 5.0) as usize;
-        let foundation_camera_x_offset = if self.camera.x < -Self::FOUNDATION_X_OFFSET {
-            self.camera.x % 48.0 + Self::ROW_WIDTH
+        if self.layout.foundations_cover_tableau() {
+            let band = self.layout.foundation_band(self.camera);
+            draw_rectangle(band.x, band.y, band.w, band.h, self.style.shade(0.9));
+        }
+        if self.layout.pinned() {
+            self.draw_pinned_foundations(atlas);
         } else {
-            self.camera.x + Self::FOUNDATION_X_OFFSET
-        };
-        for x in foundation_min..visible {
-            let local_x = x - foundation_min;
-            if let Some(card) = self.foundations.get(&x) {
-                draw_card(
-                    *card,
-                    atlas,
-                    48.0 * (local_x as f32 - 1.0) + foundation_camera_x_offset,
-                    self.camera.y,
-                )
-            } else {
-                draw_card_outline(
-                    atlas,
-                    48.0 * (local_x as f32 - 1.0) + foundation_camera_x_offset,
-                    self.camera.y,
-                );
+            self.draw_world_foundations(atlas);
+        }
+        if self.game.rules().stock {
+            self.draw_stock(atlas);
+        }
+        let card = vec2(Layout::CARD_WIDTH, Layout::CARD_HEIGHT);
+        for spot in self.hints.spots(self.moves.len()) {
+            if let Some(pos) = self.spot_pos(*spot) {
+                hints::draw_glow(pos, card, self.style);
+            }
+        }
+        if !flat {
+            for (spot, offset) in self.tweens.sliding() {
+                if let (Some(card), Some(pos)) = (self.card_at(spot), self.spot_pos(spot)) {
+                    let pos = pos + offset;
+                    draw_card(card, atlas, self.style, pos.x, pos.y);
+                }
             }
         }
         for (n, card) in self.grabbed_stack.iter().enumerate() {
-            let (x, y) = mouse_position();
-            let x = (x / 2.0).floor() * 2.0;
-            let y = (y / 2.0).floor() * 2.0;
-            draw_card(card, atlas, x, y + (16.0 * n as f32));
+            let pos = self.carried_pos(n);
+            draw_card(card, atlas, self.style, pos.x, pos.y);
+        }
+    }
+    /// where the `n`th carried card is drawn, under the mouse
+    fn carried_pos(&self, n: usize) -> Vec2 {
+        let mouse = (self.mouse() / 2.0).floor() * 2.0;
+        mouse + self.layout.fan_axis() * Layout::FAN * n as f32
+    }
+    /// where each carried card is drawn
+    fn carried_positions(&self) -> Vec<Vec2> {
+        (0..self.grabbed_stack.iter().len())
+            .map(|n| self.carried_pos(n))
+            .collect()
+    }
+    /// the card at `spot`, if there is one
+    fn card_at(&self, spot: Spot) -> Option<BitCard> {
+        match spot {
+            Spot::Card { column, idx } => {
+                self.game.tableau().get(column)?.visible().iter().nth(idx)
+            }
+            Spot::Foundation(foundation) => self.game.foundations().get(&foundation).copied(),
+            Spot::Waste => self.game.waste().last().copied(),
+            Spot::End(_) => None,
+        }
+    }
+    /// where the card at `spot` is drawn right now, partway through sliding
+    /// there if it is
+    fn drawn_pos(&self, spot: Spot) -> Option<Vec2> {
+        Some(self.spot_pos(spot)? + self.tweens.offset(spot))
+    }
+    /// column `x` as blocks of colour. all its face down cards are a single
+    /// block, so a zoomed out board with hundreds of long columns on screen
+    /// doesn't take thousands of draws.
+    fn draw_flat_column(&self, x: usize, stack: &Column) {
+        let card = vec2(Layout::CARD_WIDTH, Layout::CARD_HEIGHT);
+        if stack.is_finished() {
+            let pos = self.layout.card_pos(self.camera, x, 0);
+            draw_rectangle_lines(pos.x, pos.y, card.x, card.y, 4.0, GRAY);
+            return;
+        }
+        let hidden_rows = self.layout.hidden_rows(stack.under);
+        if hidden_rows > 0 {
+            let pos = self.layout.card_pos(self.camera, x, 0);
+            let size = card + self.layout.fan_axis() * Layout::FAN * (hidden_rows - 1) as f32;
+            self.style.draw_flat_back(pos.x, pos.y, size.x, size.y);
+        }
+        for (n, face_up) in stack.visible().iter().enumerate() {
+            let pos = self.layout.card_pos(self.camera, x, n + hidden_rows);
+            self.style
+                .draw_flat_card(face_up, pos.x, pos.y, card.x, card.y);
+        }
+    }
+    /// zooms out a step, or back in
+    fn zoom(&mut self, out: bool) {
+        let zoomed_out = self.layout.zoomed_out;
+        let zoomed_out = if out {
+            (zoomed_out + 1).min(Layout::MAX_ZOOMED_OUT)
+        } else {
+            zoomed_out.saturating_sub(1)
+        };
+        self.rescale(|layout| layout.zoomed_out = zoomed_out);
+    }
+    /// changes how big the layout draws things with `change`, keeping the
+    /// middle of the board in the middle of the window
+    fn rescale(&mut self, change: impl FnOnce(&mut Layout)) {
+        change(&mut self.layout);
+        self.fit_window();
+    }
+    /// keeps up with the window changing size, or with how much of the board
+    /// it shows changing. the middle of the board stays in the middle of the
+    /// window, and columns are dealt for any new room. call once a frame.
+    fn fit_window(&mut self) {
+        let view = self.layout.screen_size();
+        if view == self.view {
+            return;
+        }
+        // only along the board, the tops of the columns stay put
+        let axis = self.layout.main_axis();
+        self.camera += axis * (view - self.view).dot(axis) / 2.0;
+        self.view = view;
+        self.generate_new();
+    }
+    /// the board around the mouse again, blown up under the magnifier
+    fn draw_magnified(&self, atlas: Atlas) {
+        set_camera(&magnifier::camera(self.layout));
+        let area = magnifier::area(self.layout);
+        draw_rectangle(area.x, area.y, area.w, area.h, self.style.table_color());
+        self.draw_world(atlas, self.layout.is_overview(magnifier::ZOOM));
+        set_default_camera();
+        magnifier::draw_frame(WHITE);
+    }
+    /// tints every column on screen by how many moves it was part of this
+    /// session, with the count at the top
+    fn draw_heatmap(&self) {
+        set_camera(&self.layout.camera2d());
+        let most = self.session.most_activity().max(1) as f32;
+        for x in self.layout.visible_columns(self.camera) {
+            let activity = self.session.activity(x);
+            if activity == 0 {
+                continue;
+            }
+            let rect = self.layout.column_rect(self.camera, x);
+            if !self.style.monochrome {
+                let heat = activity as f32 / most;
+                let color = Color::new(1.0, 0.3, 0.0, 0.1 + 0.4 * heat);
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+            }
+            let label = activity.to_string();
+            draw_text(&label, rect.x + 2.0, rect.y - 2.0, 16.0, WHITE);
+        }
+        set_default_camera();
+    }
+    fn draw_world_foundations(&self, atlas: Atlas) {
+        for x in self.layout.visible_foundations(self.camera) {
+            let pos = self.layout.foundation_pos(self.camera, x);
+            match self.foundation_shown(x) {
+                Some(card) => draw_card(card, atlas, self.style, pos.x, pos.y),
+                None => draw_card_outline(atlas, pos.x, pos.y),
+            }
+        }
+    }
+    /// the stock and the waste in the corner of the window
+    fn draw_stock(&self, atlas: Atlas) {
+        let fanned = self.game.rules().cards_per_draw() as usize;
+        let area = self.layout.stock_area(fanned);
+        draw_rectangle(area.x, area.y, area.w, area.h, self.style.shade(0.9));
+        let pos = self.layout.stock_pos();
+        match self.game.next_stock_card() {
+            // practice shows what's coming, like the face down cards
+            Some(card) if self.game.rules().practice => {
+                draw_card(card, atlas, self.style, pos.x, pos.y);
+                self.style
+                    .fade(pos.x, pos.y, Layout::CARD_WIDTH, Layout::CARD_HEIGHT);
+            }
+            _ => draw_card_back(atlas, self.style, pos.x, pos.y),
+        }
+        let drawn = self.game.drawn().to_string();
+        draw_text(
+            &drawn,
+            pos.x + 4.0,
+            pos.y + Layout::CARD_HEIGHT - 4.0,
+            16.0,
+            WHITE,
+        );
+        // the carried card is still on the waste until it's put down
+        let carried = usize::from(self.from_waste || self.tweens.is_sliding(Spot::Waste));
+        let waste = self.game.waste();
+        let waste = &waste[..waste.len() - carried];
+        if waste.is_empty() {
+            let pos = self.layout.waste_pos();
+            draw_card_outline(atlas, pos.x, pos.y);
+        }
+        // drawing three fans the last few out, with only the top one playable
+        let shown = waste.len().min(fanned);
+        for (n, card) in waste[waste.len() - shown..].iter().enumerate() {
+            let pos = self.layout.waste_fan_pos(shown - 1 - n);
+            draw_card(*card, atlas, self.style, pos.x, pos.y);
+        }
+    }
+    /// where a hint's spot is, in layout coordinates
+    fn spot_pos(&self, spot: Spot) -> Option<Vec2> {
+        match spot {
+            Spot::Card { column, idx } => {
+                let stack = self.game.tableau().get(column)?;
+                let row = self.layout.hidden_rows(stack.under) + idx;
+                Some(self.layout.card_pos(self.camera, column, row))
+            }
+            Spot::End(column) => {
+                let stack = self.game.tableau().get(column)?;
+                let len: usize = stack.visible().len().into();
+                let row = self.layout.hidden_rows(stack.under) + len;
+                Some(
+                    self.layout
+                        .card_pos(self.camera, column, row.saturating_sub(1)),
+                )
+            }
+            Spot::Foundation(foundation) if self.layout.pinned() => {
+                let slot = self.pinned_slots().iter().position(|f| *f == foundation)?;
+                Some(self.layout.pinned_pos(self.camera, slot))
+            }
+            Spot::Foundation(foundation) => {
+                Some(self.layout.foundation_pos(self.camera, foundation))
+            }
+            Spot::Waste => Some(self.layout.waste_pos()),
         }
-        // debug!("{:?}", Self::get_row_over_mouse());
+    }
+    /// whether the mouse is over the stock, the waste or around them
+    fn is_mouse_on_stock(&self) -> bool {
+        let rules = self.game.rules();
+        rules.stock
+            && self
+                .layout
+                .stock_area(rules.cards_per_draw() as usize)
+                .contains(self.mouse())
+    }
+    /// the foundation piles shown in the pinned bar, left to right: every
+    /// pile with cards on it, then the next free one
+    fn pinned_slots(&self) -> Vec<usize> {
+        let mut slots: Vec<usize> = self.game.foundations().keys().copied().collect();
+        slots.sort_unstable();
+        slots.push(self.game.free_foundation());
+        slots
+    }
+    fn draw_pinned_foundations(&self, atlas: Atlas) {
+        for (slot, foundation) in self.pinned_slots().into_iter().enumerate() {
+            let pos = self.layout.pinned_pos(self.camera, slot);
+            match self.foundation_shown(foundation) {
+                Some(card) => draw_card(card, atlas, self.style, pos.x, pos.y),
+                None => draw_card_outline(atlas, pos.x, pos.y),
+            }
+        }
+    }
+    /// the card shown on `foundation`: the top one, or the one under it
+    /// while the top one's still on its way
+    fn foundation_shown(&self, foundation: usize) -> Option<BitCard> {
+        self.tweens
+            .covers(Spot::Foundation(foundation))
+            .unwrap_or_else(|| self.game.foundations().get(&foundation).copied())
     }
     fn is_mouse_on_foundation(&self) -> bool {
-        let (_, y) = mouse_position();
-        let y = y - self.camera.y;
-        y < Self::TABLEAU_Y_OFFSET
+        self.layout.over_foundations(self.camera, self.mouse())
+    }
+    /// the foundation pile under the mouse, if it's over one
+    fn foundation_under_mouse(&self) -> Option<usize> {
+        if self.layout.pinned() {
+            let slot = self.layout.pinned_slot_at(self.mouse())?;
+            self.pinned_slots().get(slot).copied()
+        } else {
+            self.layout.foundation_at(self.camera, self.mouse())
+        }
     }
 
     /// this finalizes an card move from a column.
-    /// this reveals a new card if the moves leaves the "visible" stack empty
-    /// and there are hidden cards.
-    fn finalize_column(&mut self) {
-        self.tableau[self.grabbed_stack_row].maybe_reveal_card(&mut ::rand::thread_rng())
+    /// the grabbed cards go back to where they came from and the move is
+    /// done properly through the rules, which also reveals a new card if
+    /// the move leaves the "visible" stack empty and there are hidden cards.
+    fn finalize_column(&mut self, mv: Move) {
+        let carried = self.carried_positions();
+        self.reset_column();
+        // where the cards are coming from, to slide them over
+        let sources = self.sources(mv);
+        let from: Vec<Vec2> = if !carried.is_empty() {
+            carried
+        } else if mv == Move::Draw {
+            vec![self.layout.stock_pos()]
+        } else {
+            sources
+                .iter()
+                .filter_map(|spot| self.drawn_pos(*spot))
+                .collect()
+        };
+        let covers = match mv {
+            Move::Foundation { foundation, .. } | Move::WasteFoundation { foundation } => {
+                self.game.foundations().get(&foundation).copied()
+            }
+            _ => None,
+        };
+        self.history.prepare(&self.game, mv);
+        let founded = self.game.founded();
+        let region = match mv {
+            Move::Foundation { from, .. } => Some(self.game.region_of(from)),
+            _ => None,
+        };
+        let region_before = region.map(|region| self.game.region_founded(region));
+        // only what the player does goes in a macro or starts a step to take
+        // back, not what the game does by itself
+        let by_hand = self.autoplay.is_none() && self.bot.is_none();
+        let action = by_hand
+            .then(|| self.macros.relative(&self.game, mv))
+            .flatten();
+        let step = (by_hand && !self.undo.is_grouped() && self.can_undo()).then(|| self.step());
+        let events = match self.game.apply(mv) {
+            Ok(events) => events,
+            // dropping cards back where they came from is fine
+            Err(MoveError::SameColumn) => return,
+            Err(_) => {
+                audio::play(Effect::Invalid);
+                return;
+            }
+        };
+        if let Some(action) = action {
+            self.macros.record(action);
+        }
+        if let Some(step) = step {
+            self.undo.push(step);
+        }
+        audio::play_move(&events);
+        for spot in sources {
+            self.tweens.stop(spot);
+        }
+        for (spot, from) in self.landed(mv, from.len()).into_iter().zip(from) {
+            self.slide(spot, from, covers);
+        }
+        self.celebrate(&events);
+        self.history.record(&events, self.moves.len());
+        self.moves.push(mv);
+        self.session.record(&events, &self.game);
+        self.bests.update(&self.game, founded, &self.session);
+        self.achievements.update(&self.game, &events);
+        let scoring = self.scoring;
+        let points = events
+            .iter()
+            .map(|event| scoring.points(event))
+            .sum::<i64>();
+        self.score += points;
+        if let Some(seat) = &mut self.hot_seat {
+            seat.record(self.game.founded() - founded, points);
+        }
+        self.publish(&events);
+        self.announce(founded);
+        self.watch_repeats(&events);
+        if let (Some(region), Some(before)) = (region, region_before) {
+            self.watch_region(region, before);
+        }
+        if let Move::Foundation { foundation, .. } = mv {
+            if self.assists.auto_chain {
+                self.autoplay = Some((Autoplay::Chain(foundation), get_time()));
+            }
+        }
+    }
+
+    /// sparks for cards that went on foundations, once they've slid there
+    /// (none in monochrome, which never animates, or in low memory mode)
+    fn celebrate(&mut self, events: &[Event]) {
+        if self.style.monochrome {
+            return;
+        }
+        for event in events {
+            let Event::Founded { foundation, card } = event else {
+                continue;
+            };
+            let Some(at) = self.spot_pos(Spot::Foundation(*foundation)) else {
+                continue;
+            };
+            let color = self.style.suit_color(*card);
+            let points = self.scoring.points(event);
+            let confetti = self.style.confetti();
+            let Some(particles) = &mut self.particles else {
+                return;
+            };
+            particles.burst(at, color, points, Tweens::LENGTH);
+            if card.number() == 12 {
+                // the king, and the foundation's done
+                particles.cascade(at, confetti, Tweens::LENGTH);
+            }
+        }
+    }
+
+    /// notices `region` being cleared by a move, `before` being how many
+    /// cards had gone on foundations from it before the move. it doesn't
+    /// count in sandbox games, cards can be made up there.
+    fn watch_region(&mut self, region: usize, before: u32) {
+        let dealt = self.game.region_dealt(region);
+        let now = self.game.region_founded(region);
+        if !self.game.rules().sandbox && u64::from(before) < dealt && u64::from(now) >= dealt {
+            self.cleared = Some(region);
+        }
+    }
+
+    /// warns when the same cards keep going back and forth on screen
+    /// without getting anywhere
+    fn watch_repeats(&mut self, events: &[Event]) {
+        /// how many times a position comes up before it's worth a warning
+        const REPEATS: u32 = 3;
+        let progress = events
+            .iter()
+            .any(|event| !matches!(event, Event::Moved(Move::Tableau { .. })));
+        if progress {
+            self.positions.clear();
+            return;
+        }
+        let hash = self
+            .game
+            .window_hash(self.layout.visible_columns(self.camera));
+        let seen = self.positions.entry(hash).or_default();
+        *seen += 1;
+        if *seen == REPEATS {
+            toast::info(format!(
+                "this has come up {} times now, going round in circles?",
+                REPEATS
+            ));
+        }
+    }
+
+    /// pops up a toast when a move passes a milestone, `founded` being how
+    /// many cards were founded before it
+    fn announce(&self, founded: usize) {
+        const MILESTONE: usize = 100;
+        let now = self.game.founded();
+        if now / MILESTONE > founded / MILESTONE {
+            toast::info(format!("{} cards founded", now / MILESTONE * MILESTONE));
+        }
+        let per_reshuffle = Game::FOUNDED_PER_RESHUFFLE;
+        if now / per_reshuffle > founded / per_reshuffle {
+            toast::info("earned a reshuffle, r over a stuck column uses it");
+        }
+    }
+
+    /// tells anyone watching through the server what happened
+    fn publish(&self, events: &[Event]) {
+        if let Some(server) = &self.server {
+            for event in events {
+                server.publish_event(&event.into());
+            }
+            let on_screen = self.layout.visible_columns(self.camera);
+            server.publish_state(&Value::from(Observed(&self.game, &on_screen)));
+        }
     }
 
     /// return the grabbed cards to the original column
     fn reset_column(&mut self) {
-        self.tableau[self.grabbed_stack_row].append(&mut self.grabbed_stack)
+        let carried = self.carried_positions();
+        if self.from_waste {
+            // the card never left the waste
+            self.from_waste = false;
+            self.grabbed_stack = CardStack::empty();
+            if let Some(&from) = carried.first() {
+                self.slide(Spot::Waste, from, None);
+            }
+            return;
+        }
+        let column = self.grabbed_stack_row;
+        let start = self.grabbed_stack_idx();
+        self.game.put_back(column, &mut self.grabbed_stack);
+        for (n, from) in carried.into_iter().enumerate() {
+            let spot = Spot::Card {
+                column,
+                idx: start + n,
+            };
+            self.slide(spot, from, None);
+        }
+    }
+
+    /// slides the card now at `spot` over from `from`, with `covers` shown
+    /// there until it arrives. monochrome doesn't animate and low memory
+    /// mode keeps no slides around, so cards just go.
+    fn slide(&mut self, spot: Spot, from: Vec2, covers: Option<BitCard>) {
+        if self.style.monochrome || self.low_memory {
+            return;
+        }
+        if let Some(to) = self.spot_pos(spot) {
+            self.tweens.slide(spot, from, to, covers);
+        }
+    }
+
+    /// the cards `mv` takes, before it's made
+    fn sources(&self, mv: Move) -> Vec<Spot> {
+        match mv {
+            Move::Tableau { from, idx, .. } | Move::Insert { from, idx, .. } => {
+                let len: usize = self.game.tableau()[from].visible().len().into();
+                (idx..len)
+                    .map(|idx| Spot::Card { column: from, idx })
+                    .collect()
+            }
+            Move::Foundation { from, .. } => {
+                let len: usize = self.game.tableau()[from].visible().len().into();
+                vec![Spot::Card {
+                    column: from,
+                    idx: len.saturating_sub(1),
+                }]
+            }
+            Move::Waste { .. } | Move::WasteFoundation { .. } => vec![Spot::Waste],
+            Move::Draw | Move::Reshuffle { .. } => Vec::new(),
+        }
     }
 
-    fn on_click(&mut self) {
+    /// where the `count` cards `mv` took ended up, after it's made
+    fn landed(&self, mv: Move, count: usize) -> Vec<Spot> {
+        let end = |column: usize| {
+            let len: usize = self.game.tableau()[column].visible().len().into();
+            (len.saturating_sub(count)..len)
+                .map(|idx| Spot::Card { column, idx })
+                .collect()
+        };
+        match mv {
+            Move::Tableau { to, .. } | Move::Waste { to } => end(to),
+            Move::Insert { at, .. } => end(at),
+            Move::Foundation { foundation, .. } | Move::WasteFoundation { foundation } => {
+                vec![Spot::Foundation(foundation)]
+            }
+            Move::Draw => vec![Spot::Waste],
+            Move::Reshuffle { .. } => Vec::new(),
+        }
+    }
+
+    /// the index the grabbed stack was taken from in its column
+    fn grabbed_stack_idx(&self) -> usize {
+        self.game.tableau()[self.grabbed_stack_row]
+            .visible()
+            .len()
+            .into()
+    }
+
+    /// the left button going down: picks cards up, or puts them down when
+    /// grabbing by clicking
+    fn on_press(&mut self) {
+        if self.is_locked() {
+            return;
+        }
+        self.finish_autoplay();
         if self.grabbed_stack.is_empty() {
-            // nothing grabbed
-            if let Some(row_over) = self.get_row_over_mouse() {
-                // calculate where the split is (vertically)
-                let (_, y) = mouse_position();
-                let y = (y - self.camera.y - Self::TABLEAU_Y_OFFSET) as usize / 16;
-                if let Some(visible_idx) =
-                    y.checked_sub(self.tableau[row_over].under.try_into().unwrap())
-                {
-                    self.grabbed_stack_row = row_over;
-                    if visible_idx >= self.tableau[row_over].visible().len().into() {
-                        // only pickup the top card
-                        if let Some(card) = self.tableau[row_over].visible_mut().pop() {
-                            self.grabbed_stack.push(card);
-                        }
-                    } else {
-                        self.grabbed_stack
-                            .take_from(self.tableau[row_over].visible_mut(), visible_idx);
-                    }
-                }
+            self.pick_up();
+            if !self.grabbed_stack.is_empty() {
+                audio::play(Effect::PickUp);
             }
         } else {
-            // drop grabbed stack on other stack
-            if let Some(row_over) = self.get_row_over_mouse() {
-                if self.is_mouse_on_foundation() && self.grabbed_stack.len() == 1 {
-                    let grabbed_card = self.grabbed_stack.top();
-                    if let Some(foundation_index) = row_over.checked_sub(3) {
-                        if let Some(card) = self.foundations.get(&foundation_index) {
-                            if card.same_suit(grabbed_card) && grabbed_card.is_next_card(*card) {
-                                self.foundations
-                                    .insert(foundation_index, self.grabbed_stack.pop().unwrap());
-                                self.finalize_column()
-                            }
-                        } else if grabbed_card.is_ace() {
-                            self.foundations
-                                .insert(foundation_index, self.grabbed_stack.pop().unwrap());
-                            self.finalize_column()
-                        }
-                    } else {
-                        self.reset_column()
-                    }
+            self.put_down();
+        }
+    }
+    /// the left button coming back up: puts dragged cards down
+    fn on_release(&mut self) {
+        if self.grab != Grab::Drag || self.grabbed_stack.is_empty() || self.is_locked() {
+            return;
+        }
+        if self.confirm_bury.is_some() {
+            // already waiting on the bury menu
+            return;
+        }
+        self.put_down();
+        if self.confirm_bury.is_none() {
+            // nowhere to go, they can't stay stuck to the mouse
+            self.reset_column();
+        }
+    }
+    /// picks up the cards under the mouse and everything on top of them
+    fn pick_up(&mut self) {
+        if self.layout.foundations_cover_tableau() && self.is_mouse_on_foundation() {
+            // the bar covers whatever is underneath it
+            return;
+        }
+        if self.is_mouse_on_stock() {
+            if self.layout.over_stock(self.mouse()) {
+                self.finalize_column(Move::Draw);
+            } else if let Some(&card) = self.game.waste().last() {
+                if self.layout.over_waste(self.mouse()) {
+                    self.from_waste = true;
+                    let id = self.game.waste_ids()[self.game.waste().len() - 1];
+                    self.grabbed_stack.push(card, id);
+                }
+            }
+            return;
+        }
+        if let Some(row_over) = self.get_row_over_mouse() {
+            // calculate where the split is
+            let depth = self.layout.depth_at(self.camera, self.mouse());
+            let under = self.game.tableau()[row_over].under;
+            if let Some(visible_idx) = depth.checked_sub(self.layout.hidden_rows(under)) {
+                self.grabbed_stack_row = row_over;
+                let len: usize = self.game.tableau()[row_over].visible().len().into();
+                if len > 0 {
+                    // below the stack only picks up the top card
+                    self.game
+                        .pick_up(row_over, visible_idx.min(len - 1), &mut self.grabbed_stack);
+                }
+            }
+        }
+    }
+    /// drops the grabbed stack on whatever is under the mouse
+    fn put_down(&mut self) {
+        if self.from_waste {
+            self.put_down_waste();
+        } else if self.is_mouse_on_foundation() && self.grabbed_stack.len() == 1 {
+            let grabbed_card = self.grabbed_stack.top();
+            if let Some(foundation_index) = self.foundation_under_mouse() {
+                if self.game.can_found(grabbed_card, foundation_index) {
+                    self.finalize_column(Move::Foundation {
+                        from: self.grabbed_stack_row,
+                        foundation: foundation_index,
+                    })
                 } else {
-                    let stack = &mut self.tableau[row_over];
-                    if stack.visible().can_stack(self.grabbed_stack.top()) {
-                        stack.append(&mut self.grabbed_stack);
-                        // success, deal with the grabbed stack
-                        self.finalize_column()
-                    } else {
-                        self.reset_column()
-                    }
+                    audio::play(Effect::Invalid);
                 }
             } else {
                 self.reset_column()
             }
+        } else if let Some(row_over) = self.get_row_over_mouse() {
+            let from = self.grabbed_stack_row;
+            let idx = self.grabbed_stack_idx();
+            let mv = Move::Tableau {
+                from,
+                idx,
+                to: row_over,
+            };
+            let inserts = row_over != from
+                && self
+                    .game
+                    .check_carried(from, &self.grabbed_stack, row_over)
+                    .is_err()
+                && self
+                    .game
+                    .check_insert_carried(from, &self.grabbed_stack, row_over)
+                    .is_ok();
+            if inserts {
+                // past the columns that were played on, a king that can't go
+                // anywhere else starts a new column in front of the one under it
+                self.finalize_column(Move::Insert {
+                    from,
+                    idx,
+                    at: row_over,
+                });
+            } else if let Some(card) = self.burying_warning(mv) {
+                // keep carrying the cards until the player decides
+                self.confirm_bury = Some((mv, card));
+            } else {
+                // failed moves just put the cards back
+                self.finalize_column(mv)
+            }
+        } else {
+            self.reset_column()
         }
     }
-    fn generate_new(&mut self) {
-        let w = screen_width();
-        let visible = (w - self.camera.x) as usize / 48 + 2;
-        if visible > self.tableau.len() {
-            let mut rng = ::rand::thread_rng();
-            for height in self.tableau.len()..visible {
-                self.tableau
-                    .push(Column::new(&mut rng, height.try_into().unwrap()))
+    /// drops the top card of the waste on whatever is under the mouse
+    fn put_down_waste(&mut self) {
+        let mv = if self.is_mouse_on_stock() {
+            None
+        } else if self.is_mouse_on_foundation() {
+            self.foundation_under_mouse()
+                .map(|foundation| Move::WasteFoundation { foundation })
+        } else {
+            self.get_row_over_mouse().map(|to| Move::Waste { to })
+        };
+        match mv {
+            Some(mv) => self.finalize_column(mv),
+            None => self.reset_column(),
+        }
+    }
+    /// sends the card under the mouse to a foundation, if it's on top of its
+    /// column and can go on one
+    fn found_under_mouse(&mut self) {
+        if self.is_locked() {
+            return;
+        }
+        self.finish_autoplay();
+        // a double click picks the cards up on the way, in click mode
+        self.reset_column();
+        if self.is_mouse_on_stock() {
+            let top = self.game.waste().last().copied();
+            if self.layout.over_waste(self.mouse()) {
+                if let Some(foundation) = top.and_then(|card| self.game.foundation_for(card)) {
+                    self.finalize_column(Move::WasteFoundation { foundation });
+                }
             }
+            return;
+        }
+        let Some(from) = self.get_row_over_mouse() else {
+            return;
+        };
+        let Some(stack) = self.game.tableau().get(from) else {
+            return;
+        };
+        let depth = self.layout.depth_at(self.camera, self.mouse());
+        let len: usize = stack.visible().len().into();
+        // below the pile counts as the top card, like picking up
+        let on_top = len > 0 && depth + 1 >= self.layout.hidden_rows(stack.under) + len;
+        if !on_top {
+            return;
+        }
+        let Some(card) = stack.visible().last() else {
+            return;
+        };
+        if let Some(foundation) = self.game.foundation_for(card) {
+            self.finalize_column(Move::Foundation { from, foundation });
         }
     }
-}
-
-fn draw_card_outline(atlas: Texture2D, x: f32, y: f32) {
-    draw_atlas_item(atlas, x, y, 418.0)
-}
-
-#[macroquad::main(window_conf)]
-async fn main() {
-    let atlas = load_texture("cards.png")
-        .await
-        .expect("could not find cards.png");
-    atlas.set_filter(FilterMode::Nearest);
-    let mut state = State::new();
-    let mut old_pos = mouse_position();
-    loop {
-        clear_background(BLACK);
-
-        state.draw(atlas);
-
-        //draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
-        //draw_rectangle(screen_width() / 2.0 - 60.0, 100.0, 120.0, 60.0, GREEN);
-        //draw_circle(screen_width() - 30.0, screen_height() - 30.0, 15.0, YELLOW);
-        //draw_text("HELLO", 20.0, 20.0, 20.0, DARKGRAY);
-        if is_mouse_button_pressed(MouseButton::Left) {
-            state.on_click();
+    /// the foundation pile in slot `slot` counting from the left: of the
+    /// pinned bar, or of the piles on screen
+    fn foundation_slot(&self, slot: usize) -> Option<usize> {
+        if self.layout.pinned() {
+            self.pinned_slots().get(slot).copied()
+        } else {
+            self.layout.visible_foundations(self.camera).nth(slot)
         }
-        if is_mouse_button_down(MouseButton::Right) {
-            if is_mouse_button_pressed(MouseButton::Right) {
-                old_pos = mouse_position();
+    }
+    /// sends the one carried card to the foundation in slot `slot`, if it
+    /// can go there. returns false if there isn't exactly one card carried,
+    /// so the key can mean something else.
+    fn found_to_slot(&mut self, slot: usize) -> bool {
+        if self.grabbed_stack.len() != 1 || self.confirm_bury.is_some() || self.is_locked() {
+            return false;
+        }
+        let card = self.grabbed_stack.top();
+        match self.foundation_slot(slot) {
+            Some(foundation) if self.game.can_found(card, foundation) => {
+                self.finalize_column(if self.from_waste {
+                    Move::WasteFoundation { foundation }
+                } else {
+                    Move::Foundation {
+                        from: self.grabbed_stack_row,
+                        foundation,
+                    }
+                });
             }
-            let new_pos = mouse_position();
-            let dx = new_pos.0 - old_pos.0;
-            let dy = new_pos.1 - old_pos.1;
-            state.camera.x += dx;
-            state.camera.y += dy;
-            old_pos = new_pos;
-            state.generate_new();
+            // keep carrying it, the player can try another one
+            Some(_) => toast::error(format!("that card can't go on foundation {}", slot + 1)),
+            None => toast::error(format!("there's no foundation {} on screen", slot + 1)),
         }
-        next_frame().await
+        true
+    }
+    /// the card `mv` would bury, if the player asked to be warned about it
+    fn burying_warning(&mut self, mv: Move) -> Option<BitCard> {
+        if !self.assists.warn_burying || self.grabbed_stack.len() < Assists::BURY_RUN {
+            return None;
+        }
+        let Move::Tableau { from, idx, .. } = mv else {
+            return None;
+        };
+        // the move can only be checked with the cards back where they were
+        self.reset_column();
+        let buried = self.game.check(mv).ok().and_then(|()| self.game.buries(mv));
+        self.game.pick_up(from, idx, &mut self.grabbed_stack);
+        buried
+    }
+    /// whether the board can't be played on any more, because the time for
+    /// a challenge ran out, or for now while the hot seat changes hands
+    fn is_locked(&self) -> bool {
+        self.challenge.as_ref().is_some_and(Run::is_over)
+            || self.hot_seat.as_ref().is_some_and(HotSeat::is_changing)
+    }
+    /// runs the challenge clock, letting go of any cards when it runs out
+    fn update_challenge(&mut self) {
+        let Some(run) = &mut self.challenge else {
+            return;
+        };
+        if run.update(self.game.founded(), self.moves.len()) {
+            self.reset_column();
+            self.confirm_bury = None;
+        }
+    }
+    /// runs the turn clock, letting go of the last player's cards when
+    /// their turn is up
+    fn update_hot_seat(&mut self) {
+        let Some(seat) = &mut self.hot_seat else {
+            return;
+        };
+        if seat.update(get_frame_time() as f64) {
+            self.reset_column();
+            self.confirm_bury = None;
+            self.autoplay = None;
+        }
+    }
+    /// turns cards back face down in columns on screen that were left alone
+    /// too long, in decay games
+    fn update_decay(&mut self) {
+        if !self.game.rules().decay || self.is_locked() {
+            return;
+        }
+        let mut columns = self.layout.visible_columns(self.camera);
+        if !self.grabbed_stack.is_empty() && !self.from_waste {
+            // not out from under the cards being carried
+            columns.retain(|column| *column != self.grabbed_stack_row);
+        }
+        let decayed = self
+            .decay
+            .update(self.game.tableau(), &columns, get_frame_time() as f64);
+        if decayed.is_empty() {
+            return;
+        }
+        for column in decayed {
+            self.game.decay(column);
+        }
+        self.publish(&[]);
+    }
+    /// puts the next card of an auto chain or sweep on its foundation once
+    /// it's time
+    fn update_autoplay(&mut self) {
+        let Some((autoplay, at)) = self.autoplay else {
+            return;
+        };
+        // no animating in monochrome, it all just happens
+        if !self.style.monochrome && get_time() - at < Assists::AUTOPLAY_STEP {
+            return;
+        }
+        self.step_autoplay(autoplay);
+    }
+    /// the next card `autoplay` would put on a foundation, if there's one on
+    /// screen
+    fn next_autoplay(&self, autoplay: Autoplay) -> Option<Move> {
+        self.layout
+            .visible_columns(self.camera)
+            .into_iter()
+            .filter(|column| *column < self.game.tableau().len())
+            .find_map(|from| {
+                let card = self.game.tableau()[from].visible().last()?;
+                let foundation = match autoplay {
+                    Autoplay::Chain(foundation) => {
+                        self.game.can_found(card, foundation).then_some(foundation)
+                    }
+                    Autoplay::Sweep => self.game.foundation_for(card),
+                }?;
+                Some(Move::Foundation { from, foundation })
+            })
+            .or_else(|| {
+                // the waste is always on screen
+                let card = *self.game.waste().last()?;
+                let foundation = match autoplay {
+                    Autoplay::Chain(foundation) => {
+                        self.game.can_found(card, foundation).then_some(foundation)
+                    }
+                    Autoplay::Sweep => self.game.foundation_for(card),
+                }?;
+                Some(Move::WasteFoundation { foundation })
+            })
+    }
+    /// makes the next move of an auto chain or sweep, or ends it if there's
+    /// nothing left for it to do
+    fn step_autoplay(&mut self, autoplay: Autoplay) {
+        if !self.grabbed_stack.is_empty() || self.is_locked() {
+            self.autoplay = None;
+            return;
+        }
+        // still going while the move is made, so it isn't put in a macro
+        if let Some(mv) = self.next_autoplay(autoplay) {
+            let made = self.moves.len();
+            self.finalize_column(mv);
+            if self.moves.len() > made {
+                self.autoplay = Some((autoplay, get_time()));
+                return;
+            }
+        }
+        self.autoplay = None;
+    }
+    /// makes the rest of an auto chain or sweep straight away, so nothing the
+    /// player does lands in the middle of one
+    fn finish_autoplay(&mut self) {
+        while let Some((autoplay, _)) = self.autoplay {
+            self.step_autoplay(autoplay);
+        }
+    }
+    /// starts putting every card on screen that can go on a foundation there,
+    /// one at a time
+    fn auto_finish(&mut self) {
+        if !self.grabbed_stack.is_empty() || self.is_locked() {
+            return;
+        }
+        self.finish_autoplay();
+        // it all goes back in one step, the rest follow as autoplay
+        self.group_undo();
+        // the first card goes straight away, the rest follow
+        self.step_autoplay(Autoplay::Sweep);
+        self.undo.ungroup(self.moves.len());
+    }
+    /// the button for `auto_finish`, while there's something for it to do
+    fn finish_button(&self) -> Option<Rect> {
+        if !self.grabbed_stack.is_empty()
+            || self.autoplay.is_some()
+            || self.next_autoplay(Autoplay::Sweep).is_none()
+        {
+            return None;
+        }
+        Some(Rect::new(
+            screen_width() - 128.0,
+            screen_height() - 84.0,
+            120.0,
+            24.0,
+        ))
+    }
+    /// what the mouse pointer looks like over the board
+    fn cursor(&self) -> Cursor {
+        if self.is_locked() {
+            return Cursor::Arrow;
+        }
+        if self.grabbed_stack.is_empty() {
+            if self.layout.foundations_cover_tableau() && self.is_mouse_on_foundation() {
+                return Cursor::Arrow;
+            }
+            if self.is_mouse_on_stock() {
+                let mouse = self.mouse();
+                let on_waste = self.layout.over_waste(mouse) && !self.game.waste().is_empty();
+                return if on_waste || self.layout.over_stock(mouse) {
+                    Cursor::Open
+                } else {
+                    Cursor::Arrow
+                };
+            }
+            let Some(stack) = self
+                .get_row_over_mouse()
+                .and_then(|column| self.game.tableau().get(column))
+            else {
+                return Cursor::Arrow;
+            };
+            let depth = self.layout.depth_at(self.camera, self.mouse());
+            // like picking up, below the pile grabs the top card
+            let over_cards =
+                depth >= self.layout.hidden_rows(stack.under) && !stack.is_visible_empty();
+            return if over_cards {
+                Cursor::Open
+            } else {
+                Cursor::Arrow
+            };
+        }
+        let fits = if self.from_waste {
+            if self.is_mouse_on_stock() {
+                // back on the waste just puts it back
+                true
+            } else if self.is_mouse_on_foundation() {
+                self.foundation_under_mouse()
+                    .is_some_and(|idx| self.game.can_found(self.grabbed_stack.top(), idx))
+            } else {
+                self.get_row_over_mouse()
+                    .is_some_and(|to| self.game.check(Move::Waste { to }).is_ok())
+            }
+        } else if self.is_mouse_on_foundation() && self.grabbed_stack.len() == 1 {
+            self.foundation_under_mouse()
+                .is_some_and(|idx| self.game.can_found(self.grabbed_stack.top(), idx))
+        } else if let Some(to) = self.get_row_over_mouse() {
+            // back where they came from is fine too, it just puts them back
+            let from = self.grabbed_stack_row;
+            matches!(
+                self.game.check_carried(from, &self.grabbed_stack, to),
+                Ok(()) | Err(MoveError::SameColumn)
+            ) || self
+                .game
+                .check_insert_carried(from, &self.grabbed_stack, to)
+                .is_ok()
+        } else {
+            false
+        };
+        if fits {
+            Cursor::Grabbing
+        } else {
+            Cursor::Invalid
+        }
+    }
+    /// the history of the card under the mouse, a line at a time
+    fn inspect(&mut self) -> Option<Vec<String>> {
+        if !self.grabbed_stack.is_empty() {
+            // the board is missing the carried cards until they're dropped
+            return None;
+        }
+        if self.is_mouse_on_stock() {
+            return None;
+        }
+        if self.is_mouse_on_foundation() {
+            return self
+                .history
+                .inspect_foundation(&self.game, self.foundation_under_mouse()?);
+        }
+        let column = self.get_row_over_mouse()?;
+        let stack = self.game.tableau().get(column)?;
+        let depth = self.layout.depth_at(self.camera, self.mouse());
+        let idx = depth.checked_sub(self.layout.hidden_rows(stack.under))?;
+        let len: usize = stack.visible().len().into();
+        if len == 0 {
+            return None;
+        }
+        // like picking up, below the pile means the top card
+        self.history.inspect(&self.game, column, idx.min(len - 1))
+    }
+    /// deals the column under the mouse a fresh card, if a reshuffle has
+    /// been earned
+    fn reshuffle(&mut self) {
+        if !self.grabbed_stack.is_empty() || self.is_locked() {
+            return;
+        }
+        if let Some(column) = self.get_row_over_mouse() {
+            self.finalize_column(Move::Reshuffle { column });
+        }
+    }
+    /// starts looking at whether anything more can be done on screen
+    fn analyse(&mut self) {
+        if self.analysis.is_some() {
+            toast::info("still looking");
+            return;
+        }
+        let visible = self.layout.visible_columns(self.camera);
+        let (Some(&first), Some(&last)) = (visible.iter().min(), visible.iter().max()) else {
+            return;
+        };
+        // carried cards aren't on the board, so look at it as if they were put back
+        let mut game = self.game.clone();
+        if !self.from_waste {
+            let mut carried = self.grabbed_stack.clone();
+            game.put_back(self.grabbed_stack_row, &mut carried);
+        }
+        self.analysis = Some(Analysis::start(&game, first..last + 1, self.moves.len()));
+        toast::info("looking for moves on screen...");
+    }
+    /// says what the analysis found once it's done, unless the board has
+    /// changed since
+    fn update_analysis(&mut self) {
+        let Some(analysis) = &self.analysis else {
+            return;
+        };
+        let Some(verdict) = analysis.poll() else {
+            return;
+        };
+        if analysis.moves == self.moves.len() {
+            toast::info(verdict.describe(&analysis.window));
+        }
+        self.analysis = None;
+    }
+    /// whether moves can be taken back. not in challenges or when taking
+    /// turns, where it would be cheating, or in streamed games, where
+    /// keeping the board to go back to means reading it all off disk.
+    fn can_undo(&self) -> bool {
+        self.challenge.is_none() && self.hot_seat.is_none() && self.game.world().is_none()
+    }
+    /// the board as it is, to come back to with `undo`
+    fn step(&self) -> Step {
+        Step {
+            board: self.game.snapshot(),
+            moves: self.moves.len(),
+            score: self.score,
+        }
+    }
+    /// puts the moves made from now until `Undo::ungroup` in one step
+    fn group_undo(&mut self) {
+        if self.can_undo() {
+            let step = self.step();
+            self.undo.group(step);
+        }
+    }
+    /// takes back the last move, and whatever the game did by itself
+    /// because of it
+    fn undo(&mut self) {
+        if !self.can_undo() {
+            toast::error("moves can't be taken back in this game");
+            return;
+        }
+        if !self.grabbed_stack.is_empty() {
+            self.reset_column();
+        }
+        // whatever was going on goes back with it
+        self.autoplay = None;
+        self.bot = None;
+        let Some(step) = self.undo.pop(self.moves.len()) else {
+            toast::error("nothing to take back");
+            return;
+        };
+        self.game.restore(&step.board);
+        self.moves.truncate(step.moves);
+        self.score = step.score;
+        self.confirm_bury = None;
+        self.positions.clear();
+        self.tweens.clear();
+        self.generate_new();
+        self.publish(&[]);
+    }
+    /// starts or stops the game playing itself
+    fn toggle_bot(&mut self) {
+        if self.bot.take().is_some() {
+            toast::info("autoplay off");
+        } else if !self.is_locked() {
+            // everything it does goes back in one step
+            if self.can_undo() {
+                let step = self.step();
+                self.undo.push(step);
+            }
+            self.bot = Some(Bot::new());
+            toast::info("autoplay on, p stops it");
+        }
+    }
+    /// starts recording a macro from the column under the mouse, or drops
+    /// the one being recorded
+    fn toggle_recording(&mut self) {
+        if self.macros.is_recording() {
+            self.macros.cancel();
+            toast::info("macro dropped");
+            return;
+        }
+        let Some(anchor) = self.get_row_over_mouse() else {
+            toast::error("point at a column to record a macro from");
+            return;
+        };
+        self.macros.start(anchor);
+        toast::info("recording a macro, F1 to F4 keeps it");
+    }
+    /// keeps the macro being recorded under key `slot`, or plays back the
+    /// one kept there from the column under the mouse, stopping at the
+    /// first move that isn't allowed
+    fn macro_key(&mut self, slot: usize) {
+        let key = macros::key_name(slot);
+        if self.macros.is_recording() {
+            let moves = self.macros.keep(slot);
+            toast::info(format!("kept {} moves under {}", moves, key));
+            return;
+        }
+        if !self.grabbed_stack.is_empty() || self.is_locked() {
+            return;
+        }
+        let actions = self.macros.get(slot).to_vec();
+        if actions.is_empty() {
+            toast::error(format!("no macro under {}, m records one", key));
+            return;
+        }
+        let Some(anchor) = self.get_row_over_mouse() else {
+            toast::error("point at a column to play the macro from");
+            return;
+        };
+        self.finish_autoplay();
+        // the whole macro goes back in one step
+        self.group_undo();
+        self.play_macro(&key, anchor, actions);
+        self.undo.ungroup(self.moves.len());
+    }
+    /// makes the moves of a macro from `anchor`, stopping at the first one
+    /// that isn't allowed
+    fn play_macro(&mut self, key: &str, anchor: usize, actions: Vec<macros::Action>) {
+        for (step, action) in actions.into_iter().enumerate() {
+            let mv = action
+                .resolve(&self.game, anchor)
+                .and_then(|mv| self.game.check(mv).map(|()| mv));
+            match mv {
+                Ok(mv) => {
+                    self.finalize_column(mv);
+                    // a chain started by the move finishes before the next
+                    self.finish_autoplay();
+                }
+                Err(e) => {
+                    toast::error(format!(
+                        "{} stopped at move {}, {}: {}",
+                        key,
+                        step + 1,
+                        action,
+                        e
+                    ));
+                    return;
+                }
+            }
+        }
+    }
+    /// makes the bot's next move once it's time, or moves along the board
+    /// when there's nothing left for it to do on screen
+    fn update_bot(&mut self) {
+        if self.is_locked() {
+            self.bot = None;
+        }
+        let Some(bot) = &mut self.bot else {
+            return;
+        };
+        // wait for the player to put down whatever they're holding
+        if !bot.is_due() || !self.grabbed_stack.is_empty() || self.autoplay.is_some() {
+            return;
+        }
+        let visible = self.layout.visible_columns(self.camera);
+        let (Some(&first), Some(&last)) = (visible.iter().min(), visible.iter().max()) else {
+            return;
+        };
+        match bot.next_move(&self.game, first..last + 1) {
+            Some(mv) => self.finalize_column(mv),
+            None => {
+                let screen = vec2(screen_width(), screen_height());
+                let along = self.layout.main_axis();
+                self.pan(-along * screen.dot(along) / 2.0);
+                self.publish(&[]);
+            }
+        }
+    }
+    /// lights up the next move on screen, or where the carried cards can go
+    fn hint(&mut self) {
+        if self.is_locked() {
+            return;
+        }
+        let made = self.moves.len();
+        let visible = self.layout.visible_columns(self.camera);
+        if self.grabbed_stack.is_empty() {
+            let (Some(&first), Some(&last)) = (visible.iter().min(), visible.iter().max()) else {
+                return;
+            };
+            let moves = self
+                .game
+                .legal_moves(first..last + 1)
+                .into_iter()
+                .filter(|mv| Self::is_move_on_screen(&visible, *mv))
+                .filter(|mv| match *mv {
+                    // a whole column moving over for nothing isn't much of a hint
+                    Move::Tableau { from, idx: 0, .. } => self.game.tableau()[from].under > 0,
+                    _ => true,
+                })
+                .collect();
+            self.hints.next_move(&self.game, moves, made);
+            if self.hints.is_empty() {
+                toast::info("no moves on screen");
+            }
+            return;
+        }
+        let from = self.grabbed_stack_row;
+        let mut spots: Vec<Spot> = visible
+            .into_iter()
+            .filter(|&to| to < self.game.tableau().len())
+            .filter(|&to| {
+                if self.from_waste {
+                    self.game.check(Move::Waste { to }).is_ok()
+                } else {
+                    to != from
+                        && (self
+                            .game
+                            .check_carried(from, &self.grabbed_stack, to)
+                            .is_ok()
+                            || self
+                                .game
+                                .check_insert_carried(from, &self.grabbed_stack, to)
+                                .is_ok())
+                }
+            })
+            .map(Spot::End)
+            .collect();
+        if self.grabbed_stack.len() == 1 {
+            let foundation = self.game.foundation_for(self.grabbed_stack.top());
+            spots.extend(foundation.map(Spot::Foundation));
+        }
+        self.hints.places(spots, made);
+        if self.hints.is_empty() {
+            toast::info("those cards can't go anywhere on screen");
+        }
+    }
+    /// whether both ends of `mv` are among the `visible` columns
+    fn is_move_on_screen(visible: &[usize], mv: Move) -> bool {
+        match mv {
+            Move::Tableau { from, to, .. } | Move::Insert { from, at: to, .. } => {
+                visible.contains(&from) && visible.contains(&to)
+            }
+            Move::Foundation { from, .. } => visible.contains(&from),
+            Move::Reshuffle { column } | Move::Waste { to: column } => visible.contains(&column),
+            Move::Draw | Move::WasteFoundation { .. } => true,
+        }
+    }
+    /// turns the next card of the stock over, in a game with one
+    fn turn_stock(&mut self) {
+        if !self.grabbed_stack.is_empty() || self.is_locked() || !self.game.rules().stock {
+            return;
+        }
+        self.finish_autoplay();
+        self.finalize_column(Move::Draw);
+    }
+    /// makes every move on screen that's the only useful one its column has,
+    /// until there aren't any left. each one puts a card on a foundation or
+    /// turns one over, so this always runs out.
+    fn auto_arrange(&mut self) {
+        if !self.grabbed_stack.is_empty() || self.is_locked() {
+            return;
+        }
+        let visible = self.layout.visible_columns(self.camera);
+        let (Some(&first), Some(&last)) = (visible.iter().min(), visible.iter().max()) else {
+            return;
+        };
+        let on_screen = |mv: &Move| Self::is_move_on_screen(&visible, *mv);
+        while let Some(mv) = solver::forced_moves(&self.game, first..last + 1)
+            .into_iter()
+            .find(on_screen)
+        {
+            let made = self.moves.len();
+            self.finalize_column(mv);
+            if self.moves.len() == made {
+                // can't happen for a legal move, but don't spin on it
+                break;
+            }
+        }
+    }
+    /// changes the board in a sandbox game
+    fn edit(&mut self, edit: Edit) {
+        match edit {
+            Edit::Spawn(card) => {
+                if let Some(column) = self.get_row_over_mouse() {
+                    if self.grabbed_stack.is_empty() && self.game.place(column, card) {
+                        self.publish(&[]);
+                    }
+                }
+            }
+            Edit::Delete => {
+                if self.from_waste {
+                    // the waste isn't part of any column, it just goes back
+                    self.reset_column();
+                } else if !self.grabbed_stack.is_empty() {
+                    // the carried cards go back only to be thrown away
+                    let carried = self.grabbed_stack.len();
+                    self.reset_column();
+                    for _ in 0..carried {
+                        self.game.remove(self.grabbed_stack_row);
+                    }
+                } else if let Some(column) = self.get_row_over_mouse() {
+                    self.game.remove(column);
+                }
+                self.publish(&[]);
+            }
+        }
+    }
+    fn generate_new(&mut self) {
+        let first = self.layout.first_column(self.camera);
+        let needed = self.layout.columns_needed(self.camera);
+        self.game.ensure_range(first..needed);
+        // carried cards have to be able to go back where they came from
+        if self.grabbed_stack.is_empty() {
+            let spare = if self.low_memory {
+                Self::SPARE_COLUMNS
+            } else {
+                Self::KEPT_COLUMNS
+            };
+            self.game
+                .forget_outside(first.saturating_sub(spare)..needed + spare);
+        }
+    }
+
+    /// streams the board to a directory of its own under `root` from now
+    /// on, named after the seed
+    fn stream_under(&mut self, root: PathBuf) {
+        let seed = self.game.seed();
+        let dir = (0..)
+            .map(|n| root.join(format!("{}-{}", seed, n)))
+            .find(|dir| !dir.exists())
+            .unwrap();
+        match World::open(&dir) {
+            Ok(world) => self.game.stream_to(world),
+            Err(e) => toast::error(format!("could not stream to {}: {}", dir.display(), e)),
+        }
+        self.stream = Some(root);
+    }
+
+    fn save_game(&mut self, path: &str, format: SaveFormat) {
+        match self.write_save(path, format) {
+            Ok(()) => toast::info(format!("saved to {}", path)),
+            Err(e) => toast::error(format!("could not save to {}: {}", path, e)),
+        }
+    }
+    /// saves without saying so, for autosaves
+    fn write_save(&mut self, path: &str, format: SaveFormat) -> std::io::Result<()> {
+        // carried cards aren't part of the board
+        self.reset_column();
+        self.finish_autoplay();
+        // chunks that can't be written go in the save instead
+        let _ = self.game.flush();
+        let save = SaveData {
+            game: self.game.clone(),
+            camera: self.camera.into(),
+            moves: self.moves.clone(),
+            played: Some(self.session.elapsed()),
+            idle_pauses: self.session.idle_pauses(),
+            bookmarks: self.bookmarks.to_save(),
+        };
+        save.save(path, format)
+    }
+
+    fn load_game(&mut self, path: &str) {
+        match SaveData::load(path) {
+            Ok(save) => {
+                self.grabbed_stack = CardStack::empty();
+                self.from_waste = false;
+                self.game = save.game;
+                self.undo.clear();
+                // grid games can only be shown on a grid and the other way around
+                let grid = self.game.rules().topology == Topology::Grid;
+                if grid != (self.layout.orientation == Orientation::Grid) {
+                    self.layout.orientation = if grid {
+                        Orientation::Grid
+                    } else {
+                        Orientation::Horizontal
+                    };
+                }
+                self.camera = save.camera.into();
+                self.view = self.layout.screen_size();
+                self.tweens.clear();
+                self.bookmarks = Bookmarks::from_save(save.bookmarks);
+                self.moves = save.moves;
+                self.history = History::new(self.low_memory);
+                // whatever was loaded isn't the challenge any more
+                self.challenge = None;
+                // and the players start their tally over on it
+                self.hot_seat = self
+                    .hot_seat
+                    .as_ref()
+                    .map(|seat| HotSeat::new(seat.turn_length()));
+                self.autoplay = None;
+                self.positions.clear();
+                // saves don't keep events, so it's worked out from the board
+                self.score = self.scoring.score(&self.game);
+                self.bests = Bests::new(&self.game);
+                if let Some(played) = save.played {
+                    self.session.resume(played, save.idle_pauses);
+                }
+                if let (None, Some(root)) = (self.game.world(), self.stream.clone()) {
+                    self.stream_under(root);
+                }
+                self.generate_new();
+                self.publish(&[]);
+            }
+            Err(e) => toast::error(format!("could not load {}: {}", path, e)),
+        }
+    }
+
+    /// writes out a replay of everything played so far
+    fn save_replay(&self, path: &str) {
+        if self.game.rules().sandbox || self.game.rules().decay {
+            // cards made up, thrown away or turned back over aren't moves,
+            // so it couldn't be played back anyway
+            return;
+        }
+        if let Err(e) = Replay::record(&self.game, &self.moves).save(path) {
+            toast::error(format!("could not save replay: {}", e));
+        }
+    }
+}
+
+/// the keys 1 to 9, in order
+const NUMBER_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+fn ctrl_down() -> bool {
+    is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
+}
+
+fn shift_down() -> bool {
+    is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+}
+
+fn alt_down() -> bool {
+    is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt)
+}
+
+fn draw_card_outline(atlas: Atlas, x: f32, y: f32) {
+    draw_texture_box(atlas, x, y, WHITE, atlas.sprites.outline())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+    let options = cli::options(&args);
+    macroquad::Window::from_config(window_conf(), play(options));
+}
+
+async fn play(options: Options) {
+    let mut loading = Loading::new(if options.serve.is_some() { 4 } else { 3 });
+    loading.step("cards").await;
+    let atlas = Atlas::load(&options.pack).await;
+    loading.step("sounds").await;
+    if let Err(e) = audio::load().await {
+        toast::error(e);
+    }
+    for e in &options.settings_errors {
+        toast::error(e.as_str());
+    }
+    prevent_quit();
+    // `Cursor` is drawn instead
+    show_mouse(false);
+    let mut settings = options.settings;
+    // the command line might have already said what to play
+    let start =
+        if settings.seed.is_some() || settings.challenge.is_some() || options.script.is_some() {
+            Start::New
+        } else {
+            title::run(&mut settings, autosave::exists()).await
+        };
+    if let Start::Quit = start {
+        return;
+    }
+    audio::set_volume(settings.volume);
+    audio::set_music_volume(settings.music);
+    loading.step("the deal").await;
+    // seeds for new games, from a fixed start when asked for so a session
+    // can be played again exactly
+    let mut seeds: Box<dyn RngCore> = match options.seeds_from {
+        Some(start) => Box::new(ChaCha8Rng::seed_from_u64(start)),
+        None => Box::new(::rand::thread_rng()),
+    };
+    let mut state = State::new(settings, &mut *seeds);
+    if let Some(root) = &options.stream {
+        state.stream_under(root.clone());
+    }
+    if let Some(addr) = &options.serve {
+        loading.step("the server").await;
+        match Server::start(addr) {
+            Ok(server) => {
+                toast::info(format!("serving the board on http://{}", addr));
+                state.server = Some(server);
+                state.publish(&[]);
+            }
+            Err(e) => toast::error(format!("could not serve on {}: {}", addr, e)),
+        }
+    }
+    let mut tabs = Tabs::new(state, seeds);
+    if let Start::Continue = start {
+        autosave::continue_games(&mut tabs);
+    }
+    let mut old_pos = mouse_position();
+    // scrolling by dragging the background with the left button (or a finger)
+    let mut dragging = false;
+    // scrolling with the keys or the window edges, to tell watchers once it stops
+    let mut scrolling = false;
+    // to spot double clicks
+    let mut gestures = Gestures::default();
+    let keep = options.autosave_keep.unwrap_or(Autosave::KEEP);
+    let mut autosaves = Autosave::new(options.autosave_every.unwrap_or(Autosave::EVERY), keep);
+    let mut screen = Screen::Playing;
+    let mut console = Console::default();
+    let mut show_stats = false;
+    let mut show_heatmap = false;
+    let mut show_minimap = false;
+    // the ring of actions, while q is held
+    let mut ring: Option<Radial<RingAction>> = None;
+    let mut power = options.power_save.then(Power::new);
+    if let Some(script) = options.script {
+        input::play(script);
+    }
+    loop {
+        input::update();
+        fullscreen::update();
+        if input::first_touch() {
+            // dragging cards around under a finger hides where they'd go
+            for (_, state) in tabs.iter_mut() {
+                state.grab = Grab::Click;
+            }
+            toast::info("tap cards to pick them up and tap again to put them down");
+        }
+        tabs.current().fit_window();
+        if let Some(region) = tabs.current().middle_region() {
+            audio::update_music(region);
+        }
+        let now = get_time();
+        let events = input::Window.poll(now);
+        let double = events
+            .into_iter()
+            .filter_map(|event| gestures.feed(event, now))
+            .any(|gesture| matches!(gesture, Gesture::DoubleClick { .. }));
+        if !screen.is_playing() {
+            // the clock stops while the game is paused
+            tabs.current().session.pause();
+        } else {
+            tabs.current().session.tick(input::had_input());
+        }
+        let mut quit = is_quit_requested();
+        let quicksave = tab_path(
+            QUICKSAVE_PATH,
+            tabs.current().id,
+            options.save_format.extension(),
+        );
+        let console_open =
+            screen.is_playing() && !tabs.current().is_typing() && console.update(tabs.current());
+        let was_playing = screen.is_playing();
+        if let Screen::Prompt(asking, open) = &mut screen {
+            let asking = *asking;
+            match open.update() {
+                Some(Answer::Typed(line)) => {
+                    screen = Screen::Playing;
+                    match asking {
+                        Asking::Seed => deal_seed(&mut tabs, &line),
+                        Asking::Column => go_to_column(tabs.current(), &line),
+                    }
+                }
+                Some(Answer::Cancelled) => screen = Screen::Playing,
+                None => {}
+            }
+        } else if let Some(open) = screen.menu() {
+            let picked = open.update();
+            if is_key_pressed(KeyCode::Escape) {
+                screen = screen.back();
+            } else if let Some(action) = picked {
+                screen = Screen::Playing;
+                match action {
+                    MenuAction::Resume => {}
+                    MenuAction::Back => screen = Screen::Paused(pause_menu()),
+                    MenuAction::Save => tabs.current().save_game(&quicksave, options.save_format),
+                    MenuAction::Load => tabs.current().load_game(&quicksave),
+                    MenuAction::NewTab => {
+                        tabs.open();
+                    }
+                    MenuAction::CloseTab => close_tab(&mut tabs),
+                    MenuAction::Quit => quit = true,
+                    // only on the title screen
+                    MenuAction::Continue | MenuAction::Challenge(_) => {}
+                    MenuAction::Autosaves => {
+                        if let Some(menu) = autosaves_menu(tabs.current().id, keep) {
+                            screen = Screen::Paused(menu);
+                        }
+                    }
+                    MenuAction::Restore(n) => {
+                        let path = autosave::kept_path(tabs.current().id, n);
+                        tabs.current().load_game(&path);
+                    }
+                    MenuAction::EnterSeed => {
+                        let note = format!(
+                            "this game's seed is {}, empty for a random one",
+                            tabs.current().game.seed()
+                        );
+                        screen = Screen::Prompt(Asking::Seed, Prompt::new("seed", &note));
+                    }
+                    MenuAction::Options => screen = Screen::Settings(options::menu()),
+                    MenuAction::OptionsPage(page) => {
+                        let settings = tabs.current().settings();
+                        screen = Screen::Settings(options::page_menu(page, &settings, None));
+                    }
+                    MenuAction::Change(page, choice) => {
+                        let mut settings = tabs.current().settings();
+                        choice.change(&mut settings);
+                        for (_, state) in tabs.iter_mut() {
+                            state.set_options(settings);
+                        }
+                        if let Err(e) = config::save(&settings) {
+                            toast::error(format!("could not save settings: {}", e));
+                        }
+                        let menu = options::page_menu(page, &settings, Some(choice));
+                        screen = Screen::Settings(menu);
+                    }
+                    MenuAction::Region(region) => {
+                        let state = tabs.current();
+                        let column = worldmap::first_column(&state.game, region);
+                        state.jump_to(column);
+                    }
+                    MenuAction::Bury => {
+                        let state = tabs.current();
+                        if let Some((mv, _)) = state.confirm_bury.take() {
+                            state.finalize_column(mv);
+                        }
+                    }
+                }
+            }
+            if screen.is_playing() && tabs.current().confirm_bury.take().is_some() {
+                // backing out of a move puts the cards back
+                tabs.current().reset_column();
+            }
+        } else if let Screen::WorldMap = screen {
+            let closed = is_key_pressed(KeyCode::Escape)
+                || (shift_down() && is_key_pressed(KeyCode::N))
+                || (is_mouse_button_pressed(MouseButton::Left) && tabs.current().world_map_click());
+            if closed {
+                screen = Screen::Playing;
+            }
+        } else if is_key_pressed(KeyCode::Escape) && !console_open && !tabs.current().is_typing() {
+            let state = tabs.current();
+            if state.grabbed_stack.is_empty() {
+                screen = Screen::Paused(pause_menu());
+            } else {
+                // the first escape just lets go of the cards
+                state.reset_column();
+            }
+        }
+        if quit {
+            for (_, state) in tabs.iter() {
+                state.save_replay(&tab_path(REPLAY_PATH, state.id, "replay"));
+            }
+            autosave::save_all(&mut tabs);
+            stats::save_totals();
+            break;
+        }
+
+        let mut clicked_tabs = false;
+        if was_playing && !console_open {
+            if ctrl_down() && is_key_pressed(KeyCode::W) {
+                close_tab(&mut tabs);
+            }
+            if ctrl_down() && is_key_pressed(KeyCode::Z) && !tabs.current().is_typing() {
+                tabs.current().undo();
+            }
+            clicked_tabs = !tabs.current().is_typing() && tabs.update();
+        }
+        clear_background(tabs.current().style.table_color());
+
+        tabs.current().draw(atlas);
+        if show_heatmap {
+            tabs.current().draw_heatmap();
+        }
+        if magnifier::is_held() && was_playing && !console_open && !tabs.current().is_typing() {
+            tabs.current().draw_magnified(atlas);
+        }
+        tabs.current().bookmarks.draw();
+        if show_minimap {
+            let bottom = tabs.height();
+            tabs.current().draw_minimap(bottom);
+        }
+        if alt_down() && screen.is_playing() {
+            if let Some(lines) = tabs.current().inspect() {
+                history::draw(&lines, mouse_position());
+            }
+        }
+        if show_stats {
+            let rules = tabs.current().game.rules();
+            tabs.current().session.draw(rules);
+        }
+        if let Some(run) = &tabs.current().challenge {
+            run.draw();
+        }
+        let state = tabs.current();
+        if let Some(seat) = &state.hot_seat {
+            seat.draw(if state.challenge.is_some() { 28.0 } else { 0.0 });
+        }
+        if tabs.current().game.rules().sandbox {
+            tabs.current().sandbox.draw();
+        }
+        tabs.draw();
+        if let Screen::WorldMap = screen {
+            let state = tabs.current();
+            worldmap::draw(&state.world_map(), state.style);
+        }
+        screen.draw();
+        if let Some(ring) = &ring {
+            ring.draw();
+        }
+        console.draw();
+        toast::draw();
+        let cursor = if !was_playing || console_open {
+            Cursor::Arrow
+        } else {
+            tabs.current().cursor()
+        };
+        cursor.draw(tabs.current().style);
+        if !was_playing || console_open {
+            input::set_precision(None);
+            if let Some(power) = &mut power {
+                power.rest();
+            }
+            next_frame().await;
+            continue;
+        }
+        let state = tabs.current();
+        // not while cards are carried, saving would put them back
+        if autosaves.is_due() && state.grabbed_stack.is_empty() && state.autoplay.is_none() {
+            autosaves.save(&mut tabs);
+            stats::save_totals();
+        }
+        let map_bottom = tabs.height();
+        let state = tabs.current();
+        input::set_precision(state.precision());
+        state.update_challenge();
+        state.update_hot_seat();
+        state.update_decay();
+        state.update_autoplay();
+        state.update_bot();
+        state.update_analysis();
+        let first_column = state.layout.first_column(state.camera);
+        if state.game.rules().sandbox && !state.bookmarks.is_naming() {
+            if let Some(edit) = state.sandbox.update() {
+                state.edit(edit);
+            }
+        }
+        // with one card carried, 1 to 8 send it to that foundation instead
+        // of jumping to a bookmark
+        let founded = !ctrl_down()
+            && !state.sandbox.is_typing()
+            && !state.bookmarks.is_naming()
+            && NUMBER_KEYS[..8]
+                .iter()
+                .position(|key| is_key_pressed(*key))
+                .is_some_and(|slot| state.found_to_slot(slot));
+        if !state.sandbox.is_typing() && !founded {
+            if let Some(camera) = state.bookmarks.update(state.camera, first_column) {
+                state.camera = camera;
+                state.generate_new();
+                state.publish(&[]);
+            }
+        }
+
+        //draw_line(40.0, 40.0, 100.0, 200.0, 15.0, BLUE);
+        //draw_rectangle(screen_width() / 2.0 - 60.0, 100.0, 120.0, 60.0, GREEN);
+        //draw_circle(screen_width() - 30.0, screen_height() - 30.0, 15.0, YELLOW);
+        //draw_text("HELLO", 20.0, 20.0, 20.0, DARKGRAY);
+        if is_mouse_button_pressed(MouseButton::Left) && !clicked_tabs {
+            let pos = Vec2::from(mouse_position());
+            let on_map = show_minimap
+                .then(|| minimap::column_at(state.game.tableau().len(), map_bottom))
+                .flatten();
+            if state.finish_button().is_some_and(|rect| rect.contains(pos)) {
+                state.auto_finish();
+            } else if let Some(column) = on_map {
+                // the column clicked on ends up in the middle of the screen
+                let shown = state.layout.visible_columns(state.camera).len();
+                state.jump_to(column.saturating_sub(shown / 2));
+            } else if (state.layout.drag_to_scroll() || input::is_touch())
+                && state.grabbed_stack.is_empty()
+                && state.is_mouse_on_background()
+            {
+                dragging = true;
+            } else if double {
+                state.found_under_mouse();
+            } else {
+                state.on_press();
+            }
+        } else if is_mouse_button_released(MouseButton::Left) && !dragging {
+            state.on_release();
+        }
+        if !state.is_typing() && !ctrl_down() {
+            if is_key_pressed(KeyCode::I) {
+                show_stats = !show_stats;
+            }
+            if is_key_pressed(KeyCode::H) {
+                if shift_down() {
+                    state.hint();
+                } else {
+                    show_heatmap = !show_heatmap;
+                }
+            }
+            if is_key_pressed(KeyCode::R) {
+                state.reshuffle();
+            }
+            if is_key_pressed(KeyCode::F) {
+                state.auto_finish();
+            }
+            if is_key_pressed(KeyCode::D) {
+                state.turn_stock();
+            }
+            if is_key_pressed(KeyCode::P) {
+                state.toggle_bot();
+            }
+            if is_key_pressed(KeyCode::Q) {
+                ring = Some(action_ring());
+            }
+            if is_key_pressed(KeyCode::G) {
+                screen = Screen::Prompt(Asking::Column, column_prompt(state));
+            }
+            if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+                state.zoom(true);
+            }
+            if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+                state.zoom(false);
+            }
+            if is_key_pressed(KeyCode::N) {
+                if state.game.rules().topology == Topology::Grid {
+                    toast::error("the map only works for boards on a line");
+                } else if shift_down() {
+                    screen = Screen::WorldMap;
+                } else {
+                    show_minimap = !show_minimap;
+                }
+            }
+            if is_key_pressed(KeyCode::E) {
+                state.analyse();
+            }
+            if is_key_pressed(KeyCode::M) {
+                if shift_down() {
+                    if audio::toggle_mute() {
+                        toast::info("sound off, shift+m turns it back on");
+                    } else {
+                        toast::info("sound on");
+                    }
+                } else {
+                    state.toggle_recording();
+                }
+            }
+            if let Some(slot) = macros::key_pressed() {
+                state.macro_key(slot);
+            }
+            if state.assists.auto_arrange && is_key_pressed(KeyCode::A) {
+                state.auto_arrange();
+            }
+        }
+        if let Some(open) = &ring {
+            if !is_key_down(KeyCode::Q) {
+                let picked = open.picked();
+                ring = None;
+                match picked {
+                    Some(RingAction::Hint) => state.hint(),
+                    Some(RingAction::AutoFinish) => state.auto_finish(),
+                    Some(RingAction::Analyse) => state.analyse(),
+                    Some(RingAction::Autoplay) => state.toggle_bot(),
+                    Some(RingAction::GoTo) => {
+                        screen = Screen::Prompt(Asking::Column, column_prompt(state));
+                    }
+                    Some(RingAction::Menu) => {
+                        state.reset_column();
+                        screen = Screen::Paused(pause_menu());
+                    }
+                    None => {}
+                }
+            }
+        }
+        if let Some((_, card)) = state.confirm_bury {
+            screen = Screen::Paused(assist::bury_menu(card));
+        }
+        if let Some(region) = state.cleared.take() {
+            screen = Screen::WonRegion(region_menu(region));
+        }
+        if is_key_pressed(KeyCode::F5) {
+            state.save_game(&quicksave, options.save_format);
+        }
+        if is_key_pressed(KeyCode::F9) {
+            state.load_game(&quicksave);
+        }
+        if is_mouse_button_down(MouseButton::Right) || dragging {
+            if is_mouse_button_pressed(MouseButton::Right)
+                || is_mouse_button_pressed(MouseButton::Left)
+            {
+                old_pos = mouse_position();
+            }
+            let new_pos = mouse_position();
+            let dx = new_pos.0 - old_pos.0;
+            let dy = new_pos.1 - old_pos.1;
+            let flip = if state.layout.invert_pan { -1.0 } else { 1.0 };
+            state.pan(vec2(dx, dy) * flip);
+            old_pos = new_pos;
+        }
+        let scrolled = state.scroll();
+        if scrolling && !scrolled {
+            state.publish(&[]);
+        }
+        scrolling = scrolled;
+        if dragging && !is_mouse_button_down(MouseButton::Left) {
+            dragging = false;
+            state.publish(&[]);
+        }
+        if is_mouse_button_released(MouseButton::Right) {
+            // new columns might have come into view
+            state.publish(&[]);
+        }
+        if let Some(power) = &mut power {
+            power.rest();
+        }
+        next_frame().await
+    }
+}
+
+#[derive(Clone, Copy)]
+enum MenuAction {
+    Resume,
+    /// back to the menu this one came from: the pause menu, or the title
+    /// screen before the game's started
+    Back,
+    Save,
+    Load,
+    NewTab,
+    CloseTab,
+    Quit,
+    /// go ahead with a move that buries a card
+    Bury,
+    /// load the games that were open last time, from the title screen
+    Continue,
+    /// ask for a seed to deal a new game from
+    EnterSeed,
+    /// play a challenge, from the title screen
+    Challenge(Period),
+    /// pick an old autosave to go back to
+    Autosaves,
+    /// go back to the `n`th newest autosave of this tab
+    Restore(usize),
+    /// the options screen
+    Options,
+    /// a page of the options screen
+    OptionsPage(Page),
+    /// change a setting from the options screen
+    Change(Page, Choice),
+    /// go to the start of a region
+    Region(usize),
+}
+
+/// what the ring of actions can do
+#[derive(Clone, Copy)]
+enum RingAction {
+    Hint,
+    AutoFinish,
+    Analyse,
+    Autoplay,
+    GoTo,
+    Menu,
+}
+
+/// the ring q holds open, for the things that otherwise need keys to
+/// remember
+fn action_ring() -> Radial<RingAction> {
+    Radial::new(&[
+        ("hint", RingAction::Hint),
+        ("auto finish", RingAction::AutoFinish),
+        ("is it dead?", RingAction::Analyse),
+        ("autoplay", RingAction::Autoplay),
+        ("go to column", RingAction::GoTo),
+        ("menu", RingAction::Menu),
+    ])
+}
+
+/// the menu escape brings up
+fn pause_menu() -> Menu<MenuAction> {
+    Menu::new(
+        "paused",
+        &[
+            ("resume", MenuAction::Resume),
+            ("quicksave", MenuAction::Save),
+            ("quickload", MenuAction::Load),
+            ("new tab", MenuAction::NewTab),
+            ("new game from seed", MenuAction::EnterSeed),
+            ("restore autosave", MenuAction::Autosaves),
+            ("options", MenuAction::Options),
+            ("close tab", MenuAction::CloseTab),
+            ("quit", MenuAction::Quit),
+        ],
+    )
+}
+
+/// the autosaves kept of the tab with `id` to go back to, newest first.
+/// `None` with a message if there aren't any yet.
+fn autosaves_menu(id: usize, keep: usize) -> Option<Menu<MenuAction>> {
+    let kept = autosave::kept(id, keep);
+    if kept.is_empty() {
+        toast::error("no autosaves to go back to yet");
+        return None;
+    }
+    let items: Vec<(String, MenuAction)> = kept
+        .into_iter()
+        .map(|(n, ago)| (format!("saved {}", ago), MenuAction::Restore(n)))
+        .chain([("back".to_owned(), MenuAction::Resume)])
+        .collect();
+    let items: Vec<(&str, MenuAction)> = items
+        .iter()
+        .map(|(label, action)| (label.as_str(), *action))
+        .collect();
+    Some(Menu::new("restore autosave", &items))
+}
+
+/// says well done for clearing `region`
+fn region_menu(region: usize) -> Menu<MenuAction> {
+    Menu::new(
+        &format!("region {} cleared!", region + 1),
+        &[
+            ("keep playing", MenuAction::Resume),
+            ("go to the next region", MenuAction::Region(region + 1)),
+        ],
+    )
+}
+
+/// what a `Prompt` is asking for
+#[derive(Clone, Copy)]
+enum Asking {
+    /// a seed to deal a new game from
+    Seed,
+    /// a column to go to
+    Column,
+}
+
+/// asks which column to go to
+fn column_prompt(state: &State) -> Prompt {
+    let note = format!(
+        "now at column {}, {} dealt so far",
+        state.layout.first_column(state.camera),
+        state.game.tableau().len()
+    );
+    Prompt::new("go to column", &note)
+}
+
+/// moves the camera to the column typed in
+fn go_to_column(state: &mut State, line: &str) {
+    match line.parse() {
+        Ok(column) => {
+            state.jump_to(column);
+            toast::info(format!("went to column {}", column));
+        }
+        Err(_) => toast::error(format!(
+            "`{}` isn't a column, columns are whole numbers",
+            line
+        )),
+    }
+}
+
+/// replaces the current game with one dealt from the seed typed in, keeping
+/// a replay of the old one
+fn deal_seed(tabs: &mut Tabs, line: &str) {
+    let seed = if line.is_empty() {
+        tabs.random_seed()
+    } else {
+        match line.parse() {
+            Ok(seed) => seed,
+            Err(_) => {
+                toast::error(format!("`{}` isn't a seed, seeds are whole numbers", line));
+                return;
+            }
+        }
+    };
+    let old = tabs.deal(seed);
+    old.save_replay(&tab_path(REPLAY_PATH, old.id, "replay"));
+    toast::info(format!("dealt seed {}", seed));
+}
+
+/// closes the current tab, keeping a replay of its game
+fn close_tab(tabs: &mut Tabs) {
+    if let Some(closed) = tabs.close() {
+        closed.save_replay(&tab_path(REPLAY_PATH, closed.id, "replay"));
     }
 }
@@ -0,0 +1,112 @@
+//! a map of the board so far. n shows a strip along the bottom of the window
+//! with a tick for every column that's been dealt: taller for more cards,
+//! bluer for more of them face down, green once it's done and a stub once
+//! it's been emptied. the part on screen is boxed, and clicking anywhere on
+//! the strip jumps there.
+
+use std::ops::Range;
+
+use infiniteklondike::game::Game;
+use macroquad::prelude::*;
+
+use crate::input::mouse_position;
+use crate::style::Style;
+
+/// how tall the strip is, in window pixels
+const HEIGHT: f32 = 28.0;
+/// how many cards make a full height tick
+const TALLEST: f32 = 20.0;
+
+/// where the strip goes, with `bottom` pixels left free under it
+fn strip(bottom: f32) -> Rect {
+    Rect::new(
+        0.0,
+        screen_height() - bottom - HEIGHT,
+        screen_width(),
+        HEIGHT,
+    )
+}
+
+/// the columns each pixel wide slice of the strip covers, when there are
+/// more columns than pixels
+fn slice(columns: usize, x: usize, width: usize) -> Range<usize> {
+    x * columns / width..((x + 1) * columns / width).max(x * columns / width + 1)
+}
+
+/// the column under the mouse, if it's on the strip. `columns` is how many
+/// there are.
+pub fn column_at(columns: usize, bottom: f32) -> Option<usize> {
+    let strip = strip(bottom);
+    let mouse = Vec2::from(mouse_position());
+    if columns == 0 || !strip.contains(mouse) {
+        return None;
+    }
+    let column = ((mouse.x - strip.x) / strip.w * columns as f32) as usize;
+    Some(column.min(columns - 1))
+}
+
+/// draws the strip for the columns of `game`, boxing the ones in `visible`
+pub fn draw(game: &Game, visible: Range<usize>, bottom: f32, style: Style) {
+    let strip = strip(bottom);
+    draw_rectangle(strip.x, strip.y, strip.w, strip.h, style.shade(0.85));
+    let columns = game.columns();
+    if columns == 0 {
+        return;
+    }
+    // one tick per column, or per pixel when they don't fit
+    let ticks = columns.min(strip.w as usize).max(1);
+    let width = strip.w / ticks as f32;
+    for tick in 0..ticks {
+        // face down and face up counts, without dealing the columns that
+        // haven't been
+        let counts: Vec<(f32, f32)> = slice(columns, tick, ticks)
+            .map(|x| {
+                let (under, up) = game.column_counts(x);
+                (under as f32, up as f32)
+            })
+            .collect();
+        let tallest = counts
+            .iter()
+            .map(|(under, up)| under + up)
+            .fold(0.0, f32::max);
+        let hidden = counts
+            .iter()
+            .map(|(under, up)| under / (under + up).max(1.0))
+            .fold(0.0, f32::max);
+        let finished = counts.iter().all(|(under, up)| under + up == 0.0);
+        let (height, color) = if tallest == 0.0 {
+            (2.0, GRAY)
+        } else if finished {
+            (HEIGHT - 4.0, if style.monochrome { WHITE } else { GREEN })
+        } else {
+            let height = (tallest / TALLEST).min(1.0) * (HEIGHT - 4.0);
+            let color = if style.monochrome {
+                WHITE
+            } else {
+                Color::new(1.0 - 0.7 * hidden, 1.0 - 0.5 * hidden, 1.0, 1.0)
+            };
+            (height.max(2.0), color)
+        };
+        let x = strip.x + tick as f32 * width;
+        draw_rectangle(
+            x,
+            strip.bottom() - 2.0 - height,
+            (width - 1.0).max(1.0),
+            height,
+            color,
+        );
+    }
+    if visible.is_empty() {
+        return;
+    }
+    let start = strip.x + visible.start as f32 / columns as f32 * strip.w;
+    let end = strip.x + visible.end.min(columns) as f32 / columns as f32 * strip.w;
+    draw_rectangle_lines(
+        start,
+        strip.y,
+        (end - start).max(2.0),
+        strip.h,
+        2.0,
+        if style.monochrome { WHITE } else { YELLOW },
+    );
+}
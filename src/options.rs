@@ -0,0 +1,267 @@
+//! the options screen, from the pause menu. it's a page of buttons for each
+//! part of the settings, each showing what it's set to now and changing it
+//! to the next thing when picked. display options and assists change in
+//! every tab straight away. rules can't change under a game that's going, so
+//! they're what the next game dealt starts with. whatever's changed is
+//! written back to the settings file.
+
+use infiniteklondike::cards::Deck;
+use infiniteklondike::rules::Reveal;
+
+use crate::assist::Assists;
+use crate::settings::{Grab, Settings};
+use crate::style::{CardBack, SuitMarks, Table};
+use crate::ui::Menu;
+use crate::MenuAction;
+
+/// a page of the options screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Display,
+    Assists,
+    Rules,
+}
+
+impl Page {
+    const ALL: [Page; 3] = [Page::Display, Page::Assists, Page::Rules];
+
+    fn title(self) -> &'static str {
+        match self {
+            Page::Display => "display",
+            Page::Assists => "assists",
+            Page::Rules => "rules for new games",
+        }
+    }
+
+    fn choices(self) -> &'static [Choice] {
+        match self {
+            Page::Display => &[
+                Choice::CardScale,
+                Choice::PanSpeed,
+                Choice::InvertPan,
+                Choice::Grab,
+                Choice::Monochrome,
+                Choice::SuitMarks,
+                Choice::FourColors,
+                Choice::CornerBadges,
+                Choice::RunBadges,
+                Choice::CardBack,
+                Choice::Table,
+                Choice::Volume,
+                Choice::Music,
+            ],
+            Page::Assists => &[
+                Choice::WarnBurying,
+                Choice::AutoArrange,
+                Choice::AutoChain,
+                Choice::Precision,
+            ],
+            Page::Rules => &[
+                Choice::Stock,
+                Choice::Deck,
+                Choice::Reveal,
+                Choice::Decay,
+                Choice::Practice,
+                Choice::Sandbox,
+            ],
+        }
+    }
+}
+
+/// one setting the options screen can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    CardScale,
+    PanSpeed,
+    InvertPan,
+    Grab,
+    Monochrome,
+    SuitMarks,
+    FourColors,
+    CornerBadges,
+    RunBadges,
+    CardBack,
+    Table,
+    Volume,
+    Music,
+    WarnBurying,
+    AutoArrange,
+    AutoChain,
+    Precision,
+    Stock,
+    Deck,
+    Reveal,
+    Decay,
+    Practice,
+    Sandbox,
+}
+
+impl Choice {
+    /// the card scales picking card size goes through
+    const SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+    /// the pan speeds picking pan speed goes through, in window pixels a
+    /// second
+    const SPEEDS: [f32; 4] = [450.0, 900.0, 1800.0, 3600.0];
+    /// the volumes picking volume goes through
+    const VOLUMES: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+    /// the reveal rules picking reveal goes through
+    const REVEALS: [Reveal; 4] = [
+        Reveal::One,
+        Reveal::Many(2),
+        Reveal::Many(3),
+        Reveal::Chance(50),
+    ];
+
+    /// the button for it, with what it's set to in `settings`
+    fn label(self, settings: &Settings) -> String {
+        let on = |on: bool| if on { "on" } else { "off" };
+        let Settings {
+            layout,
+            rules,
+            style,
+            assists,
+            ..
+        } = settings;
+        match self {
+            Choice::CardScale => format!("card size: {}%", (layout.card_scale * 100.0).round()),
+            Choice::PanSpeed => format!("pan speed: {}", layout.pan_speed),
+            Choice::InvertPan => format!("invert pan: {}", on(layout.invert_pan)),
+            Choice::Grab => format!("grab: {}", settings.grab),
+            Choice::Monochrome => format!("monochrome: {}", on(style.monochrome)),
+            Choice::SuitMarks => format!("suit marks: {}", style.suit_marks),
+            Choice::FourColors => format!("four colours: {}", on(style.four_colors)),
+            Choice::CornerBadges => format!("corner badges: {}", on(style.corner_badges)),
+            Choice::RunBadges => format!("run badges: {}", on(style.run_badges)),
+            Choice::CardBack => format!("card back: {}", style.card_back),
+            Choice::Table => format!("table: {}", style.table),
+            Choice::Volume => format!("volume: {}", percent(settings.volume)),
+            Choice::Music => format!("music: {}", percent(settings.music)),
+            Choice::WarnBurying => format!("burying warning: {}", on(assists.warn_burying)),
+            Choice::AutoArrange => format!("auto-arrange: {}", on(assists.auto_arrange)),
+            Choice::AutoChain => format!("auto-chain: {}", on(assists.auto_chain)),
+            Choice::Precision => format!("precision mouse: {}", on(assists.precision.is_some())),
+            Choice::Stock => format!(
+                "stock: {}",
+                match (rules.stock, rules.draw_three) {
+                    (false, _) => "off",
+                    (true, false) => "draw one",
+                    (true, true) => "draw three",
+                }
+            ),
+            Choice::Deck => format!("deck: {}", rules.deck),
+            Choice::Reveal => format!("reveal: {}", rules.reveal),
+            Choice::Decay => format!("decay: {}", on(rules.decay)),
+            Choice::Practice => format!("practice: {}", on(rules.practice)),
+            Choice::Sandbox => format!("sandbox: {}", on(rules.sandbox)),
+        }
+    }
+
+    /// moves it on to the next thing it can be
+    pub fn change(self, settings: &mut Settings) {
+        let Settings {
+            layout,
+            rules,
+            style,
+            assists,
+            ..
+        } = settings;
+        match self {
+            Choice::CardScale => layout.card_scale = next(&Self::SCALES, layout.card_scale),
+            Choice::PanSpeed => layout.pan_speed = next(&Self::SPEEDS, layout.pan_speed),
+            Choice::InvertPan => layout.invert_pan = !layout.invert_pan,
+            Choice::Grab => {
+                settings.grab = match settings.grab {
+                    Grab::Drag => Grab::Click,
+                    Grab::Click => Grab::Drag,
+                }
+            }
+            Choice::Monochrome => style.monochrome = !style.monochrome,
+            Choice::SuitMarks => {
+                style.suit_marks = match style.suit_marks {
+                    SuitMarks::Pips => SuitMarks::Letters,
+                    SuitMarks::Letters => SuitMarks::Shapes,
+                    SuitMarks::Shapes => SuitMarks::Pips,
+                }
+            }
+            Choice::FourColors => style.four_colors = !style.four_colors,
+            Choice::CornerBadges => style.corner_badges = !style.corner_badges,
+            Choice::RunBadges => style.run_badges = !style.run_badges,
+            Choice::CardBack => style.card_back = next(&CardBack::ALL, style.card_back),
+            Choice::Table => style.table = next(&Table::ALL, style.table),
+            Choice::Volume => settings.volume = next(&Self::VOLUMES, settings.volume),
+            Choice::Music => settings.music = next(&Self::VOLUMES, settings.music),
+            Choice::WarnBurying => assists.warn_burying = !assists.warn_burying,
+            Choice::AutoArrange => assists.auto_arrange = !assists.auto_arrange,
+            Choice::AutoChain => assists.auto_chain = !assists.auto_chain,
+            Choice::Precision => {
+                assists.precision = match assists.precision {
+                    Some(_) => None,
+                    None => Some(Assists::PRECISION),
+                }
+            }
+            Choice::Stock => {
+                (rules.stock, rules.draw_three) = match (rules.stock, rules.draw_three) {
+                    (false, _) => (true, false),
+                    (true, false) => (true, true),
+                    (true, true) => (false, false),
+                }
+            }
+            Choice::Deck => {
+                rules.deck = match rules.deck {
+                    Deck::Full => Deck::Piquet,
+                    Deck::Piquet => Deck::Full,
+                }
+            }
+            Choice::Reveal => rules.reveal = next(&Self::REVEALS, rules.reveal),
+            Choice::Decay => rules.decay = !rules.decay,
+            Choice::Practice => rules.practice = !rules.practice,
+            Choice::Sandbox => rules.sandbox = !rules.sandbox,
+        }
+    }
+}
+
+/// a volume out of 100, or off
+fn percent(volume: f32) -> String {
+    if volume == 0.0 {
+        "off".to_owned()
+    } else {
+        format!("{}%", (volume * 100.0).round())
+    }
+}
+
+/// the one after `now` in `all`, going back round to the first. something
+/// that isn't in there, from the settings file, goes to the first.
+fn next<T: Copy + PartialEq>(all: &[T], now: T) -> T {
+    let idx = all.iter().position(|value| *value == now);
+    all[idx.map_or(0, |idx| (idx + 1) % all.len())]
+}
+
+/// the first page of the options screen, with the pages on it
+pub fn menu() -> Menu<MenuAction> {
+    let items: Vec<(&str, MenuAction)> = Page::ALL
+        .into_iter()
+        .map(|page| (page.title(), MenuAction::OptionsPage(page)))
+        .chain([("back", MenuAction::Back)])
+        .collect();
+    Menu::new("options", &items)
+}
+
+/// a page of the options screen showing `settings`, with the keyboard on
+/// `choice` if it's given
+pub fn page_menu(page: Page, settings: &Settings, choice: Option<Choice>) -> Menu<MenuAction> {
+    let labels: Vec<(String, MenuAction)> = page
+        .choices()
+        .iter()
+        .map(|choice| (choice.label(settings), MenuAction::Change(page, *choice)))
+        .chain([("back".to_owned(), MenuAction::Options)])
+        .collect();
+    let items: Vec<(&str, MenuAction)> = labels
+        .iter()
+        .map(|(label, action)| (label.as_str(), *action))
+        .collect();
+    let mut menu = Menu::new(page.title(), &items);
+    if let Some(idx) = choice.and_then(|choice| page.choices().iter().position(|c| *c == choice)) {
+        menu.set_focus(idx);
+    }
+    menu
+}
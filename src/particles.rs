@@ -0,0 +1,138 @@
+//! little celebrations drawn over the cards: a puff of sparks and the
+//! points it scored when a card goes on a foundation, and a shower of them
+//! when a foundation's finished with its king. everything's worked out from
+//! how long ago it started, so there's nothing to move along each frame,
+//! and it's in layout coordinates, drawn with the board's camera.
+
+use ::rand::Rng;
+use macroquad::prelude::*;
+
+use crate::layout::Layout;
+
+/// a spark flying off and falling
+#[derive(Debug, Clone, Copy)]
+struct Spark {
+    from: Vec2,
+    /// how fast it set off, in layout units a second
+    velocity: Vec2,
+    /// when it set off, from `get_time`. it can be a moment from now.
+    born: f64,
+    /// how long it lasts, in seconds
+    life: f32,
+    color: Color,
+}
+
+/// points floating up off a foundation
+#[derive(Debug, Clone)]
+struct Popup {
+    text: String,
+    from: Vec2,
+    born: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Particles {
+    sparks: Vec<Spark>,
+    popups: Vec<Popup>,
+}
+
+impl Particles {
+    /// how fast sparks fall, in layout units a second squared
+    const GRAVITY: f32 = 400.0;
+    /// how long a popup takes to float away, in seconds
+    const POPUP_LIFE: f32 = 0.9;
+    /// how far a popup floats up, in layout units
+    const POPUP_RISE: f32 = 24.0;
+    /// how many sparks a card going on a foundation makes
+    const BURST: usize = 14;
+    /// how many a finished foundation makes
+    const CASCADE: usize = 90;
+
+    /// a card going on the foundation at `at`, the top left corner of it,
+    /// `delay` seconds from now. `points` is what it scored, shown if it's
+    /// anything.
+    pub fn burst(&mut self, at: Vec2, color: Color, points: i64, delay: f64) {
+        self.tidy();
+        let center = at + vec2(Layout::CARD_WIDTH, Layout::CARD_HEIGHT) / 2.0;
+        let born = get_time() + delay;
+        let mut rng = ::rand::thread_rng();
+        for _ in 0..Self::BURST {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = rng.gen_range(40.0..120.0);
+            self.sparks.push(Spark {
+                from: center,
+                velocity: vec2(angle.cos(), angle.sin()) * speed,
+                born,
+                life: rng.gen_range(0.3..0.6),
+                color,
+            });
+        }
+        if points != 0 {
+            self.popups.push(Popup {
+                text: format!("{:+}", points),
+                from: vec2(at.x + 8.0, at.y),
+                born,
+            });
+        }
+    }
+
+    /// a foundation at `at` finished with its king: sparks of every colour
+    /// in `colors` showering up and out from it
+    pub fn cascade(&mut self, at: Vec2, colors: &[Color], delay: f64) {
+        self.tidy();
+        let center = at + vec2(Layout::CARD_WIDTH / 2.0, 0.0);
+        let now = get_time() + delay;
+        let mut rng = ::rand::thread_rng();
+        for n in 0..Self::CASCADE {
+            // mostly upwards, so they fall back down over the board
+            let angle: f32 = rng.gen_range(-2.6..-0.5);
+            let speed = rng.gen_range(120.0..300.0);
+            self.sparks.push(Spark {
+                from: center,
+                velocity: vec2(angle.cos(), angle.sin()) * speed,
+                // not all at once
+                born: now + rng.gen_range(0.0..0.4),
+                life: rng.gen_range(0.8..1.6),
+                color: colors[n % colors.len()],
+            });
+        }
+    }
+
+    /// drops everything that's finished
+    fn tidy(&mut self) {
+        let now = get_time();
+        self.sparks
+            .retain(|spark| now - spark.born < f64::from(spark.life));
+        self.popups
+            .retain(|popup| now - popup.born < f64::from(Self::POPUP_LIFE));
+    }
+
+    /// draws everything, over the cards
+    pub fn draw(&self) {
+        let now = get_time();
+        for spark in &self.sparks {
+            let age = (now - spark.born) as f32;
+            if !(0.0..spark.life).contains(&age) {
+                continue;
+            }
+            let pos =
+                spark.from + spark.velocity * age + vec2(0.0, Self::GRAVITY / 2.0 * age * age);
+            let left = 1.0 - age / spark.life;
+            let color = Color {
+                a: spark.color.a * left,
+                ..spark.color
+            };
+            draw_rectangle(pos.x - 1.0, pos.y - 1.0, 2.0, 2.0, color);
+        }
+        for popup in &self.popups {
+            let age = (now - popup.born) as f32;
+            if !(0.0..Self::POPUP_LIFE).contains(&age) {
+                continue;
+            }
+            let done = age / Self::POPUP_LIFE;
+            let color = Color::new(1.0, 1.0, 1.0, 1.0 - done * done);
+            let y = popup.from.y - Self::POPUP_RISE * done;
+            draw_text(&popup.text, popup.from.x, y, 18.0, color);
+        }
+    }
+}
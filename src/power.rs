@@ -0,0 +1,43 @@
+//! a power saving mode for when the game is left open in the background.
+//! once nothing has happened for a moment the loop sleeps between frames,
+//! so an idle window only wakes up a few times a second to check for input.
+
+use std::time::Duration;
+
+use macroquad::prelude::{get_time, screen_height, screen_width, vec2, Vec2};
+
+use crate::input;
+
+pub struct Power {
+    /// when the last input came in, from `get_time`
+    last_input: f64,
+    /// the window size last frame, since resizes aren't input events
+    screen: Vec2,
+}
+
+impl Power {
+    /// how long to keep running at full speed after the last input, so
+    /// drags and key repeats stay smooth
+    const AWAKE_FOR: f64 = 1.0;
+    /// how long to sleep for each frame once idle
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    pub fn new() -> Self {
+        Self {
+            last_input: get_time(),
+            screen: vec2(screen_width(), screen_height()),
+        }
+    }
+
+    /// call once a frame, right before `next_frame`. sleeps if there hasn't
+    /// been any input for a while.
+    pub fn rest(&mut self) {
+        let screen = vec2(screen_width(), screen_height());
+        if input::had_input() || screen != self.screen || input::is_playing() {
+            self.last_input = get_time();
+            self.screen = screen;
+        } else if get_time() - self.last_input > Self::AWAKE_FOR {
+            std::thread::sleep(Self::SLEEP);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! draws the start of a board into a png without a window, using the same
+//! atlas and the same spacing as the game.
+
+use image::{Rgba, RgbaImage};
+
+use infiniteklondike::cards::{BitCard, Column};
+use infiniteklondike::game::Game;
+use macroquad::math::Rect;
+
+use crate::atlas::Sprites;
+use crate::style::CardBack;
+
+const CARD_WIDTH: u32 = 44;
+const CARD_HEIGHT: u32 = 64;
+const ROW_WIDTH: u32 = 48;
+const STACK_OFFSET: u32 = 16;
+const TABLEAU_Y_OFFSET: u32 = 68;
+const MARGIN: u32 = 2;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: [u8; 4] = [230, 41, 55, 255];
+
+/// renders the first `columns` columns of `game`, hidden cards face down
+pub fn render(game: &Game, atlas: &RgbaImage, sprites: &Sprites, columns: usize) -> RgbaImage {
+    let tableau: Vec<&Column> = (0..columns).map_while(|x| game.tableau().get(x)).collect();
+    let tallest = tableau
+        .iter()
+        .map(|column| column.under + column.visible().len() as u32)
+        .max()
+        .unwrap_or(1);
+    let mut out = RgbaImage::from_pixel(
+        MARGIN * 2 + ROW_WIDTH * tableau.len() as u32 - (ROW_WIDTH - CARD_WIDTH),
+        MARGIN * 2 + TABLEAU_Y_OFFSET + STACK_OFFSET * (tallest.max(1) - 1) + CARD_HEIGHT,
+        Rgba([0, 0, 0, 255]),
+    );
+    // foundations start out empty, so only draw them from the fourth column on
+    // like the game does
+    for x in 3..tableau.len() as u32 {
+        let x = MARGIN + ROW_WIDTH * x;
+        blit(&mut out, atlas, sprites.outline(), WHITE, x, MARGIN);
+    }
+    for (x, column) in tableau.iter().enumerate() {
+        let x = MARGIN + ROW_WIDTH * x as u32;
+        let y = MARGIN + TABLEAU_Y_OFFSET;
+        for n in 0..column.under {
+            let back = sprites.back(CardBack::default());
+            blit(&mut out, atlas, back, WHITE, x, y + STACK_OFFSET * n);
+        }
+        for (n, card) in column.visible().iter().enumerate() {
+            draw_card(
+                &mut out,
+                atlas,
+                sprites,
+                card,
+                x,
+                y + STACK_OFFSET * (n as u32 + column.under),
+            );
+        }
+    }
+    out
+}
+
+fn draw_card(
+    out: &mut RgbaImage,
+    atlas: &RgbaImage,
+    sprites: &Sprites,
+    card: BitCard,
+    x: u32,
+    y: u32,
+) {
+    let color = if card.is_red() { RED } else { WHITE };
+    blit(out, atlas, sprites.face(), WHITE, x, y);
+    blit(out, atlas, sprites.suit(card.suit()), color, x, y);
+    blit(out, atlas, sprites.rank(card.number()), color, x, y);
+}
+
+/// draws the atlas sprite `src` stretched to the size of a card, tinted by
+/// `tint`. anything past the edge of the atlas is left out.
+fn blit(out: &mut RgbaImage, atlas: &RgbaImage, src: Rect, tint: [u8; 4], x: u32, y: u32) {
+    for dy in 0..CARD_HEIGHT {
+        for dx in 0..CARD_WIDTH {
+            let sx = (src.x + src.w * dx as f32 / CARD_WIDTH as f32) as u32;
+            let sy = (src.y + src.h * dy as f32 / CARD_HEIGHT as f32) as u32;
+            if sx >= atlas.width() || sy >= atlas.height() {
+                continue;
+            }
+            let pixel = atlas.get_pixel(sx, sy).0;
+            let alpha = pixel[3] as u32 * tint[3] as u32 / 255;
+            let dst = out.get_pixel_mut(x + dx, y + dy);
+            for ((d, s), t) in dst.0.iter_mut().zip(pixel).zip(tint).take(3) {
+                let s = s as u32 * t as u32 / 255;
+                *d = ((s * alpha + *d as u32 * (255 - alpha)) / 255) as u8;
+            }
+        }
+    }
+}
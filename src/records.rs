@@ -0,0 +1,105 @@
+//! best times, kept in a text file with one time a line:
+//!
+//! ```text
+//! seed 1234 6ec6bb26f581214f 100 312.5
+//! stretch 6ec6bb26f581214f 100 95.25
+//! ```
+//!
+//! a `seed` line is how long a game of that seed took to put that many cards
+//! on the foundations, by the game's clock. a `stretch` line is the quickest
+//! that many cards went on the foundations one after another in one sitting,
+//! on any seed. both are kept apart by the [`Rules::key`] of the rules they
+//! were played under. lines are only ever added, the best is the lowest.
+//!
+//! [`Rules::key`]: crate::rules::Rules::key
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// times are kept for every this many cards founded
+pub const MILESTONE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// a game of this seed, from the start
+    Seed(u64),
+    /// any run of cards in one sitting
+    Stretch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    pub kind: Kind,
+    /// the [`Rules::key`](crate::rules::Rules::key) of the rules played
+    pub variant: u64,
+    pub cards: usize,
+    pub seconds: f64,
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Seed(seed) => write!(f, "seed {} ", seed)?,
+            Kind::Stretch => write!(f, "stretch ")?,
+        }
+        write!(f, "{:016x} {} {}", self.variant, self.cards, self.seconds)
+    }
+}
+
+impl FromStr for Time {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (kind, variant, cards, seconds) = match words.as_slice() {
+            ["seed", seed, variant, cards, seconds] => {
+                let seed = seed
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a seed", seed))?;
+                (Kind::Seed(seed), variant, cards, seconds)
+            }
+            ["stretch", variant, cards, seconds] => (Kind::Stretch, variant, cards, seconds),
+            _ => {
+                return Err(format!(
+                    "`{}` should be: seed <seed> or stretch, then rules cards seconds",
+                    s
+                ))
+            }
+        };
+        Ok(Time {
+            kind,
+            variant: u64::from_str_radix(variant, 16)
+                .map_err(|_| format!("`{}` isn't a rules key", variant))?,
+            cards: cards
+                .parse()
+                .map_err(|_| format!("`{}` isn't a number", cards))?,
+            seconds: seconds
+                .parse()
+                .ok()
+                .filter(|seconds: &f64| seconds.is_finite())
+                .ok_or_else(|| format!("`{}` isn't a time", seconds))?,
+        })
+    }
+}
+
+/// the best time for `cards` cards of this kind under these rules, if
+/// there is one. lines that can't be read are left out.
+pub fn best(path: impl AsRef<Path>, kind: Kind, variant: u64, cards: usize) -> Option<f64> {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| line.parse().ok())
+        .filter(|time: &Time| time.kind == kind && time.variant == variant && time.cards == cards)
+        .map(|time| time.seconds)
+        .min_by(f64::total_cmp)
+}
+
+/// adds a time to the end of the file
+pub fn record(path: impl AsRef<Path>, time: &Time) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", time)
+}
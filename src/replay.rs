@@ -0,0 +1,329 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::cards::Deck;
+use crate::game::{Game, Move, MoveError};
+use crate::rules::{Reveal, Rules, Topology};
+
+/// the first line, followed by the version
+const HEADER: &str = "infinite-klondike replay";
+/// version 1 hashed every column of the board, see [`Game::state_hash`]
+const VERSION: u32 = 2;
+
+/// a recorded game: the seed, how many columns were generated, every move made,
+/// and the hash of the board at the end. games with other rules also get a
+/// line like `topology grid`, `sandbox`, `practice`, `stock`, `draw three`,
+/// `deck piquet`, `reveal chance:50` or `decay` after the columns. decays
+/// aren't moves, so replays of decay games don't check out.
+///
+/// replays are plain text, one thing per line:
+///
+/// ```text
+/// infinite-klondike replay 2
+/// seed 1234
+/// columns 57
+/// t 3 0 5
+/// f 4 0
+/// hash 7c1f0e2a9b3d4c5e
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub columns: usize,
+    pub rules: Rules,
+    pub moves: Vec<Move>,
+    pub hash: u64,
+    /// the hash is of every column, from a version 1 replay
+    pub full_hash: bool,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Parse { line: usize, reason: &'static str },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{}", e),
+            ReplayError::Parse { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(e: std::io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    IllegalMove {
+        index: usize,
+        mv: Move,
+        error: MoveError,
+    },
+    HashMismatch {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::IllegalMove { index, mv, error } => {
+                write!(f, "move {} ({:?}) is illegal: {}", index, mv, error)
+            }
+            VerifyError::HashMismatch { expected, actual } => write!(
+                f,
+                "final state hash is {:016x}, replay says {:016x}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl Replay {
+    /// records the current state of `game` reached by playing `moves`
+    pub fn record(game: &Game, moves: &[Move]) -> Self {
+        Self {
+            seed: game.seed(),
+            columns: game.columns(),
+            rules: game.rules(),
+            moves: moves.to_vec(),
+            hash: game.state_hash(),
+            full_hash: false,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ReplayError> {
+        let mut lines = text.lines().enumerate().map(|(n, line)| (n + 1, line));
+        let err = |line, reason| ReplayError::Parse { line, reason };
+        let version = match lines.next() {
+            Some((_, line)) => line
+                .strip_prefix(HEADER)
+                .and_then(|version| version.trim().parse::<u32>().ok())
+                .ok_or_else(|| err(1, "not a replay"))?,
+            None => return Err(err(1, "not a replay")),
+        };
+        if version == 0 || version > VERSION {
+            return Err(err(1, "made by a newer version of the game"));
+        }
+        let mut seed = None;
+        let mut columns = None;
+        let mut hash = None;
+        let mut rules = Rules::default();
+        let mut moves = Vec::new();
+        for (n, line) in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let num = |i: usize| -> Result<usize, ReplayError> {
+                words
+                    .get(i)
+                    .and_then(|w| w.parse().ok())
+                    .ok_or_else(|| err(n, "expected a number"))
+            };
+            match words.first().copied() {
+                None => {}
+                Some("seed") => {
+                    let parsed = words.get(1).and_then(|w| w.parse().ok());
+                    seed = Some(parsed.ok_or_else(|| err(n, "bad seed"))?);
+                }
+                Some("columns") => columns = Some(num(1)?),
+                Some("topology") => {
+                    let parsed = words.get(1).and_then(|w| w.parse().ok());
+                    rules.topology = parsed.ok_or_else(|| err(n, "unknown topology"))?;
+                }
+                Some("sandbox") => rules.sandbox = true,
+                Some("stock") => rules.stock = true,
+                Some("draw") => match words.get(1).copied() {
+                    Some("three") => rules.draw_three = true,
+                    _ => return Err(err(n, "unknown draw")),
+                },
+                Some("practice") => rules.practice = true,
+                Some("decay") => rules.decay = true,
+                Some("deck") => {
+                    let parsed = words.get(1).and_then(|w| w.parse().ok());
+                    rules.deck = parsed.ok_or_else(|| err(n, "unknown deck"))?;
+                }
+                Some("reveal") => {
+                    let parsed = words.get(1).and_then(|w| w.parse().ok());
+                    rules.reveal = parsed.ok_or_else(|| err(n, "unknown reveal"))?;
+                }
+                Some("hash") => {
+                    let parsed = words.get(1).and_then(|w| u64::from_str_radix(w, 16).ok());
+                    hash = Some(parsed.ok_or_else(|| err(n, "bad hash"))?);
+                }
+                Some("t") => moves.push(Move::Tableau {
+                    from: num(1)?,
+                    idx: num(2)?,
+                    to: num(3)?,
+                }),
+                Some("r") => moves.push(Move::Reshuffle { column: num(1)? }),
+                Some("d") => moves.push(Move::Draw),
+                Some("w") => moves.push(Move::Waste { to: num(1)? }),
+                Some("wf") => moves.push(Move::WasteFoundation {
+                    foundation: num(1)?,
+                }),
+                Some("i") => moves.push(Move::Insert {
+                    from: num(1)?,
+                    idx: num(2)?,
+                    at: num(3)?,
+                }),
+                Some("f") => moves.push(Move::Foundation {
+                    from: num(1)?,
+                    foundation: num(2)?,
+                }),
+                Some(_) => return Err(err(n, "unknown line")),
+            }
+        }
+        Ok(Self {
+            seed: seed.ok_or_else(|| err(0, "missing seed"))?,
+            columns: columns.ok_or_else(|| err(0, "missing columns"))?,
+            rules,
+            moves,
+            hash: hash.ok_or_else(|| err(0, "missing hash"))?,
+            full_hash: version < 2,
+        })
+    }
+
+    /// replays every move from the seed without any graphics, checking that
+    /// each one was legal and that the board ends up where the replay says.
+    pub fn verify(&self) -> Result<Game, VerifyError> {
+        // `columns` counts the inserted columns, which the moves put back
+        let inserts = (self.moves.iter())
+            .filter(|mv| matches!(mv, Move::Insert { .. }))
+            .count();
+        let columns = self.columns.saturating_sub(inserts);
+        let mut game = Game::with_rules(self.seed, columns, self.rules);
+        for (index, mv) in self.moves.iter().enumerate() {
+            game.apply(*mv).map_err(|error| VerifyError::IllegalMove {
+                index,
+                mv: *mv,
+                error,
+            })?;
+        }
+        let actual = if self.full_hash {
+            game.full_state_hash()
+        } else {
+            game.state_hash()
+        };
+        if actual != self.hash {
+            return Err(VerifyError::HashMismatch {
+                expected: self.hash,
+                actual,
+            });
+        }
+        Ok(game)
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = if self.full_hash { 1 } else { VERSION };
+        writeln!(f, "{} {}", HEADER, version)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "columns {}", self.columns)?;
+        if self.rules.topology != Topology::Line {
+            writeln!(f, "topology {}", self.rules.topology)?;
+        }
+        if self.rules.sandbox {
+            writeln!(f, "sandbox")?;
+        }
+        if self.rules.practice {
+            writeln!(f, "practice")?;
+        }
+        if self.rules.stock {
+            writeln!(f, "stock")?;
+        }
+        if self.rules.draw_three {
+            writeln!(f, "draw three")?;
+        }
+        if self.rules.deck != Deck::Full {
+            writeln!(f, "deck {}", self.rules.deck)?;
+        }
+        if self.rules.reveal != Reveal::One {
+            writeln!(f, "reveal {}", self.rules.reveal)?;
+        }
+        if self.rules.decay {
+            writeln!(f, "decay")?;
+        }
+        for mv in &self.moves {
+            match mv {
+                Move::Tableau { from, idx, to } => writeln!(f, "t {} {} {}", from, idx, to)?,
+                Move::Foundation { from, foundation } => writeln!(f, "f {} {}", from, foundation)?,
+                Move::Reshuffle { column } => writeln!(f, "r {}", column)?,
+                Move::Insert { from, idx, at } => writeln!(f, "i {} {} {}", from, idx, at)?,
+                Move::Draw => writeln!(f, "d")?,
+                Move::Waste { to } => writeln!(f, "w {}", to)?,
+                Move::WasteFoundation { foundation } => writeln!(f, "wf {}", foundation)?,
+            }
+        }
+        writeln!(f, "hash {:016x}", self.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::tests::play;
+
+    fn recorded() -> Replay {
+        let mut game = Game::new(7, 20);
+        let moves = play(&mut game, 0..20, 10);
+        assert!(moves.len() >= 2);
+        Replay::record(&game, &moves)
+    }
+
+    #[test]
+    fn recorded_game_verifies() {
+        let replay = recorded();
+        let game = replay.verify().unwrap();
+        assert_eq!(game.state_hash(), replay.hash);
+
+        let parsed = Replay::parse(&replay.to_string()).unwrap();
+        assert_eq!(parsed, replay);
+        parsed.verify().unwrap();
+    }
+
+    #[test]
+    fn tampered_move_is_rejected() {
+        let mut replay = recorded();
+        replay.moves[1] = Move::Tableau {
+            from: 0,
+            idx: 52,
+            to: 1,
+        };
+        match replay.verify() {
+            Err(VerifyError::IllegalMove { index: 1, .. }) => {}
+            other => panic!("expected move 1 to be illegal, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn tampered_hash_is_rejected() {
+        let mut replay = recorded();
+        let actual = replay.hash;
+        replay.hash ^= 1;
+        match replay.verify() {
+            Err(VerifyError::HashMismatch {
+                expected,
+                actual: got,
+            }) => {
+                assert_eq!(expected, replay.hash);
+                assert_eq!(got, actual);
+            }
+            other => panic!("expected a hash mismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+}
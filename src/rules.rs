@@ -0,0 +1,232 @@
+//! the parts of the rules that can change from game to game
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cards::Deck;
+use crate::game::Fnv;
+
+/// how the columns of the tableau are connected to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// one endless row, any column can move onto any other
+    #[default]
+    Line,
+    /// an experimental grid of piles going on forever to the right and
+    /// down. cards only move between piles that are next to each other.
+    Grid,
+}
+
+impl Topology {
+    /// how many face down cards `column` gets in the deal
+    pub fn hidden(self, column: usize) -> u32 {
+        let depth = match self {
+            Topology::Line => column,
+            Topology::Grid => {
+                let (x, y) = grid_coords(column);
+                x + y
+            }
+        };
+        depth.try_into().unwrap()
+    }
+
+    /// whether cards can move straight from column `a` to column `b`
+    pub fn connected(self, a: usize, b: usize) -> bool {
+        match self {
+            Topology::Line => true,
+            Topology::Grid => {
+                let (ax, ay) = grid_coords(a);
+                let (bx, by) = grid_coords(b);
+                ax.abs_diff(bx) + ay.abs_diff(by) == 1
+            }
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Topology::Line => "line",
+            Topology::Grid => "grid",
+        })
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(Topology::Line),
+            "grid" => Ok(Topology::Grid),
+            _ => Err(format!("unknown topology `{}`, try line or grid", s)),
+        }
+    }
+}
+
+/// what happens when the last face up card leaves a column that still has
+/// face down cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reveal {
+    /// the next card is turned over, the normal rule
+    #[default]
+    One,
+    /// the next card is only turned over this many times out of a hundred.
+    /// otherwise the column is left empty for a king, and gets another
+    /// chance every time a card goes on a foundation, or the next time it's
+    /// emptied if a king went on it. harder.
+    Chance(u8),
+    /// this many cards are turned over at once, or all of them if there
+    /// aren't that many. they come up in any order, so only the run on top
+    /// can be moved until the rest are dug out. easier to see into a
+    /// column, harder to clear it.
+    Many(u8),
+}
+
+impl Reveal {
+    /// whether the chance is a percentage that can come up, and there's at
+    /// least one card to turn over and no more than a suit
+    pub fn is_valid(self) -> bool {
+        match self {
+            Reveal::One => true,
+            Reveal::Chance(percent) => (1..=100).contains(&percent),
+            Reveal::Many(count) => (1..=13).contains(&count),
+        }
+    }
+}
+
+impl fmt::Display for Reveal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reveal::One => f.write_str("one"),
+            Reveal::Chance(percent) => write!(f, "chance:{}", percent),
+            Reveal::Many(count) => write!(f, "many:{}", count),
+        }
+    }
+}
+
+impl FromStr for Reveal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!(
+                "unknown reveal `{}`, try one, chance:<percent> or many:<n>",
+                s
+            )
+        };
+        let (kind, n) = match s.split_once(':') {
+            Some((kind, n)) => (kind, Some(n.parse::<u8>().map_err(|_| err())?)),
+            None => (s, None),
+        };
+        let reveal = match (kind, n) {
+            ("one", None) => Reveal::One,
+            ("chance", Some(percent)) => Reveal::Chance(percent),
+            ("many", Some(count)) => Reveal::Many(count),
+            _ => return Err(err()),
+        };
+        if reveal.is_valid() {
+            Ok(reveal)
+        } else {
+            Err(err())
+        }
+    }
+}
+
+/// everything about the rules that isn't fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rules {
+    pub topology: Topology,
+    /// unranked free play: any run can go on any column, and cards can be
+    /// made up or thrown away
+    pub sandbox: bool,
+    /// unranked practice: face down cards are shown, greyed out
+    pub practice: bool,
+    pub reveal: Reveal,
+    /// classic klondike's stock and waste piles, on top of the columns. the
+    /// stock never runs out.
+    pub stock: bool,
+    /// the stock turns three cards over at a time instead of one, and only
+    /// the last of them can be played
+    pub draw_three: bool,
+    pub deck: Deck,
+    /// face up cards left alone for too long turn back face down, see
+    /// [`Game::decay`](crate::game::Game::decay)
+    pub decay: bool,
+}
+
+impl Rules {
+    /// how many cards the stock turns over at a time
+    pub fn cards_per_draw(&self) -> u64 {
+        if self.draw_three {
+            3
+        } else {
+            1
+        }
+    }
+
+    /// a number for this variant of the rules, for keeping results from
+    /// different variants apart. the same rules always get the same key.
+    pub fn key(&self) -> u64 {
+        let mut hash = Fnv::new();
+        hash.write(self.to_string().as_bytes());
+        hash.finish()
+    }
+}
+
+/// the canonical name of the variant, like `line one` or `grid many:3
+/// practice`. `key` is a hash of this, so it mustn't change.
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.topology, self.reveal)?;
+        if self.sandbox {
+            f.write_str(" sandbox")?;
+        }
+        if self.practice {
+            f.write_str(" practice")?;
+        }
+        if self.stock {
+            f.write_str(" stock")?;
+        }
+        if self.draw_three {
+            f.write_str(" draw:3")?;
+        }
+        if self.deck != Deck::Full {
+            write!(f, " deck:{}", self.deck)?;
+        }
+        if self.decay {
+            f.write_str(" decay")?;
+        }
+        Ok(())
+    }
+}
+
+/// the column index of the pile at `x`, `y` on the grid.
+///
+/// piles are numbered along the diagonals, so every pile a given number of
+/// steps from the corner comes before the ones further out:
+///
+/// ```text
+/// 0 1 3 6
+/// 2 4 7
+/// 5 8
+/// 9
+/// ```
+pub fn grid_index(x: usize, y: usize) -> usize {
+    let diagonal = x + y;
+    diagonal * (diagonal + 1) / 2 + y
+}
+
+/// where column `index` is on the grid, the inverse of [`grid_index`]
+pub fn grid_coords(index: usize) -> (usize, usize) {
+    // the float guess can be one off for huge indices, so nudge it
+    let mut diagonal = (((8 * index + 1) as f64).sqrt() as usize).saturating_sub(1) / 2;
+    while diagonal * (diagonal + 1) / 2 > index {
+        diagonal -= 1;
+    }
+    while (diagonal + 1) * (diagonal + 2) / 2 <= index {
+        diagonal += 1;
+    }
+    let y = index - diagonal * (diagonal + 1) / 2;
+    (diagonal - y, y)
+}
@@ -0,0 +1,77 @@
+//! the tools for sandbox games. c makes up a card and puts it on the column
+//! under the mouse, delete (or x) throws away the last card of that column,
+//! or the cards being carried.
+
+use infiniteklondike::cards::BitCard;
+use macroquad::prelude::*;
+
+use crate::ctrl_down;
+use crate::input::{get_char_pressed, is_key_pressed};
+
+/// a change to the board that isn't a move
+pub enum Edit {
+    Spawn(BitCard),
+    Delete,
+}
+
+#[derive(Default)]
+pub struct Sandbox {
+    /// the card being typed in to spawn
+    spawning: Option<String>,
+    /// what went wrong with the last card typed in
+    error: Option<String>,
+}
+
+impl Sandbox {
+    pub fn is_typing(&self) -> bool {
+        self.spawning.is_some()
+    }
+
+    /// handles input, returning what to do to the board
+    pub fn update(&mut self) -> Option<Edit> {
+        if let Some(name) = &mut self.spawning {
+            while let Some(c) = get_char_pressed() {
+                if c.is_ascii_alphanumeric() && name.len() < 3 {
+                    name.push(c.to_ascii_uppercase());
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+            if is_key_pressed(KeyCode::Escape) {
+                self.spawning = None;
+            } else if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+                let name = self.spawning.take().unwrap();
+                match name.parse() {
+                    Ok(card) => return Some(Edit::Spawn(card)),
+                    Err(_) => self.error = Some(format!("`{}` isn't a card, try 7H or QS", name)),
+                }
+            }
+            return None;
+        }
+        if ctrl_down() {
+            return None;
+        }
+        if is_key_pressed(KeyCode::C) {
+            // the c that started this shouldn't end up in the card
+            while get_char_pressed().is_some() {}
+            self.spawning = Some(String::new());
+            self.error = None;
+        }
+        if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::X) {
+            return Some(Edit::Delete);
+        }
+        None
+    }
+
+    pub fn draw(&self) {
+        let label = match (&self.spawning, &self.error) {
+            (Some(name), _) => format!("sandbox (unranked) - card to add: {}_", name),
+            (None, Some(error)) => format!("sandbox (unranked) - {}", error),
+            (None, None) => "sandbox (unranked) - c adds a card, delete removes one".to_owned(),
+        };
+        let y = screen_height() - 48.0;
+        draw_rectangle(0.0, y, screen_width(), 24.0, Color::new(0.0, 0.0, 0.0, 0.8));
+        draw_text(&label, 8.0, y + 17.0, 20.0, WHITE);
+    }
+}
@@ -0,0 +1,1542 @@
+//! saved games.
+//!
+//! the binary format is meant to stay readable by every future version:
+//!
+//! ```text
+//! "IKSV"              magic
+//! u16                 format version, little endian
+//! u8                  flags, bit 0 means the payload is deflate compressed
+//! u32                 payload length, little endian
+//! [u8]                payload
+//! ```
+//!
+//! the payload is a sequence of sections, each one a varint length followed by
+//! that many bytes, so newer versions can add sections at the end that older
+//! ones skip. numbers inside are LEB128 varints unless noted.
+//!
+//! 1. seed (u64 le), camera x and y (f32 le)
+//! 2. column count, then how many of them differ from how they were dealt,
+//!    then for each of those: how many columns after the previous one it is
+//!    (counting from column 0 for the first), face down count, face up count,
+//!    face up cards. version 1 saves wrote every column, without the gaps.
+//! 3. foundation count, then for each: pile index, card
+//! 4. move count, then for each: 0 from idx to (tableau), 1 from pile
+//!    (foundation), 2 column (reshuffle), 3 from idx at (insert), 4 (draw),
+//!    5 to (waste onto a column) or 6 pile (waste onto a foundation)
+//! 5. topology, 0 for a line and 1 for a grid. missing means a line.
+//! 6. flags, bit 0 set for sandbox games, bit 1 for practice games, bit 2
+//!    for games with a stock, bit 3 for drawing three from it, bit 4 for
+//!    the piquet deck and bit 5 for decay games. missing means none.
+//! 7. seconds actually played (f64 le), leaving out time spent idle, then how
+//!    many times the timer stopped for being idle. missing or empty means
+//!    unknown.
+//! 8. what happens when a column runs out of face up cards: 0 to turn one
+//!    over, 1 then a percent to leave it to chance, 2 then a count to turn
+//!    over that many. missing means one.
+//! 9. how many reshuffles have been used. missing means none.
+//! 10. how many columns were inserted, then where each one is, counted like
+//!     the changed columns in section 2. missing means none.
+//! 11. how many cards were turned over from the stock, then how many are on
+//!     the waste and those cards, bottom first. missing means none.
+//! 12. how many regions had cards put on foundations from them, then for
+//!     each: the region and how many cards. missing means none.
+//! 13. how many bookmarks there are, then for each: the length of its name,
+//!     the name as utf-8, and the camera x and y (f32 le). missing means
+//!     none.
+//! 14. how many cards have been put down by hand, then the ids of the face
+//!     up cards of each column in section 2, the cards on the foundations in
+//!     section 3 and the cards on the waste in section 11, in the same order.
+//!     missing means the ones that aren't as they were dealt are unknown.
+//! 15. for games streamed to disk (see [`crate::world`]), the length of the
+//!     directory's path, the path as utf-8, then how many changed columns
+//!     are in it, then for each: how many columns after the previous one it
+//!     is, face down count and face up count. those columns are left out of
+//!     section 2. missing or empty means the game isn't streamed. saves
+//!     with this are format version 3, the rest still say 2.
+//! 16. how many columns in section 2 have face up cards that decayed face
+//!     down, then for each: how many columns after the previous one it is,
+//!     counted like section 2, how many cards, and each card and its id, the
+//!     first to decay first. missing means none.
+//! 17. how many of the streamed columns in section 15 have face up cards
+//!     that decayed face down, then for each: how many columns after the
+//!     previous one it is, counted like section 15, and how many cards.
+//!     missing means none.
+//!
+//! saves can also be written as pretty printed json instead, for reading and
+//! editing by hand. it's the same shape as the board served over http, with
+//! the camera and moves added. loading tells the two apart by the first byte.
+//! json saves before version 4 had every column of the board in order, since
+//! then they only have the changed ones, each with its `x`, and
+//! `column_count`.
+//!
+//! columns that were never touched aren't kept in binary saves, since the
+//! seed deals them again. the ones that were are only put back on the board
+//! when the game gets to them, so loading takes as long for a save hundreds of
+//! thousands of columns out as for one that never left the start.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::cards::{BitCard, CardId, CardStack, Column, Deck};
+use crate::game::{CardIds, Game, Move};
+use crate::json::{self, Value};
+use crate::rules::{Reveal, Rules, Topology};
+use crate::world::World;
+
+const MAGIC: &[u8; 4] = b"IKSV";
+/// the newest format version. binary saves are still version 2 unless they
+/// stream, and json saves are version 4 since they only have the changed
+/// columns.
+const VERSION: u16 = 4;
+const COMPRESSED: u8 = 1;
+/// bit of the flags section for sandbox games
+const SANDBOX: u8 = 1;
+/// bit of the flags section for practice games
+const PRACTICE: u8 = 2;
+/// bit of the flags section for games with a stock
+const STOCK: u8 = 4;
+/// bit of the flags section for stocks that turn three cards over at a time
+const DRAW_THREE: u8 = 8;
+/// bit of the flags section for games played with the 32 card piquet deck
+const PIQUET: u8 = 16;
+/// bit of the flags section for games where face up cards decay
+const DECAY: u8 = 32;
+
+/// how a save gets written to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveFormat {
+    /// small, compressed and meant to load in every future version
+    #[default]
+    Binary,
+    /// pretty printed, for poking at by hand
+    Json,
+}
+
+impl SaveFormat {
+    /// the file extension saves in this format usually get
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveFormat::Binary => "iksv",
+            SaveFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for SaveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(SaveFormat::Binary),
+            "json" => Ok(SaveFormat::Json),
+            _ => Err(format!("unknown save format `{}`, try binary or json", s)),
+        }
+    }
+}
+
+/// everything needed to pick a game back up where it was left
+pub struct SaveData {
+    pub game: Game,
+    /// where the frontend was looking
+    pub camera: [f32; 2],
+    /// every move so far, so the replay keeps working after loading
+    pub moves: Vec<Move>,
+    /// how long the game has been played for, in seconds, leaving out
+    /// whenever the player was away. `None` for saves from before it was
+    /// kept track of.
+    pub played: Option<f64>,
+    /// how many times the player went idle, so a time can be told apart
+    /// from one that was paused for a while
+    pub idle_pauses: u32,
+    /// places the player marked to come back to, by name, with where the
+    /// camera was
+    pub bookmarks: Vec<(String, [f32; 2])>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    NotASave,
+    /// made by a newer version of the game
+    UnsupportedVersion(u16),
+    Corrupt(&'static str),
+    Json(json::ParseError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::NotASave => f.write_str("not a saved game"),
+            SaveError::UnsupportedVersion(v) => {
+                write!(f, "saved by a newer version of the game (format {})", v)
+            }
+            SaveError::Corrupt(what) => write!(f, "save is corrupt: {}", what),
+            SaveError::Json(e) => write!(f, "save is not valid json: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl SaveData {
+    pub fn save(&self, path: impl AsRef<Path>, format: SaveFormat) -> std::io::Result<()> {
+        let bytes = match format {
+            SaveFormat::Binary => self.to_bytes(true),
+            SaveFormat::Json => format!("{:#}\n", self.to_json()).into_bytes(),
+        };
+        // written next to it first, so a crash can't leave half a save over
+        // the last good one
+        let path = path.as_ref();
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(partial, path)
+    }
+
+    /// loads a save in either format
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let bytes = std::fs::read(path)?;
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => {
+                let text = std::str::from_utf8(&bytes).map_err(|_| SaveError::NotASave)?;
+                Self::from_json(&text.parse().map_err(SaveError::Json)?)
+            }
+            _ => Self::from_bytes(&bytes),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let moves: Vec<Value> = self.moves.iter().map(|mv| Value::from(*mv)).collect();
+        let mut save = Value::object().with("version", VERSION);
+        // the board's fields go straight in, next to the save's own
+        if let (Value::Object(fields), Value::Object(game)) = (&mut save, Value::from(&self.game)) {
+            fields.extend(game);
+        }
+        let bookmarks: Vec<Value> = self
+            .bookmarks
+            .iter()
+            .map(|(name, camera)| {
+                Value::object()
+                    .with("name", name.as_str())
+                    .with("camera", camera.to_vec())
+            })
+            .collect();
+        let save = save
+            .with("camera", self.camera.to_vec())
+            .with("moves", moves)
+            .with("bookmarks", bookmarks);
+        match self.played {
+            Some(played) => save
+                .with("played", played)
+                .with("idle_pauses", self.idle_pauses),
+            None => save,
+        }
+    }
+
+    pub fn from_json(save: &Value) -> Result<Self, SaveError> {
+        let version = save
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(SaveError::NotASave)?;
+        if version > VERSION.into() {
+            return Err(SaveError::UnsupportedVersion(
+                version.try_into().unwrap_or(u16::MAX),
+            ));
+        }
+        let seed = save
+            .get("seed")
+            .and_then(Value::as_str)
+            .and_then(|seed| seed.parse().ok())
+            .ok_or(SaveError::Corrupt("seed should be a number in a string"))?;
+        let mut rules = Rules::default();
+        if let Some(topology) = save.get("topology") {
+            rules.topology = topology
+                .as_str()
+                .and_then(|topology| topology.parse().ok())
+                .ok_or(SaveError::Corrupt("unknown topology"))?;
+        }
+        if let Some(sandbox) = save.get("sandbox") {
+            rules.sandbox = sandbox
+                .as_bool()
+                .ok_or(SaveError::Corrupt("sandbox should be true or false"))?;
+        }
+        if let Some(practice) = save.get("practice") {
+            rules.practice = practice
+                .as_bool()
+                .ok_or(SaveError::Corrupt("practice should be true or false"))?;
+        }
+        if let Some(stock) = save.get("stock") {
+            rules.stock = stock
+                .as_bool()
+                .ok_or(SaveError::Corrupt("stock should be true or false"))?;
+        }
+        if let Some(deck) = save.get("deck") {
+            rules.deck = deck
+                .as_str()
+                .and_then(|deck| deck.parse().ok())
+                .ok_or(SaveError::Corrupt("unknown deck"))?;
+        }
+        if let Some(draw_three) = save.get("draw_three") {
+            rules.draw_three = draw_three
+                .as_bool()
+                .ok_or(SaveError::Corrupt("draw_three should be true or false"))?;
+        }
+        if let Some(decay) = save.get("decay") {
+            rules.decay = decay
+                .as_bool()
+                .ok_or(SaveError::Corrupt("decay should be true or false"))?;
+        }
+        if let Some(reveal) = save.get("reveal") {
+            rules.reveal = reveal
+                .as_str()
+                .and_then(|reveal| reveal.parse().ok())
+                .ok_or(SaveError::Corrupt("unknown way of turning cards over"))?;
+        }
+        let camera = json_camera(save);
+
+        let sparse = version >= 4;
+        let columns = match save.get("column_count") {
+            Some(count) if sparse => count
+                .as_u64()
+                .ok_or(SaveError::Corrupt("column_count should be a number"))?
+                as usize,
+            _ if sparse => return Err(SaveError::Corrupt("column_count is missing")),
+            _ => array(save, "columns")?.len(),
+        };
+        let has_ids = save.get("placed").is_some();
+        let mut tableau = BTreeMap::new();
+        let mut column_ids = BTreeMap::new();
+        for (n, column) in array(save, "columns")?.iter().enumerate() {
+            // before version 4 every column was there, in order
+            let x = if sparse {
+                column
+                    .get("x")
+                    .and_then(Value::as_u64)
+                    .ok_or(SaveError::Corrupt("columns need an x"))? as usize
+            } else {
+                n
+            };
+            if x >= columns {
+                return Err(SaveError::Corrupt("column is past the end of the board"));
+            }
+            if tableau.contains_key(&x) {
+                return Err(SaveError::Corrupt("a column is there twice"));
+            }
+            if has_ids {
+                let ids = array(column, "ids")?.iter().map(json_id);
+                column_ids.insert(x, ids.collect::<Result<Vec<_>, _>>()?);
+            }
+            let under = column
+                .get("under")
+                .and_then(Value::as_u64)
+                .and_then(|under| under.try_into().ok())
+                .ok_or(SaveError::Corrupt("columns need a face down count"))?;
+            let visible_cards = array(column, "visible")?;
+            if visible_cards.len() > 13 {
+                return Err(SaveError::Corrupt("too many face up cards"));
+            }
+            let mut visible = CardStack::empty();
+            for card in visible_cards {
+                // ids are filled in at the end
+                visible.push(json_card(card)?, CardId::default());
+            }
+            let mut loaded = Column::from_parts(under, visible);
+            if column.get("turned_under").is_some() {
+                let cards = array(column, "turned_under")?.iter().map(json_card);
+                let ids = array(column, "turned_under_ids")?.iter().map(json_id);
+                if cards.len() != ids.len() {
+                    return Err(SaveError::Corrupt("there should be an id for every card"));
+                }
+                let turned_under = cards
+                    .zip(ids)
+                    .map(|(card, id)| Ok((card?, id?)))
+                    .collect::<Result<_, SaveError>>()?;
+                loaded = loaded
+                    .with_turned_under(turned_under)
+                    .map_err(SaveError::Corrupt)?;
+            }
+            tableau.insert(x, loaded);
+        }
+
+        // by their index, whatever order the file has them in, since that's
+        // the order their ids go in
+        let mut by_index = BTreeMap::new();
+        for foundation in array(save, "foundations")? {
+            let idx = foundation
+                .get("index")
+                .and_then(Value::as_u64)
+                .ok_or(SaveError::Corrupt("foundations need an index"))?;
+            if by_index.insert(idx as usize, foundation).is_some() {
+                return Err(SaveError::Corrupt("two foundations have the same index"));
+            }
+        }
+        let mut foundations = HashMap::new();
+        for (idx, foundation) in &by_index {
+            let card = foundation
+                .get("card")
+                .ok_or(SaveError::Corrupt("foundations need a card"))?;
+            foundations.insert(*idx, json_card(card)?);
+        }
+
+        let moves = match save.get("moves") {
+            Some(_) => array(save, "moves")?
+                .iter()
+                .map(json_move)
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let played = match save.get("played") {
+            Some(played) => Some(
+                played
+                    .as_f64()
+                    .ok_or(SaveError::Corrupt("played should be a number of seconds"))?,
+            ),
+            None => None,
+        };
+        let idle_pauses = save
+            .get("idle_pauses")
+            .and_then(Value::as_u64)
+            .map_or(0, |pauses| pauses as u32);
+        let reshuffles = save
+            .get("reshuffles")
+            .and_then(Value::as_u64)
+            .map_or(0, |used| used as u32);
+        let inserted = match save.get("inserted") {
+            Some(_) => array(save, "inserted")?
+                .iter()
+                .map(|x| {
+                    x.as_u64()
+                        .map(|x| x as usize)
+                        .filter(|x| *x < columns)
+                        .ok_or(SaveError::Corrupt(
+                            "inserted columns should be on the board",
+                        ))
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let drawn = save.get("drawn").and_then(Value::as_u64).unwrap_or(0);
+        let waste = match save.get("waste") {
+            Some(_) => array(save, "waste")?
+                .iter()
+                .map(json_card)
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let mut regions = BTreeMap::new();
+        if save.get("regions").is_some() {
+            for region in array(save, "regions")? {
+                let idx = region
+                    .get("region")
+                    .and_then(Value::as_u64)
+                    .ok_or(SaveError::Corrupt("regions need a number"))?;
+                let founded = region
+                    .get("founded")
+                    .and_then(Value::as_u64)
+                    .ok_or(SaveError::Corrupt("regions need a founded count"))?;
+                regions.insert(idx as usize, founded as u32);
+            }
+        }
+        let mut bookmarks = Vec::new();
+        if save.get("bookmarks").is_some() {
+            for bookmark in array(save, "bookmarks")? {
+                let name = bookmark
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or(SaveError::Corrupt("bookmarks need a name"))?;
+                bookmarks.push((name.to_owned(), json_camera(bookmark)));
+            }
+        }
+        // saves with ids have them for every card, or none at all
+        let ids = match save.get("placed") {
+            Some(placed) => Some(CardIds {
+                placed: placed
+                    .as_u64()
+                    .ok_or(SaveError::Corrupt("placed should be a number"))?,
+                columns: column_ids.into_values().collect(),
+                foundations: by_index
+                    .values()
+                    .map(|foundation| json_id(foundation.get("id").unwrap_or(&Value::Null)))
+                    .collect::<Result<_, _>>()?,
+                waste: array(save, "waste_ids")?
+                    .iter()
+                    .map(json_id)
+                    .collect::<Result<_, _>>()?,
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            game: Game::from_parts(seed, rules, columns, tableau, foundations)
+                .with_reshuffles(reshuffles)
+                .with_inserted(inserted)
+                .with_stock(drawn, waste)
+                .with_region_founded(regions)
+                .with_ids(ids)
+                .map_err(SaveError::Corrupt)?,
+            camera,
+            moves,
+            played,
+            idle_pauses,
+            bookmarks,
+        })
+    }
+
+    pub fn to_bytes(&self, compress: bool) -> Vec<u8> {
+        let mut payload = Vec::new();
+
+        let mut section = Vec::new();
+        section.extend(self.game.seed().to_le_bytes());
+        section.extend(self.camera[0].to_le_bytes());
+        section.extend(self.camera[1].to_le_bytes());
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.game.columns() as u64);
+        let changed = self.game.changed_columns();
+        write_varint(&mut section, changed.len() as u64);
+        let mut next = 0;
+        for &(x, column) in &changed {
+            write_varint(&mut section, (x - next) as u64);
+            next = x + 1;
+            write_varint(&mut section, column.under.into());
+            section.push(column.visible().len());
+            section.extend(column.visible().iter().map(BitCard::bits));
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        let mut foundations: Vec<_> = self.game.foundations().iter().collect();
+        foundations.sort_by_key(|(idx, _)| **idx);
+        write_varint(&mut section, foundations.len() as u64);
+        for (idx, card) in foundations {
+            write_varint(&mut section, *idx as u64);
+            section.push(card.bits());
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.moves.len() as u64);
+        for mv in &self.moves {
+            match *mv {
+                Move::Tableau { from, idx, to } => {
+                    section.push(0);
+                    write_varint(&mut section, from as u64);
+                    write_varint(&mut section, idx as u64);
+                    write_varint(&mut section, to as u64);
+                }
+                Move::Foundation { from, foundation } => {
+                    section.push(1);
+                    write_varint(&mut section, from as u64);
+                    write_varint(&mut section, foundation as u64);
+                }
+                Move::Reshuffle { column } => {
+                    section.push(2);
+                    write_varint(&mut section, column as u64);
+                }
+                Move::Insert { from, idx, at } => {
+                    section.push(3);
+                    write_varint(&mut section, from as u64);
+                    write_varint(&mut section, idx as u64);
+                    write_varint(&mut section, at as u64);
+                }
+                Move::Draw => section.push(4),
+                Move::Waste { to } => {
+                    section.push(5);
+                    write_varint(&mut section, to as u64);
+                }
+                Move::WasteFoundation { foundation } => {
+                    section.push(6);
+                    write_varint(&mut section, foundation as u64);
+                }
+            }
+        }
+        write_section(&mut payload, &section);
+
+        let topology = match self.game.rules().topology {
+            Topology::Line => 0,
+            Topology::Grid => 1,
+        };
+        write_section(&mut payload, &[topology]);
+
+        let mut flags = 0;
+        if self.game.rules().sandbox {
+            flags |= SANDBOX;
+        }
+        if self.game.rules().practice {
+            flags |= PRACTICE;
+        }
+        if self.game.rules().stock {
+            flags |= STOCK;
+        }
+        if self.game.rules().draw_three {
+            flags |= DRAW_THREE;
+        }
+        if self.game.rules().deck == Deck::Piquet {
+            flags |= PIQUET;
+        }
+        if self.game.rules().decay {
+            flags |= DECAY;
+        }
+        write_section(&mut payload, &[flags]);
+
+        let mut section = Vec::new();
+        if let Some(played) = self.played {
+            section.extend(played.to_le_bytes());
+            write_varint(&mut section, self.idle_pauses.into());
+        }
+        write_section(&mut payload, &section);
+
+        let reveal = match self.game.rules().reveal {
+            Reveal::One => vec![0],
+            Reveal::Chance(percent) => vec![1, percent],
+            Reveal::Many(count) => vec![2, count],
+        };
+        write_section(&mut payload, &reveal);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.game.reshuffles_used().into());
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.game.inserted().len() as u64);
+        let mut next = 0;
+        for x in self.game.inserted() {
+            write_varint(&mut section, (x - next) as u64);
+            next = x + 1;
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.game.drawn());
+        write_varint(&mut section, self.game.waste().len() as u64);
+        section.extend(self.game.waste().iter().map(|card| card.bits()));
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        let regions = self.game.regions_founded();
+        write_varint(&mut section, regions.len() as u64);
+        for (region, founded) in regions {
+            write_varint(&mut section, *region as u64);
+            write_varint(&mut section, (*founded).into());
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.bookmarks.len() as u64);
+        for (name, camera) in &self.bookmarks {
+            write_varint(&mut section, name.len() as u64);
+            section.extend(name.as_bytes());
+            section.extend(camera[0].to_le_bytes());
+            section.extend(camera[1].to_le_bytes());
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        write_varint(&mut section, self.game.placed());
+        for (_, column) in &changed {
+            for id in column.visible().ids() {
+                write_varint(&mut section, id.bits());
+            }
+        }
+        let mut foundation_ids: Vec<_> = self.game.foundation_ids().iter().collect();
+        foundation_ids.sort_by_key(|(idx, _)| **idx);
+        for (_, id) in foundation_ids {
+            write_varint(&mut section, id.bits());
+        }
+        for id in self.game.waste_ids() {
+            write_varint(&mut section, id.bits());
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        if let Some(world) = self.game.world() {
+            let dir = world.dir().to_string_lossy();
+            write_varint(&mut section, dir.len() as u64);
+            section.extend(dir.as_bytes());
+            let streamed = self.game.streamed_columns();
+            write_varint(&mut section, streamed.len() as u64);
+            let mut next = 0;
+            for (x, (under, visible)) in streamed {
+                write_varint(&mut section, (x - next) as u64);
+                next = x + 1;
+                write_varint(&mut section, under.into());
+                section.push(visible);
+            }
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        let decayed: Vec<_> = (changed.iter())
+            .filter(|(_, column)| !column.turned_under().is_empty())
+            .collect();
+        write_varint(&mut section, decayed.len() as u64);
+        let mut next = 0;
+        for (x, column) in decayed {
+            write_varint(&mut section, (x - next) as u64);
+            next = x + 1;
+            write_varint(&mut section, column.turned_under().len() as u64);
+            for (card, id) in column.turned_under() {
+                section.push(card.bits());
+                write_varint(&mut section, id.bits());
+            }
+        }
+        write_section(&mut payload, &section);
+
+        let mut section = Vec::new();
+        let decayed = self.game.streamed_decayed();
+        write_varint(&mut section, decayed.len() as u64);
+        let mut next = 0;
+        for (x, count) in decayed {
+            write_varint(&mut section, (x - next) as u64);
+            next = x + 1;
+            write_varint(&mut section, count.into());
+        }
+        write_section(&mut payload, &section);
+
+        let (flags, payload) = if compress {
+            (
+                COMPRESSED,
+                miniz_oxide::deflate::compress_to_vec(&payload, 6),
+            )
+        } else {
+            (0, payload)
+        };
+        // older versions can still read saves that don't stream, they just
+        // skip the section
+        let version: u16 = if self.game.world().is_some() { 3 } else { 2 };
+        let mut out = Vec::with_capacity(payload.len() + 11);
+        out.extend(MAGIC);
+        out.extend(version.to_le_bytes());
+        out.push(flags);
+        out.extend((payload.len() as u32).to_le_bytes());
+        out.extend(payload);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveError> {
+        if bytes.len() < 11 || &bytes[..4] != MAGIC {
+            return Err(SaveError::NotASave);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version > VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+        let flags = bytes[6];
+        let len = u32::from_le_bytes(bytes[7..11].try_into().unwrap()) as usize;
+        let payload = bytes
+            .get(11..11 + len)
+            .ok_or(SaveError::Corrupt("file is cut short"))?;
+        let payload = if flags & COMPRESSED != 0 {
+            miniz_oxide::inflate::decompress_to_vec(payload)
+                .map_err(|_| SaveError::Corrupt("could not decompress"))?
+        } else {
+            payload.to_vec()
+        };
+
+        let mut reader = Reader(&payload);
+        let mut section = Reader(reader.section()?);
+        let seed = u64::from_le_bytes(section.array()?);
+        let camera = [
+            f32::from_le_bytes(section.array()?),
+            f32::from_le_bytes(section.array()?),
+        ];
+
+        let mut section = Reader(reader.section()?);
+        let columns = section.varint()? as usize;
+        let changed = if version < 2 {
+            columns
+        } else {
+            section.varint()? as usize
+        };
+        let mut tableau = BTreeMap::new();
+        let mut next = 0;
+        for _ in 0..changed {
+            let x = if version < 2 {
+                next
+            } else {
+                next + section.varint()? as usize
+            };
+            if x >= columns {
+                return Err(SaveError::Corrupt("column is past the end of the board"));
+            }
+            next = x + 1;
+            let under = section
+                .varint()?
+                .try_into()
+                .map_err(|_| SaveError::Corrupt("too many face down cards"))?;
+            let len = section.byte()?;
+            if len > 13 {
+                return Err(SaveError::Corrupt("too many face up cards"));
+            }
+            let mut visible = CardStack::empty();
+            for _ in 0..len {
+                // ids are filled in at the end
+                visible.push(section.card()?, CardId::default());
+            }
+            tableau.insert(x, Column::from_parts(under, visible));
+        }
+
+        let mut section = Reader(reader.section()?);
+        let mut foundations = HashMap::new();
+        for _ in 0..section.varint()? {
+            let idx = section.varint()? as usize;
+            foundations.insert(idx, section.card()?);
+        }
+
+        let mut section = Reader(reader.section()?);
+        let mut moves = Vec::new();
+        for _ in 0..section.varint()? {
+            moves.push(match section.byte()? {
+                0 => Move::Tableau {
+                    from: section.varint()? as usize,
+                    idx: section.varint()? as usize,
+                    to: section.varint()? as usize,
+                },
+                1 => Move::Foundation {
+                    from: section.varint()? as usize,
+                    foundation: section.varint()? as usize,
+                },
+                2 => Move::Reshuffle {
+                    column: section.varint()? as usize,
+                },
+                3 => Move::Insert {
+                    from: section.varint()? as usize,
+                    idx: section.varint()? as usize,
+                    at: section.varint()? as usize,
+                },
+                4 => Move::Draw,
+                5 => Move::Waste {
+                    to: section.varint()? as usize,
+                },
+                6 => Move::WasteFoundation {
+                    foundation: section.varint()? as usize,
+                },
+                _ => return Err(SaveError::Corrupt("unknown kind of move")),
+            });
+        }
+
+        // sections from here on were added later, so they might be missing
+        let mut rules = Rules::default();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            rules.topology = match section.byte()? {
+                0 => Topology::Line,
+                1 => Topology::Grid,
+                _ => return Err(SaveError::Corrupt("unknown topology")),
+            };
+        }
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            let flags = section.byte()?;
+            rules.sandbox = flags & SANDBOX != 0;
+            rules.practice = flags & PRACTICE != 0;
+            rules.stock = flags & STOCK != 0;
+            rules.draw_three = flags & DRAW_THREE != 0;
+            rules.decay = flags & DECAY != 0;
+            if flags & PIQUET != 0 {
+                rules.deck = Deck::Piquet;
+            }
+        }
+        let mut played = None;
+        let mut idle_pauses = 0;
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            if !section.0.is_empty() {
+                played = Some(f64::from_le_bytes(section.array()?));
+                idle_pauses = section.varint()? as u32;
+            }
+        }
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            rules.reveal = match section.byte()? {
+                0 => Reveal::One,
+                1 => Reveal::Chance(section.byte()?),
+                2 => Reveal::Many(section.byte()?),
+                _ => return Err(SaveError::Corrupt("unknown way of turning cards over")),
+            };
+            if !rules.reveal.is_valid() {
+                return Err(SaveError::Corrupt("too many or too few cards turned over"));
+            }
+        }
+        let mut reshuffles = 0;
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            reshuffles = section.varint()? as u32;
+        }
+        let mut inserted = Vec::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            let mut next = 0;
+            for _ in 0..section.varint()? {
+                let x = next + section.varint()? as usize;
+                if x >= columns {
+                    return Err(SaveError::Corrupt("column is past the end of the board"));
+                }
+                inserted.push(x);
+                next = x + 1;
+            }
+        }
+        let mut drawn = 0;
+        let mut waste = Vec::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            drawn = section.varint()?;
+            for _ in 0..section.varint()? {
+                waste.push(section.card()?);
+            }
+        }
+        let mut regions = BTreeMap::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            for _ in 0..section.varint()? {
+                let region = section.varint()? as usize;
+                regions.insert(region, section.varint()? as u32);
+            }
+        }
+        let mut bookmarks = Vec::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            for _ in 0..section.varint()? {
+                let len = section.varint()? as usize;
+                let name = std::str::from_utf8(section.take(len)?)
+                    .map_err(|_| SaveError::Corrupt("bookmark names should be utf-8"))?;
+                let camera = [
+                    f32::from_le_bytes(section.array()?),
+                    f32::from_le_bytes(section.array()?),
+                ];
+                bookmarks.push((name.to_owned(), camera));
+            }
+        }
+        let mut ids = None;
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            let placed = section.varint()?;
+            let columns = tableau
+                .values()
+                .map(|column| {
+                    (0..column.visible().len())
+                        .map(|_| section.id())
+                        .collect::<Result<_, _>>()
+                })
+                .collect::<Result<_, _>>()?;
+            ids = Some(CardIds {
+                placed,
+                columns,
+                foundations: (0..foundations.len())
+                    .map(|_| section.id())
+                    .collect::<Result<_, _>>()?,
+                waste: (0..waste.len())
+                    .map(|_| section.id())
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+        let mut world = None;
+        let mut streamed = BTreeMap::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            if !section.0.is_empty() {
+                let len = section.varint()? as usize;
+                let dir = std::str::from_utf8(section.take(len)?)
+                    .map_err(|_| SaveError::Corrupt("the world directory should be utf-8"))?;
+                let mut next = 0;
+                for _ in 0..section.varint()? {
+                    let x = next + section.varint()? as usize;
+                    if x >= columns {
+                        return Err(SaveError::Corrupt("column is past the end of the board"));
+                    }
+                    next = x + 1;
+                    let under = section
+                        .varint()?
+                        .try_into()
+                        .map_err(|_| SaveError::Corrupt("too many face down cards"))?;
+                    streamed.insert(x, (under, section.byte()?));
+                }
+                if !streamed.is_empty() && !Path::new(dir).is_dir() {
+                    return Err(SaveError::Corrupt("the directory it streamed to is gone"));
+                }
+                world = Some(World::open(dir)?);
+            }
+        }
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            let mut next = 0;
+            for _ in 0..section.varint()? {
+                let x = next + section.varint()? as usize;
+                next = x + 1;
+                let turned_under = (0..section.varint()?)
+                    .map(|_| Ok((section.card()?, section.id()?)))
+                    .collect::<Result<_, SaveError>>()?;
+                let column = tableau.remove(&x).ok_or(SaveError::Corrupt(
+                    "decayed cards on a column that's as dealt",
+                ))?;
+                let column = column
+                    .with_turned_under(turned_under)
+                    .map_err(SaveError::Corrupt)?;
+                tableau.insert(x, column);
+            }
+        }
+        let mut streamed_decayed = BTreeMap::new();
+        if !reader.0.is_empty() {
+            let mut section = Reader(reader.section()?);
+            let mut next = 0;
+            for _ in 0..section.varint()? {
+                let x = next + section.varint()? as usize;
+                next = x + 1;
+                let count = section.varint()?;
+                let (under, _) = streamed.get(&x).ok_or(SaveError::Corrupt(
+                    "decayed cards on a column that isn't streamed",
+                ))?;
+                if count > u64::from(*under) {
+                    return Err(SaveError::Corrupt("more decayed cards than face down ones"));
+                }
+                streamed_decayed.insert(x, count as u32);
+            }
+        }
+
+        Ok(Self {
+            game: Game::from_parts(seed, rules, columns, tableau, foundations)
+                .with_reshuffles(reshuffles)
+                .with_inserted(inserted)
+                .with_stock(drawn, waste)
+                .with_region_founded(regions)
+                .with_world(world, streamed, streamed_decayed)
+                .with_ids(ids)
+                .map_err(SaveError::Corrupt)?,
+            camera,
+            moves,
+            played,
+            idle_pauses,
+            bookmarks,
+        })
+    }
+}
+
+fn array<'a>(value: &'a Value, key: &'static str) -> Result<&'a [Value], SaveError> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .ok_or(SaveError::Corrupt("a list is missing"))
+}
+
+/// the `camera` of `value`, or the start if it hasn't got one
+fn json_camera(value: &Value) -> [f32; 2] {
+    match value.get("camera").and_then(Value::as_array) {
+        Some([x, y]) => [x, y].map(|n| n.as_f64().unwrap_or(0.0) as f32),
+        _ => [0.0; 2],
+    }
+}
+
+fn json_card(card: &Value) -> Result<BitCard, SaveError> {
+    card.as_str()
+        .and_then(|card| card.parse().ok())
+        .ok_or(SaveError::Corrupt("cards look like AS, 10H or QD"))
+}
+
+fn json_id(id: &Value) -> Result<CardId, SaveError> {
+    id.as_str()
+        .and_then(|id| u64::from_str_radix(id, 16).ok())
+        .and_then(CardId::from_bits)
+        .ok_or(SaveError::Corrupt("card ids are hex in a string, like 1a8"))
+}
+
+fn json_move(mv: &Value) -> Result<Move, SaveError> {
+    let field = |key| {
+        mv.get(key)
+            .and_then(Value::as_u64)
+            .map(|n| n as usize)
+            .ok_or(SaveError::Corrupt("move is missing a field"))
+    };
+    match mv.get("kind").and_then(Value::as_str) {
+        Some("tableau") => Ok(Move::Tableau {
+            from: field("from")?,
+            idx: field("idx")?,
+            to: field("to")?,
+        }),
+        Some("foundation") => Ok(Move::Foundation {
+            from: field("from")?,
+            foundation: field("foundation")?,
+        }),
+        Some("reshuffle") => Ok(Move::Reshuffle {
+            column: field("column")?,
+        }),
+        Some("draw") => Ok(Move::Draw),
+        Some("waste") => Ok(Move::Waste { to: field("to")? }),
+        Some("waste_foundation") => Ok(Move::WasteFoundation {
+            foundation: field("foundation")?,
+        }),
+        Some("insert") => Ok(Move::Insert {
+            from: field("from")?,
+            idx: field("idx")?,
+            at: field("at")?,
+        }),
+        _ => Err(SaveError::Corrupt("unknown kind of move")),
+    }
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+pub(crate) fn write_section(out: &mut Vec<u8>, section: &[u8]) {
+    write_varint(out, section.len() as u64);
+    out.extend(section);
+}
+
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], SaveError> {
+        if self.0.len() < n {
+            return Err(SaveError::Corrupt("data is cut short"));
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8, SaveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], SaveError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64, SaveError> {
+        let mut n = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(SaveError::Corrupt("number is too long"))
+    }
+
+    pub(crate) fn card(&mut self) -> Result<BitCard, SaveError> {
+        BitCard::from_bits(self.byte()?).ok_or(SaveError::Corrupt("not a card"))
+    }
+
+    pub(crate) fn id(&mut self) -> Result<CardId, SaveError> {
+        CardId::from_bits(self.varint()?).ok_or(SaveError::Corrupt("not a card id"))
+    }
+
+    pub(crate) fn section(&mut self) -> Result<&'a [u8], SaveError> {
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{Origin, Suit};
+    use crate::game::tests::play;
+    use crate::tableau::Tableau;
+
+    fn played_save(game: Game, moves: Vec<Move>) -> SaveData {
+        SaveData {
+            game,
+            camera: [12.5, -3.0],
+            moves,
+            played: Some(93.25),
+            idle_pauses: 2,
+            bookmarks: vec![("start".to_owned(), [0.0, 0.0])],
+        }
+    }
+
+    /// the cards and ids of every changed column
+    fn changed(game: &Game) -> Vec<(usize, u32, Vec<BitCard>, Vec<CardId>)> {
+        (game.changed_columns().into_iter())
+            .map(|(x, column)| {
+                let cards = column.visible().iter().collect();
+                (x, column.under, cards, column.visible().ids().to_vec())
+            })
+            .collect()
+    }
+
+    fn assert_same(loaded: &SaveData, save: &SaveData) {
+        assert_eq!(loaded.game.seed(), save.game.seed());
+        assert_eq!(loaded.game.rules(), save.game.rules());
+        assert_eq!(loaded.game.columns(), save.game.columns());
+        assert_eq!(changed(&loaded.game), changed(&save.game));
+        assert_eq!(loaded.game.foundations(), save.game.foundations());
+        assert_eq!(loaded.game.foundation_ids(), save.game.foundation_ids());
+        assert_eq!(loaded.game.full_state_hash(), save.game.full_state_hash());
+        assert_eq!(loaded.camera, save.camera);
+        assert_eq!(loaded.moves, save.moves);
+        assert_eq!(loaded.played, save.played);
+        assert_eq!(loaded.idle_pauses, save.idle_pauses);
+        assert_eq!(loaded.bookmarks, save.bookmarks);
+        assert_eq!(loaded.to_bytes(false), save.to_bytes(false));
+    }
+
+    /// a save with the header for `version` around `sections`
+    fn fixture(version: u16, sections: &[&[u8]]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for section in sections {
+            write_section(&mut payload, section);
+        }
+        let mut out = MAGIC.to_vec();
+        out.extend(version.to_le_bytes());
+        out.push(0);
+        out.extend((payload.len() as u32).to_le_bytes());
+        out.extend(payload);
+        out
+    }
+
+    fn varints(numbers: &[u64]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for &n in numbers {
+            write_varint(&mut out, n);
+        }
+        out
+    }
+
+    fn card(number: u8, suit: Suit) -> BitCard {
+        BitCard::new(number, suit)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut game = Game::with_rules(
+            7,
+            50,
+            Rules {
+                decay: true,
+                ..Rules::default()
+            },
+        );
+        let moves = play(&mut game, 0..50, 60);
+        let decayed = (0..50).find(|&x| game.decay(x).is_some());
+        assert!(decayed.is_some(), "nothing to decay");
+        let save = played_save(game, moves);
+        assert!(save.game.changed_columns().len() > 1);
+        for compress in [false, true] {
+            let bytes = save.to_bytes(compress);
+            assert_eq!(bytes[4..6], 2u16.to_le_bytes());
+            assert_same(&SaveData::from_bytes(&bytes).unwrap(), &save);
+        }
+    }
+
+    #[test]
+    fn columns_come_back_at_their_gaps() {
+        let mut game = Game::new(3, 50);
+        // far apart, so the gaps between changed columns aren't all 0
+        let moves = [play(&mut game, 5..15, 10), play(&mut game, 30..45, 10)].concat();
+        let save = played_save(game, moves);
+        let xs: Vec<usize> = (save.game.changed_columns().iter())
+            .map(|(x, _)| *x)
+            .collect();
+        assert!(xs.iter().any(|&x| x >= 30), "{:?}", xs);
+        assert_same(&SaveData::from_bytes(&save.to_bytes(true)).unwrap(), &save);
+    }
+
+    #[test]
+    fn loads_version_1() {
+        // every column in order, without the count of changed ones or gaps
+        let mut columns = varints(&[2, 3]);
+        columns.extend([1, card(12, Suit::Spade).bits()]);
+        columns.extend(varints(&[0]));
+        columns.extend([2, card(4, Suit::Heart).bits(), card(3, Suit::Club).bits()]);
+        let mut foundations = varints(&[1, 0]);
+        foundations.push(card(0, Suit::Diamond).bits());
+        let mut first = 42u64.to_le_bytes().to_vec();
+        first.extend(1.0f32.to_le_bytes());
+        first.extend(2.0f32.to_le_bytes());
+        let moves = [&[1, 1][..], &varints(&[3, 0])].concat();
+        let bytes = fixture(1, &[&first, &columns, &foundations, &moves]);
+
+        let save = SaveData::from_bytes(&bytes).unwrap();
+        let game = &save.game;
+        assert_eq!(game.seed(), 42);
+        assert_eq!(game.columns(), 2);
+        assert_eq!(save.camera, [1.0, 2.0]);
+        assert_eq!(
+            save.moves,
+            [Move::Foundation {
+                from: 3,
+                foundation: 0
+            }]
+        );
+        assert_eq!(save.played, None);
+        assert_eq!(game.rules(), Rules::default());
+        let first = game.column(0);
+        assert_eq!(first.under, 3);
+        assert!(first.visible().iter().eq([card(12, Suit::Spade)]));
+        let second = game.column(1);
+        assert_eq!(second.under, 0);
+        assert!((second.visible().iter()).eq([card(4, Suit::Heart), card(3, Suit::Club)]));
+        assert_eq!(game.foundations()[&0], card(0, Suit::Diamond));
+        // saves from before ids don't know where their cards came from
+        assert!((second.visible().ids().iter()).all(|id| matches!(id.origin(), Origin::Unknown(_))));
+    }
+
+    #[test]
+    fn loads_version_2_gaps_and_ids() {
+        let first = [7u64.to_le_bytes().to_vec(), vec![0; 8]].concat();
+        // 10 columns, 2 changed: 3 after column 0 and 2 after that, so 3
+        // and 6
+        let mut columns = varints(&[10, 2, 3, 1]);
+        columns.extend([
+            2,
+            card(12, Suit::Spade).bits(),
+            card(11, Suit::Heart).bits(),
+        ]);
+        columns.extend(varints(&[2, 0]));
+        columns.extend([1, card(4, Suit::Diamond).bits()]);
+        let mut foundations = varints(&[1, 2]);
+        foundations.push(card(0, Suit::Club).bits());
+        let ids = [
+            CardId::dealt(3),
+            CardId::revealed(3, 1),
+            CardId::placed(0),
+            CardId::drawn(0),
+        ];
+        let mut id_section = varints(&[1]);
+        for id in ids {
+            write_varint(&mut id_section, id.bits());
+        }
+        let bytes = fixture(
+            2,
+            &[
+                &first,
+                &columns,
+                &foundations,
+                &varints(&[0]),
+                &[0],
+                &[0],
+                &[],
+                &[0],
+                &varints(&[0]),
+                &varints(&[0]),
+                &varints(&[0, 0]),
+                &varints(&[0]),
+                &varints(&[0]),
+                &id_section,
+            ],
+        );
+
+        let game = SaveData::from_bytes(&bytes).unwrap().game;
+        let xs: Vec<usize> = (game.changed_columns().iter()).map(|(x, _)| *x).collect();
+        assert_eq!(xs, [3, 6]);
+        let third = game.column(3);
+        assert_eq!(third.under, 1);
+        assert!((third.visible().iter()).eq([card(12, Suit::Spade), card(11, Suit::Heart)]));
+        // the ids go with the columns in order, then the foundations
+        assert_eq!(third.visible().ids(), &ids[..2]);
+        assert_eq!(game.column(6).visible().ids(), &ids[2..3]);
+        assert_eq!(game.foundation_ids()[&2], ids[3]);
+        assert_eq!(game.placed(), 1);
+    }
+
+    #[test]
+    fn bad_gaps_are_corrupt() {
+        let first = [7u64.to_le_bytes().to_vec(), vec![0; 8]].concat();
+        // the second column lands on 10, past the end
+        let mut columns = varints(&[10, 2, 3, 0]);
+        columns.push(0);
+        columns.extend(varints(&[6, 0]));
+        columns.push(0);
+        let bytes = fixture(2, &[&first, &columns, &varints(&[0]), &varints(&[0])]);
+        assert!(matches!(
+            SaveData::from_bytes(&bytes),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn out_of_range_reveals_are_corrupt() {
+        for reveal in [
+            Reveal::Chance(0),
+            Reveal::Chance(101),
+            Reveal::Many(0),
+            Reveal::Many(14),
+        ] {
+            let rules = Rules {
+                reveal,
+                ..Rules::default()
+            };
+            let save = played_save(Game::with_rules(7, 10, rules), Vec::new());
+            assert!(matches!(
+                SaveData::from_bytes(&save.to_bytes(false)),
+                Err(SaveError::Corrupt(_))
+            ));
+        }
+        let rules = Rules {
+            reveal: Reveal::Many(13),
+            ..Rules::default()
+        };
+        let save = played_save(Game::with_rules(7, 10, rules), Vec::new());
+        let loaded = SaveData::from_bytes(&save.to_bytes(false)).unwrap();
+        assert_eq!(loaded.game.rules().reveal, Reveal::Many(13));
+    }
+
+    #[test]
+    fn saving_replaces_the_file_whole() {
+        let dir = std::env::temp_dir().join(format!("ik-partial-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("game.sav");
+        std::fs::write(&path, b"an older save").unwrap();
+        let mut game = Game::new(7, 20);
+        let moves = play(&mut game, 0..20, 5);
+        let save = played_save(game, moves);
+        for format in [SaveFormat::Binary, SaveFormat::Json] {
+            save.save(&path, format).unwrap();
+            assert_same(&SaveData::load(&path).unwrap(), &save);
+            let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+            assert_eq!(files.len(), 1);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let bytes = fixture(VERSION + 1, &[]);
+        assert!(matches!(
+            SaveData::from_bytes(&bytes),
+            Err(SaveError::UnsupportedVersion(v)) if v == VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut game = Game::with_rules(
+            7,
+            50,
+            Rules {
+                decay: true,
+                ..Rules::default()
+            },
+        );
+        let moves = play(&mut game, 0..50, 60);
+        (0..50).find(|&x| game.decay(x).is_some()).unwrap();
+        let save = played_save(game, moves);
+        let text = format!("{:#}", save.to_json());
+        let loaded = SaveData::from_json(&text.parse().unwrap()).unwrap();
+        assert_same(&loaded, &save);
+    }
+
+    /// a json save of a game with two foundations, with `edit` done to
+    /// its list of them
+    fn edited_foundations(edit: impl FnOnce(&mut Vec<Value>)) -> (SaveData, Value) {
+        let rules = Rules {
+            sandbox: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_rules(7, 10, rules);
+        for (x, suit) in [(0, Suit::Heart), (1, Suit::Spade)] {
+            assert!(game.place(x, card(0, suit)));
+            game.apply(Move::Foundation {
+                from: x,
+                foundation: x,
+            })
+            .unwrap();
+        }
+        let save = played_save(game, Vec::new());
+        let mut json = save.to_json();
+        let Value::Object(fields) = &mut json else {
+            unreachable!();
+        };
+        let foundations = fields.iter_mut().find(|(key, _)| key == "foundations");
+        let Some((_, Value::Array(foundations))) = foundations else {
+            unreachable!();
+        };
+        edit(foundations);
+        (save, json)
+    }
+
+    #[test]
+    fn json_foundations_go_by_index() {
+        let (save, json) = edited_foundations(|foundations| foundations.reverse());
+        assert_same(&SaveData::from_json(&json).unwrap(), &save);
+
+        let (_, json) = edited_foundations(|foundations| {
+            let first = foundations[0].clone();
+            foundations[1] = first;
+        });
+        assert!(matches!(
+            SaveData::from_json(&json),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn loads_json_before_version_4() {
+        // every column in order, without x or column_count
+        let text = r#"{
+            "version": 3,
+            "seed": "42",
+            "camera": [1, 2],
+            "foundations": [{"index": 0, "card": "AD"}],
+            "columns": [
+                {"under": 3, "visible": ["KS"]},
+                {"under": 0, "visible": ["5H", "4C"]}
+            ],
+            "moves": []
+        }"#;
+        let save = SaveData::from_json(&text.parse().unwrap()).unwrap();
+        let game = &save.game;
+        assert_eq!(game.seed(), 42);
+        assert_eq!(game.columns(), 2);
+        assert_eq!(save.camera, [1.0, 2.0]);
+        assert_eq!(game.column(0).under, 3);
+        assert!((game.column(1).visible().iter()).eq([card(4, Suit::Heart), card(3, Suit::Club)]));
+        assert_eq!(game.foundations()[&0], card(0, Suit::Diamond));
+        // from version 4 on they need them
+        let sparse = text.replace("\"version\": 3", "\"version\": 4");
+        assert!(matches!(
+            SaveData::from_json(&sparse.parse().unwrap()),
+            Err(SaveError::Corrupt("column_count is missing"))
+        ));
+    }
+
+    #[test]
+    fn streamed_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ik-save-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let rules = Rules {
+            decay: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_rules(11, 3 * Tableau::CHUNK, rules);
+        game.stream_to(World::open(&dir).unwrap());
+        let mut moves = play(&mut game, 0..8, 20);
+        let decayed = (0..8).find(|&x| game.decay(x).is_some());
+        assert!(decayed.is_some(), "nothing to decay");
+        moves.extend(play(
+            &mut game,
+            2 * Tableau::CHUNK..2 * Tableau::CHUNK + 8,
+            20,
+        ));
+        // the first chunk goes out to disk, the last stays in the save
+        game.forget_outside(2 * Tableau::CHUNK..3 * Tableau::CHUNK);
+        let save = played_save(game, moves);
+        assert!(!save.game.streamed_columns().is_empty());
+        assert!(!save.game.streamed_decayed().is_empty());
+
+        let bytes = save.to_bytes(true);
+        assert_eq!(bytes[4..6], 3u16.to_le_bytes());
+        let loaded = SaveData::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.game.world().unwrap().dir(), dir);
+        assert_eq!(loaded.game.streamed_columns(), save.game.streamed_columns());
+        assert_eq!(loaded.game.streamed_decayed(), save.game.streamed_decayed());
+        assert_eq!(loaded.game.turned_over(), save.game.turned_over());
+        assert_same(&loaded, &save);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
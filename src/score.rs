@@ -0,0 +1,140 @@
+//! points for a game. standard scoring is the usual klondike one: 10 for
+//! every card put on a foundation and 5 for every card turned over, with
+//! reshuffles costing the 100 that going through the deck again does. the
+//! deck never runs out, so there's no bonus for finishing. cards never come
+//! back off the foundations here either, so the usual 15 point penalty for
+//! that has nothing to apply to.
+//!
+//! vegas scoring pays 5 for each card on a foundation against a 52 point
+//! buy-in, and each reshuffle buys in again.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::{Event, Game};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scoring {
+    #[default]
+    Standard,
+    Vegas,
+}
+
+impl Scoring {
+    /// what a game is worth before any moves
+    pub fn start(self) -> i64 {
+        match self {
+            Scoring::Standard => 0,
+            Scoring::Vegas => -52,
+        }
+    }
+
+    /// what `event` is worth
+    pub fn points(self, event: &Event) -> i64 {
+        match (self, event) {
+            (Scoring::Standard, Event::Founded { .. }) => 10,
+            (Scoring::Standard, Event::Revealed { .. }) => 5,
+            (Scoring::Standard, Event::Reshuffled { .. }) => -100,
+            (Scoring::Vegas, Event::Founded { .. }) => 5,
+            (Scoring::Vegas, Event::Reshuffled { .. }) => -52,
+            // it scored when it was revealed the first time
+            (_, Event::Moved(_) | Event::Drawn { .. } | Event::TurnedBack { .. })
+            | (Scoring::Vegas, Event::Revealed { .. }) => 0,
+        }
+    }
+
+    /// the score of a game going by its board, the same as adding up the
+    /// points of every event that got it there. for games loaded from a
+    /// save, which doesn't keep the events.
+    pub fn score(self, game: &Game) -> i64 {
+        let founded = game.founded() as i64;
+        let turned_over = game.turned_over() as i64;
+        let reshuffles = i64::from(game.reshuffles_used());
+        match self {
+            Scoring::Standard => 10 * founded + 5 * turned_over - 100 * reshuffles,
+            Scoring::Vegas => self.start() + 5 * founded - 52 * reshuffles,
+        }
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scoring::Standard => "standard",
+            Scoring::Vegas => "vegas",
+        })
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Scoring::Standard),
+            "vegas" => Ok(Scoring::Vegas),
+            _ => Err(format!("unknown scoring `{}`, try standard or vegas", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Move;
+    use crate::rules::Rules;
+
+    #[test]
+    fn decayed_cards_only_score_once() {
+        // the sandbox lets any card go anywhere, to get at the decay
+        let rules = Rules {
+            sandbox: true,
+            decay: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_rules(1, 10, rules);
+        let scoring = Scoring::Standard;
+        let mut score = scoring.start();
+        let mut events = Vec::new();
+        let mut apply = |game: &mut Game, mv| {
+            let happened = game.apply(mv).unwrap();
+            score += happened.iter().map(|e| scoring.points(e)).sum::<i64>();
+            events.extend(happened);
+        };
+        // turns cards over on columns 3 and 5, putting 5's on 3
+        apply(
+            &mut game,
+            Move::Tableau {
+                from: 3,
+                idx: 0,
+                to: 4,
+            },
+        );
+        apply(
+            &mut game,
+            Move::Tableau {
+                from: 5,
+                idx: 0,
+                to: 3,
+            },
+        );
+        let revealed = game.tableau()[3].visible().top();
+        // the card turned over decays and comes back up
+        assert_eq!(game.decay(3), Some(revealed));
+        apply(
+            &mut game,
+            Move::Tableau {
+                from: 3,
+                idx: 0,
+                to: 6,
+            },
+        );
+        assert!(events.contains(&Event::TurnedBack {
+            column: 3,
+            card: revealed
+        }));
+        assert_eq!(game.turned_over(), 2);
+        assert_eq!(score, 10);
+        assert_eq!(score, scoring.score(&game));
+    }
+}
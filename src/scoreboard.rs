@@ -0,0 +1,109 @@
+//! playing a daily or weekly challenge: the clock counting down along the
+//! top of the window, and the leaderboard once it runs out
+
+use infiniteklondike::challenge::{self, Challenge, Entry};
+use macroquad::prelude::*;
+
+use crate::toast;
+
+/// where challenge results are kept
+const LEADERBOARD_PATH: &str = "leaderboard.txt";
+
+pub struct Run {
+    pub challenge: Challenge,
+    /// when the game started, from `get_time`
+    started: f64,
+    /// the best games of this challenge and which one is this game, once
+    /// the time is up
+    results: Option<(Vec<Entry>, Option<usize>)>,
+}
+
+impl Run {
+    /// how many games the leaderboard shows
+    const SHOWN: usize = 10;
+
+    pub fn new(challenge: Challenge) -> Self {
+        Self {
+            challenge,
+            started: get_time(),
+            results: None,
+        }
+    }
+
+    /// seconds left to play. the clock keeps running while idle, it's a race.
+    fn time_left(&self) -> f64 {
+        (self.challenge.period.time_budget() - (get_time() - self.started)).max(0.0)
+    }
+
+    /// whether the time is up and no more moves can be made
+    pub fn is_over(&self) -> bool {
+        self.results.is_some()
+    }
+
+    /// puts the result on the leaderboard once the time runs out. returns
+    /// true on the frame that happens.
+    pub fn update(&mut self, founded: usize, moves: usize) -> bool {
+        if self.is_over() || self.time_left() > 0.0 {
+            return false;
+        }
+        let entry = Entry {
+            period: self.challenge.period,
+            name: self.challenge.name.clone(),
+            variant: self.challenge.rules().key(),
+            founded,
+            moves,
+        };
+        if let Err(e) = challenge::record(LEADERBOARD_PATH, &entry) {
+            toast::error(format!("could not write to {}: {}", LEADERBOARD_PATH, e));
+        }
+        let entries = challenge::leaderboard(LEADERBOARD_PATH, &self.challenge);
+        // ties with earlier games go under them
+        let rank = entries.iter().rposition(|other| *other == entry);
+        self.results = Some((entries, rank));
+        true
+    }
+
+    pub fn draw(&self) {
+        let left = self.time_left() as u64;
+        let next = self.challenge.time_left();
+        let label = format!(
+            "{}: {}:{:02} left to play, next one in {}d {:02}:{:02}:{:02}",
+            self.challenge,
+            left / 60,
+            left % 60,
+            next / (24 * 60 * 60),
+            next / (60 * 60) % 24,
+            next / 60 % 60,
+            next % 60
+        );
+        let width = measure_text(&label, None, 20, 1.0).width + 16.0;
+        let x = (screen_width() - width) / 2.0;
+        draw_rectangle(x, 0.0, width, 24.0, Color::new(0.0, 0.0, 0.0, 0.8));
+        draw_text(&label, x + 8.0, 17.0, 20.0, WHITE);
+
+        let Some((entries, rank)) = &self.results else {
+            return;
+        };
+        let shown = entries.len().min(Self::SHOWN);
+        let below = rank.filter(|rank| *rank >= Self::SHOWN);
+        let height = 56.0 + 20.0 * (shown + usize::from(below.is_some())) as f32;
+        let x = screen_width() / 2.0 - 150.0;
+        let y = (screen_height() - height) / 2.0;
+        draw_rectangle(x, y, 300.0, height, Color::new(0.0, 0.0, 0.0, 0.9));
+        draw_text("time's up", x + 12.0, y + 26.0, 24.0, WHITE);
+        for (n, entry) in entries.iter().take(Self::SHOWN).enumerate() {
+            let line = format!(
+                "{:>2}. {} cards, {} moves",
+                n + 1,
+                entry.founded,
+                entry.moves
+            );
+            let color = if Some(n) == *rank { YELLOW } else { GRAY };
+            draw_text(&line, x + 12.0, y + 52.0 + 20.0 * n as f32, 20.0, color);
+        }
+        if let Some(rank) = below {
+            let line = format!("you came {} of {}", rank + 1, entries.len());
+            draw_text(&line, x + 12.0, y + height - 4.0, 20.0, YELLOW);
+        }
+    }
+}
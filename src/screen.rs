@@ -0,0 +1,63 @@
+//! what the window is showing. most of the time that's the game being
+//! played, with everything else (the title menu, the pause menu, the options
+//! screen and so on) drawn over it and taking the input while the game waits
+//! underneath with its clock stopped. only `Playing` lets input through to
+//! the board.
+
+use crate::ui::{Menu, Prompt};
+use crate::{Asking, MenuAction};
+
+pub enum Screen {
+    /// the title screen, before the first game's dealt. see `title`.
+    Title(Menu<MenuAction>),
+    Playing,
+    /// the pause menu, or one it led to
+    Paused(Menu<MenuAction>),
+    /// the options screen
+    Settings(Menu<MenuAction>),
+    /// every card dealt to a region has gone on the foundations
+    WonRegion(Menu<MenuAction>),
+    /// something being typed in
+    Prompt(Asking, Prompt),
+    /// the map of the whole board, from shift+n
+    WorldMap,
+}
+
+impl Screen {
+    pub fn is_playing(&self) -> bool {
+        matches!(self, Screen::Playing)
+    }
+
+    /// the menu on screen, if it's one of the screens that are a menu
+    pub fn menu(&mut self) -> Option<&mut Menu<MenuAction>> {
+        match self {
+            Screen::Title(menu)
+            | Screen::Paused(menu)
+            | Screen::Settings(menu)
+            | Screen::WonRegion(menu) => Some(menu),
+            Screen::Playing | Screen::Prompt(..) | Screen::WorldMap => None,
+        }
+    }
+
+    /// where escape goes from here. the options screen goes back to the
+    /// pause menu it came from, and anything else back to the game.
+    pub fn back(&self) -> Screen {
+        match self {
+            Screen::Settings(_) => Screen::Paused(crate::pause_menu()),
+            _ => Screen::Playing,
+        }
+    }
+
+    /// draws the menu or prompt, over what's been drawn of the game. the
+    /// world map needs the game, so it's drawn by the tab.
+    pub fn draw(&self) {
+        match self {
+            Screen::Title(menu)
+            | Screen::Paused(menu)
+            | Screen::Settings(menu)
+            | Screen::WonRegion(menu) => menu.draw(),
+            Screen::Prompt(_, prompt) => prompt.draw(),
+            Screen::Playing | Screen::WorldMap => {}
+        }
+    }
+}
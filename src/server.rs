@@ -0,0 +1,113 @@
+//! serves the running game over http, for companion apps, stream overlays and
+//! keeping an eye on long runs from somewhere else.
+//!
+//! - `GET /state` is the whole board as json, as an observer sees it (see
+//!   [`infiniteklondike::json::Observed`])
+//! - `GET /events` is a server-sent event stream with one json event per move
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use infiniteklondike::json::Value;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+#[derive(Default)]
+struct Shared {
+    state: String,
+    listeners: Vec<Sender<String>>,
+}
+
+pub struct Server {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Server {
+    /// starts listening on `addr` in the background
+    pub fn start(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let accept_shared = shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = accept_shared.clone();
+                thread::spawn(move || {
+                    // a client hanging up halfway through isn't our problem
+                    let _ = handle(stream, &shared);
+                });
+            }
+        });
+        Ok(Self { shared })
+    }
+
+    pub fn publish_state(&self, state: &Value) {
+        self.shared.lock().unwrap().state = state.to_string();
+    }
+
+    pub fn publish_event(&self, event: &Value) {
+        let event = event.to_string();
+        // listeners that went away get dropped when sending to them fails
+        self.shared
+            .lock()
+            .unwrap()
+            .listeners
+            .retain(|listener| listener.send(event.clone()).is_ok());
+    }
+}
+
+fn handle(mut stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // skip the headers, nothing in them matters here
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    let mut words = request.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("GET"), Some("/state")) => {
+            let state = shared.lock().unwrap().state.clone();
+            respond(&mut stream, "200 OK", "application/json", &state)
+        }
+        (Some("GET"), Some("/events")) => {
+            let (sender, receiver) = mpsc::channel();
+            shared.lock().unwrap().listeners.push(sender);
+            stream_events(&mut stream, receiver)
+        }
+        (Some("GET"), Some("/")) => respond(
+            &mut stream,
+            "200 OK",
+            "text/plain",
+            "infinite klondike\n\nGET /state for the board, GET /events for moves as they happen\n",
+        ),
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "not found\n"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+fn stream_events(stream: &mut TcpStream, events: Receiver<String>) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n"
+    )?;
+    stream.flush()?;
+    for event in events {
+        write!(stream, "data: {}\n\n", event)?;
+        stream.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! everything the player picks about how games are set up and shown
+
+use std::fmt;
+use std::str::FromStr;
+
+use infiniteklondike::challenge::Period;
+use infiniteklondike::rules::Rules;
+use infiniteklondike::score::Scoring;
+
+use crate::assist::Assists;
+use crate::layout::Layout;
+use crate::style::Style;
+
+/// what a new game (or tab) starts with
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+    pub layout: Layout,
+    pub rules: Rules,
+    pub style: Style,
+    pub assists: Assists,
+    /// how many seconds without any input before the session timer stops
+    pub idle_after: f64,
+    /// start on today's or this week's challenge instead of a random deal.
+    /// only the first game, new tabs get random deals.
+    pub challenge: Option<Period>,
+    /// deal from this seed instead of a random one. like `challenge`, only
+    /// for the first game.
+    pub seed: Option<u64>,
+    pub grab: Grab,
+    pub scoring: Scoring,
+    /// keep as little around as possible, for small machines like a
+    /// raspberry pi: only the columns near the screen are kept generated,
+    /// and card history, sparks and sliding cards are off
+    pub low_memory: bool,
+    /// two players taking turns of this many seconds on the same board
+    pub hot_seat: Option<f64>,
+    /// how loud sound is, from 0 for none to 1
+    pub volume: f32,
+    /// how loud the music is, from 0 for none to 1 for as loud as the sound
+    /// effects
+    pub music: f32,
+}
+
+/// how cards are picked up and put down with the mouse
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Grab {
+    /// hold the button down to carry cards and let go over where they go
+    #[default]
+    Drag,
+    /// click to pick cards up, and click again to put them down
+    Click,
+}
+
+impl fmt::Display for Grab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Grab::Drag => "drag",
+            Grab::Click => "click",
+        })
+    }
+}
+
+impl FromStr for Grab {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drag" => Ok(Grab::Drag),
+            "click" => Ok(Grab::Click),
+            _ => Err(format!("unknown grab `{}`, try drag or click", s)),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            layout: Layout::default(),
+            rules: Rules::default(),
+            style: Style::default(),
+            assists: Assists::default(),
+            idle_after: 60.0,
+            challenge: None,
+            seed: None,
+            grab: Grab::default(),
+            scoring: Scoring::default(),
+            low_memory: false,
+            hot_seat: None,
+            volume: 1.0,
+            music: 0.5,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! generates a lot of seeds and measures what the deal looks like, so changes
+//! to how columns are generated can be compared with numbers instead of vibes.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::game::{Game, Move};
+
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+];
+const SUITS: [&str; 4] = ["diamond", "heart", "club", "spade"];
+
+#[derive(Default, Clone)]
+pub struct RegionStats {
+    /// legal moves right after the deal, summed over all seeds
+    pub moves: usize,
+    /// of those, how many go to a foundation
+    pub foundation_moves: usize,
+    /// seeds where the region had nothing to do at all
+    pub stuck: usize,
+}
+
+pub struct Report {
+    pub seeds: usize,
+    pub columns: usize,
+    pub region_size: usize,
+    /// how often each rank was dealt face up, ace first
+    pub ranks: [usize; 13],
+    /// how often each suit was dealt face up, indexed by `Suit as usize`
+    pub suits: [usize; 4],
+    pub regions: Vec<RegionStats>,
+}
+
+/// deals `seeds` consecutive seeds starting at `first_seed`, each `columns`
+/// wide, and tallies them up in regions of `region_size` columns
+pub fn simulate(first_seed: u64, seeds: usize, columns: usize, region_size: usize) -> Report {
+    let region_count = columns.div_ceil(region_size);
+    let mut report = Report {
+        seeds,
+        columns,
+        region_size,
+        ranks: [0; 13],
+        suits: [0; 4],
+        regions: vec![RegionStats::default(); region_count],
+    };
+    for seed in (first_seed..).take(seeds) {
+        let game = Game::new(seed, columns);
+        for (_, column) in game.tableau().iter() {
+            for card in column.visible().iter() {
+                report.ranks[card.number() as usize] += 1;
+                report.suits[card.suit() as usize] += 1;
+            }
+        }
+        for (region, stats) in report.regions.iter_mut().enumerate() {
+            let window = region_window(region, region_size, columns);
+            let moves = game.legal_moves(window);
+            stats.moves += moves.len();
+            stats.foundation_moves += moves
+                .iter()
+                .filter(|mv| matches!(mv, Move::Foundation { .. }))
+                .count();
+            if moves.is_empty() {
+                stats.stuck += 1;
+            }
+        }
+    }
+    report
+}
+
+fn region_window(region: usize, region_size: usize, columns: usize) -> Range<usize> {
+    region * region_size..((region + 1) * region_size).min(columns)
+}
+
+/// pearson's chi squared against a uniform distribution
+fn chi_squared(counts: &[usize]) -> f64 {
+    let total: usize = counts.iter().sum();
+    let expected = total as f64 / counts.len() as f64;
+    counts
+        .iter()
+        .map(|count| (*count as f64 - expected).powi(2) / expected)
+        .sum()
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total: usize = self.ranks.iter().sum();
+        let percent = |count: usize| 100.0 * count as f64 / total as f64;
+        writeln!(
+            f,
+            "{} seeds, {} columns each, {} face up cards",
+            self.seeds, self.columns, total
+        )?;
+        writeln!(f)?;
+        write!(f, "ranks:")?;
+        for (name, count) in RANKS.iter().zip(self.ranks) {
+            write!(f, " {}={:.2}%", name, percent(count))?;
+        }
+        writeln!(
+            f,
+            "\n  chi squared {:.2} (12 degrees of freedom, ~21.0 is p=0.05)",
+            chi_squared(&self.ranks)
+        )?;
+        write!(f, "suits:")?;
+        for (name, count) in SUITS.iter().zip(self.suits) {
+            write!(f, " {}={:.2}%", name, percent(count))?;
+        }
+        writeln!(
+            f,
+            "\n  chi squared {:.2} (3 degrees of freedom, ~7.81 is p=0.05)",
+            chi_squared(&self.suits)
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>12} {:>12} {:>12} {:>12}",
+            "columns", "moves/seed", "to found.", "stuck"
+        )?;
+        for (region, stats) in self.regions.iter().enumerate() {
+            let window = region_window(region, self.region_size, self.columns);
+            writeln!(
+                f,
+                "{:>12} {:>12.2} {:>12.2} {:>11.1}%",
+                format!("{}..{}", window.start, window.end),
+                stats.moves as f64 / self.seeds as f64,
+                stats.foundation_moves as f64 / self.seeds as f64,
+                100.0 * stats.stuck as f64 / self.seeds as f64
+            )?;
+        }
+        Ok(())
+    }
+}
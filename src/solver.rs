@@ -0,0 +1,234 @@
+//! a depth first search over a window of the tableau, looking for the move
+//! sequence that gets the most cards onto foundations.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use crate::game::{Game, Move};
+
+pub struct Solution {
+    /// cards on foundations at the end of `moves`
+    pub founded: usize,
+    pub moves: Vec<Move>,
+    /// how many distinct positions were looked at
+    pub explored: usize,
+    /// true if the whole search space was covered, so `founded` is the best possible
+    pub exhausted: bool,
+}
+
+struct Frame {
+    game: Game,
+    moves: Vec<Move>,
+}
+
+/// searches the columns in `window` for the best sequence of moves, giving up
+/// after looking at `max_states` positions.
+pub fn solve(game: &Game, window: Range<usize>, max_states: usize) -> Solution {
+    let mut game = game.clone();
+    game.ensure_range(window.clone());
+    let mut best = Solution {
+        founded: game.founded(),
+        moves: Vec::new(),
+        explored: 0,
+        exhausted: true,
+    };
+    let mut seen = HashSet::new();
+    // the search is iterative since the path can get a lot deeper than the stack
+    let mut path: Vec<Move> = Vec::new();
+    let mut stack = Vec::new();
+    // nothing outside the window changes, so it's all that needs hashing
+    seen.insert(game.window_hash(window.clone()));
+    stack.push(Frame {
+        moves: candidates(&game, &window),
+        game,
+    });
+    while let Some(frame) = stack.last_mut() {
+        let mv = match frame.moves.pop() {
+            Some(mv) => mv,
+            None => {
+                stack.pop();
+                path.pop();
+                continue;
+            }
+        };
+        if seen.len() >= max_states {
+            best.exhausted = false;
+            break;
+        }
+        let mut next = frame.game.clone();
+        next.apply(mv).unwrap();
+        if !seen.insert(next.window_hash(window.clone())) {
+            continue;
+        }
+        path.push(mv);
+        if next.founded() > best.founded {
+            best.founded = next.founded();
+            best.moves = path.clone();
+        }
+        stack.push(Frame {
+            moves: candidates(&next, &window),
+            game: next,
+        });
+    }
+    best.explored = seen.len();
+    best
+}
+
+/// the moves worth trying from `game`, best last since they get popped off the end
+fn candidates(game: &Game, window: &Range<usize>) -> Vec<Move> {
+    let mut moves = game.legal_moves(window.clone());
+    moves.retain(|mv| !is_pointless(game, *mv));
+    moves.sort_by_key(|mv| std::cmp::Reverse(priority(game, *mv)));
+    moves
+}
+
+/// moving a whole column with nothing under it onto an empty column changes nothing
+fn is_pointless(game: &Game, mv: Move) -> bool {
+    match mv {
+        Move::Tableau { from, idx, to } => {
+            idx == 0 && game.tableau()[from].under == 0 && game.tableau()[to].is_visible_empty()
+        }
+        Move::Foundation { .. }
+        | Move::Reshuffle { .. }
+        | Move::Insert { .. }
+        | Move::Draw
+        | Move::Waste { .. }
+        | Move::WasteFoundation { .. } => false,
+    }
+}
+
+/// lower goes first: foundation plays, then moves that reveal a card, then the rest
+fn priority(game: &Game, mv: Move) -> u8 {
+    match mv {
+        Move::Foundation { .. } | Move::WasteFoundation { .. } => 0,
+        Move::Tableau { from, idx: 0, .. } if game.tableau()[from].under > 0 => 1,
+        Move::Tableau { .. }
+        | Move::Reshuffle { .. }
+        | Move::Insert { .. }
+        | Move::Draw
+        | Move::Waste { .. } => 2,
+    }
+}
+
+/// the moves in `window` that are the only useful thing the column they come
+/// from can do. useful means putting its top card on a foundation, or moving
+/// all of its face up cards off to turn over the next face down one. when a
+/// column has exactly one of those there's nothing to decide, so the player
+/// can have them made for them.
+pub fn forced_moves(game: &Game, window: Range<usize>) -> Vec<Move> {
+    let mut by_column: BTreeMap<usize, Vec<Move>> = BTreeMap::new();
+    for mv in game.legal_moves(window) {
+        let from = match mv {
+            Move::Foundation { from, .. } => from,
+            Move::Tableau { from, idx: 0, .. } if game.tableau()[from].under > 0 => from,
+            Move::Tableau { .. }
+            | Move::Reshuffle { .. }
+            | Move::Insert { .. }
+            | Move::Draw
+            | Move::Waste { .. }
+            | Move::WasteFoundation { .. } => continue,
+        };
+        by_column.entry(from).or_default().push(mv);
+    }
+    by_column
+        .into_values()
+        .filter(|moves| moves.len() == 1)
+        .map(|moves| moves[0])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{BitCard, CardId, CardStack, Column, Suit};
+    use crate::rules::Rules;
+    use std::collections::HashMap;
+
+    /// a board of just these columns, each with the given face down cards
+    /// and face up cards, the top one last
+    fn position(columns: &[(u32, &[BitCard])]) -> Game {
+        let mut placed = 0;
+        let changed = (columns.iter().enumerate())
+            .map(|(x, (under, cards))| {
+                let mut visible = CardStack::empty();
+                for card in cards.iter() {
+                    visible.push(*card, CardId::placed(placed));
+                    placed += 1;
+                }
+                (x, Column::from_parts(*under, visible))
+            })
+            .collect();
+        let mut game =
+            Game::from_parts(1, Rules::default(), columns.len(), changed, HashMap::new());
+        game.ensure_range(0..columns.len());
+        game
+    }
+
+    fn heart(number: u8) -> BitCard {
+        BitCard::new(number, Suit::Heart)
+    }
+
+    fn spade(number: u8) -> BitCard {
+        BitCard::new(number, Suit::Spade)
+    }
+
+    #[test]
+    fn finds_every_card_that_can_be_founded() {
+        let game = position(&[
+            (0, &[heart(1), heart(0)]),
+            (0, &[heart(2)]),
+            (0, &[spade(12)]),
+        ]);
+        let solution = solve(&game, 0..3, 1000);
+        assert_eq!(solution.founded, 3);
+        assert!(solution.exhausted);
+        let mut after = game.clone();
+        for mv in &solution.moves {
+            after.apply(*mv).unwrap();
+        }
+        assert_eq!(after.founded(), 3);
+
+        // cut off before it's looked at everything
+        assert!(!solve(&game, 0..3, 1).exhausted);
+    }
+
+    #[test]
+    fn dead_position_runs_out_of_moves() {
+        let game = position(&[(0, &[heart(12)]), (0, &[heart(8)]), (0, &[heart(4)])]);
+        let solution = solve(&game, 0..3, 1000);
+        assert_eq!(solution.founded, 0);
+        assert!(solution.moves.is_empty());
+        assert!(solution.exhausted);
+        assert_eq!(solution.explored, 1);
+        assert!(forced_moves(&game, 0..3).is_empty());
+    }
+
+    #[test]
+    fn forced_moves_are_the_only_useful_ones() {
+        let game = position(&[
+            // the ace can only go up
+            (0, &[heart(0)]),
+            // the queen can only go on the first king, turning a card over
+            (1, &[spade(11)]),
+            (0, &[heart(12)]),
+            // the jack could go on either black queen
+            (1, &[heart(10)]),
+            (0, &[spade(11)]),
+        ]);
+        let forced = forced_moves(&game, 0..5);
+        assert_eq!(
+            forced,
+            [
+                Move::Foundation {
+                    from: 0,
+                    foundation: 0
+                },
+                Move::Tableau {
+                    from: 1,
+                    idx: 0,
+                    to: 2
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! numbers about how the current session is going, shown with i, and which
+//! columns it's been spent on, shown with h. the same numbers are added up
+//! over every session ever played in `stats.txt` and shown under them.
+//!
+//! the clock only runs while someone is playing. once there's been no input
+//! for a while (a minute unless `--idle-after` says otherwise) it stops, and
+//! the time spent away doesn't count.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use infiniteklondike::game::{Event, Game, Move};
+use infiniteklondike::records::MILESTONE;
+use infiniteklondike::rules::Rules;
+use macroquad::prelude::*;
+
+pub struct Session {
+    /// how many seconds have been played, not counting time spent idle
+    played: f64,
+    /// when `tick` was last called, from `get_time`
+    last_tick: f64,
+    /// when the last input came in, from `get_time`
+    last_input: f64,
+    /// how long without input before the clock stops, in seconds
+    idle_after: f64,
+    /// how many times the clock stopped because nobody was playing
+    idle_pauses: u32,
+    /// when each card went on a foundation, in seconds into the session
+    founded: Vec<f64>,
+    moves: usize,
+    /// how many face down cards were turned over
+    revealed: usize,
+    /// one past the furthest column played on
+    furthest: usize,
+    /// how many moves each column was part of
+    activity: HashMap<usize, u32>,
+    /// the quickest `MILESTONE` cards in a row went on foundations, in
+    /// seconds
+    fastest: Option<f64>,
+}
+
+impl Session {
+    const WIDTH: f32 = 260.0;
+    const GRAPH_HEIGHT: f32 = 48.0;
+    /// the graph only shows this many of the latest minutes
+    const MINUTES_SHOWN: usize = 30;
+
+    pub fn new(idle_after: f64) -> Self {
+        let now = get_time();
+        Self {
+            played: 0.0,
+            last_tick: now,
+            last_input: now,
+            idle_after,
+            idle_pauses: 0,
+            founded: Vec::new(),
+            moves: 0,
+            revealed: 0,
+            furthest: 0,
+            activity: HashMap::new(),
+            fastest: None,
+        }
+    }
+
+    /// moves the clock along. call once a frame, saying whether there was
+    /// any input.
+    pub fn tick(&mut self, input: bool) {
+        let now = get_time();
+        let was_idle = self.is_idle();
+        if input {
+            self.last_input = now;
+        }
+        // time is counted up to the moment the player went idle, not up to
+        // when that was noticed
+        let counted_until = now.min(self.last_input + self.idle_after);
+        if !was_idle {
+            let played = (counted_until - self.last_tick).max(0.0);
+            self.played += played;
+            with_totals(|totals| totals.played += played);
+        }
+        if !was_idle && self.is_idle() {
+            self.idle_pauses += 1;
+        }
+        self.last_tick = now;
+    }
+
+    /// stops the clock straight away, for when the game goes out of sight.
+    /// it starts again with the next input.
+    pub fn pause(&mut self) {
+        self.tick(false);
+        self.last_input = f64::NEG_INFINITY;
+    }
+
+    /// whether the clock is stopped because nobody is playing
+    pub fn is_idle(&self) -> bool {
+        get_time() - self.last_input > self.idle_after
+    }
+
+    pub fn idle_after(&self) -> f64 {
+        self.idle_after
+    }
+
+    pub fn idle_pauses(&self) -> u32 {
+        self.idle_pauses
+    }
+
+    /// picks up the clock from a save. the graph starts over, since it can't
+    /// tell when the saved game's cards were founded.
+    pub fn resume(&mut self, played: f64, idle_pauses: u32) {
+        self.played = played;
+        self.idle_pauses = idle_pauses;
+        self.founded.clear();
+    }
+
+    /// keeps track of what a move did, leaving `game` as it is now
+    pub fn record(&mut self, events: &[Event], game: &Game) {
+        let now = self.elapsed();
+        self.furthest = self.furthest.max(game.explored());
+        with_totals(|totals| totals.add(events, game.explored()));
+        for event in events {
+            match event {
+                Event::Moved(mv) => {
+                    self.moves += 1;
+                    let columns = match *mv {
+                        Move::Tableau { from, to, .. } => vec![from, to],
+                        Move::Insert { from, at, .. } => vec![from, at],
+                        Move::Foundation { from, .. } => vec![from],
+                        Move::Reshuffle { column } | Move::Waste { to: column } => vec![column],
+                        Move::Draw | Move::WasteFoundation { .. } => vec![],
+                    };
+                    for column in columns {
+                        *self.activity.entry(column).or_default() += 1;
+                    }
+                }
+                Event::Founded { .. } => {
+                    self.founded.push(now);
+                    if let Some(first) = self.founded.len().checked_sub(MILESTONE) {
+                        let took = now - self.founded[first];
+                        self.fastest = Some(self.fastest.map_or(took, |fastest| fastest.min(took)));
+                    }
+                }
+                Event::Revealed { .. } => self.revealed += 1,
+                Event::TurnedBack { .. } | Event::Reshuffled { .. } | Event::Drawn { .. } => {}
+            }
+        }
+    }
+
+    /// how many moves `column` was part of
+    pub fn activity(&self, column: usize) -> u32 {
+        self.activity.get(&column).copied().unwrap_or(0)
+    }
+
+    /// the most moves any one column was part of
+    pub fn most_activity(&self) -> u32 {
+        self.activity.values().copied().max().unwrap_or(0)
+    }
+
+    /// the quickest any `MILESTONE` cards in a row went on foundations this
+    /// session, in seconds
+    pub fn fastest(&self) -> Option<f64> {
+        self.fastest
+    }
+
+    /// how long the session has been played, in seconds
+    pub fn elapsed(&self) -> f64 {
+        self.played
+    }
+
+    /// how many cards went on foundations in each minute of the session
+    fn per_minute(&self) -> Vec<usize> {
+        let mut minutes = vec![0; (self.elapsed() / 60.0) as usize + 1];
+        for time in &self.founded {
+            minutes[(time / 60.0) as usize] += 1;
+        }
+        minutes
+    }
+
+    /// draws the panel, headed with the variant of the rules being played
+    pub fn draw(&self, rules: Rules) {
+        let x = 8.0;
+        let y = 8.0;
+        draw_rectangle(
+            x,
+            y,
+            Self::WIDTH,
+            Self::GRAPH_HEIGHT + 174.0,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        let minutes = self.elapsed() / 60.0;
+        let lines = [
+            format!(
+                "{} played{}, {} moves",
+                clock(self.elapsed()),
+                if self.is_idle() { " (paused)" } else { "" },
+                self.moves
+            ),
+            format!(
+                "{} cards founded, {:.1} a minute",
+                self.founded.len(),
+                self.founded.len() as f64 / minutes.max(1.0)
+            ),
+            format!(
+                "{} turned over, furthest column {}",
+                self.revealed, self.furthest
+            ),
+            format!("rules: {}", rules),
+        ];
+        for (n, line) in lines.iter().enumerate() {
+            draw_text(line, x + 8.0, y + 20.0 + 18.0 * n as f32, 18.0, WHITE);
+        }
+
+        // a bar for each minute, the latest on the right
+        let per_minute = self.per_minute();
+        let shown = &per_minute[per_minute.len().saturating_sub(Self::MINUTES_SHOWN)..];
+        let most = shown.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let graph_x = x + 8.0;
+        let graph_y = y + 92.0;
+        let bar_width = (Self::WIDTH - 16.0) / Self::MINUTES_SHOWN as f32;
+        draw_line(
+            graph_x,
+            graph_y + Self::GRAPH_HEIGHT,
+            graph_x + Self::WIDTH - 16.0,
+            graph_y + Self::GRAPH_HEIGHT,
+            1.0,
+            WHITE,
+        );
+        let first = Self::MINUTES_SHOWN - shown.len();
+        for (n, count) in shown.iter().enumerate() {
+            let height = Self::GRAPH_HEIGHT * *count as f32 / most;
+            draw_rectangle(
+                graph_x + bar_width * (first + n) as f32,
+                graph_y + Self::GRAPH_HEIGHT - height,
+                bar_width - 1.0,
+                height,
+                WHITE,
+            );
+        }
+        draw_text(
+            &format!("cards a minute, most {}", most as usize),
+            x + 8.0,
+            graph_y + Self::GRAPH_HEIGHT + 18.0,
+            16.0,
+            GRAY,
+        );
+
+        let totals = with_totals(|totals| *totals);
+        let lines = [
+            format!(
+                "all time: {} played, {} moves",
+                clock(totals.played),
+                totals.moves
+            ),
+            format!(
+                "{} founded, {} turned over",
+                totals.founded, totals.revealed
+            ),
+            format!("furthest column {}", totals.furthest),
+        ];
+        let totals_y = graph_y + Self::GRAPH_HEIGHT + 44.0;
+        for (n, line) in lines.iter().enumerate() {
+            draw_text(line, x + 8.0, totals_y + 18.0 * n as f32, 18.0, WHITE);
+        }
+    }
+}
+
+/// where the totals over every session are kept
+const TOTALS_PATH: &str = "stats.txt";
+
+/// the numbers of every session ever played added up, kept in
+/// `TOTALS_PATH` with one `name value` line each
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub moves: u64,
+    /// face down cards turned over
+    pub revealed: u64,
+    /// cards put on foundations
+    pub founded: u64,
+    /// one past the furthest column played on in any game
+    pub furthest: usize,
+    /// seconds played, not counting time spent idle
+    pub played: f64,
+}
+
+impl Stats {
+    /// reads the totals from `TOTALS_PATH`, starting from nothing if there
+    /// aren't any yet. lines that can't be read are left out.
+    fn load() -> Self {
+        let text = std::fs::read_to_string(TOTALS_PATH).unwrap_or_default();
+        let mut stats = Self::default();
+        for line in text.lines() {
+            let mut words = line.split_whitespace();
+            let (Some(name), Some(value)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match name {
+                "moves" => stats.moves = value.parse().unwrap_or(stats.moves),
+                "revealed" => stats.revealed = value.parse().unwrap_or(stats.revealed),
+                "founded" => stats.founded = value.parse().unwrap_or(stats.founded),
+                "furthest" => stats.furthest = value.parse().unwrap_or(stats.furthest),
+                "played" => {
+                    stats.played = value
+                        .parse()
+                        .ok()
+                        .filter(|seconds: &f64| seconds.is_finite())
+                        .unwrap_or(stats.played)
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// adds what a move did, leaving one past the furthest column played
+    /// on at `explored`
+    fn add(&mut self, events: &[Event], explored: usize) {
+        self.furthest = self.furthest.max(explored);
+        for event in events {
+            match event {
+                Event::Moved(_) => self.moves += 1,
+                Event::Revealed { .. } => self.revealed += 1,
+                Event::Founded { .. } => self.founded += 1,
+                Event::TurnedBack { .. } | Event::Reshuffled { .. } | Event::Drawn { .. } => {}
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "moves {}", self.moves)?;
+        writeln!(f, "revealed {}", self.revealed)?;
+        writeln!(f, "founded {}", self.founded)?;
+        writeln!(f, "furthest {}", self.furthest)?;
+        writeln!(f, "played {}", self.played)
+    }
+}
+
+thread_local! {
+    /// the totals, read from the file the first time they're needed, and
+    /// whether they changed since they were last written
+    static TOTALS: RefCell<Option<(Stats, bool)>> = const { RefCell::new(None) };
+}
+
+fn with_totals<T>(f: impl FnOnce(&mut Stats) -> T) -> T {
+    TOTALS.with(|totals| {
+        let mut totals = totals.borrow_mut();
+        let (stats, changed) = totals.get_or_insert_with(|| (Stats::load(), false));
+        let before = *stats;
+        let out = f(stats);
+        *changed |= *stats != before;
+        out
+    })
+}
+
+/// writes the totals down, if anything was added since they last were.
+/// called along with autosaves and on the way out.
+pub fn save_totals() {
+    TOTALS.with(|totals| {
+        if let Some((stats, changed)) = totals.borrow_mut().as_mut() {
+            if !*changed {
+                return;
+            }
+            match std::fs::write(TOTALS_PATH, stats.to_string()) {
+                Ok(()) => *changed = false,
+                Err(e) => warn!("could not write {}: {}", TOTALS_PATH, e),
+            }
+        }
+    });
+}
+
+/// `seconds` as minutes and seconds, like `12:05`
+pub fn clock(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,364 @@
+//! how the board is coloured
+
+use std::fmt;
+use std::str::FromStr;
+
+use infiniteklondike::cards::{BitCard, Suit};
+use macroquad::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Style {
+    /// pure black and white, for e-ink and reflective screens. red cards get
+    /// a dotted pattern instead of a colour, there are no see-through
+    /// shades, and nothing should ever animate.
+    pub monochrome: bool,
+    /// what's drawn big on each card to tell the suits apart, on top of the
+    /// little pip in the corner
+    pub suit_marks: SuitMarks,
+    /// every suit its own colour, clubs green and diamonds blue as well as
+    /// red hearts and white spades, so they're not just told apart by red
+    pub four_colors: bool,
+    /// the suit's shape small in the top right corner of each card, next to
+    /// the rank, for telling suits apart at a glance
+    pub corner_badges: bool,
+    /// a badge on the top card of each column saying how long the run
+    /// ending there is, coloured by length
+    pub run_badges: bool,
+    pub card_back: CardBack,
+    pub table: Table,
+}
+
+/// which of the card backs in the atlas face down cards get
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardBack {
+    /// the patterned one with the infinity sign in the middle
+    #[default]
+    Pattern,
+    /// solid, with just the infinity sign
+    Infinity,
+    Stripes,
+    Checks,
+}
+
+impl CardBack {
+    pub const ALL: [CardBack; 4] = [
+        CardBack::Pattern,
+        CardBack::Infinity,
+        CardBack::Stripes,
+        CardBack::Checks,
+    ];
+}
+
+impl fmt::Display for CardBack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CardBack::Pattern => "pattern",
+            CardBack::Infinity => "infinity",
+            CardBack::Stripes => "stripes",
+            CardBack::Checks => "checks",
+        })
+    }
+}
+
+impl FromStr for CardBack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pattern" => Ok(CardBack::Pattern),
+            "infinity" => Ok(CardBack::Infinity),
+            "stripes" => Ok(CardBack::Stripes),
+            "checks" => Ok(CardBack::Checks),
+            _ => Err(format!(
+                "unknown card back `{}`, try pattern, infinity, stripes or checks",
+                s
+            )),
+        }
+    }
+}
+
+/// the colour of the table the cards are laid out on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Table {
+    #[default]
+    Black,
+    /// green baize
+    Felt,
+    Navy,
+    Wine,
+}
+
+impl Table {
+    pub const ALL: [Table; 4] = [Table::Black, Table::Felt, Table::Navy, Table::Wine];
+
+    fn color(self) -> Color {
+        match self {
+            Table::Black => BLACK,
+            Table::Felt => Color::new(0.05, 0.28, 0.12, 1.0),
+            Table::Navy => Color::new(0.04, 0.08, 0.25, 1.0),
+            Table::Wine => Color::new(0.25, 0.04, 0.09, 1.0),
+        }
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Table::Black => "black",
+            Table::Felt => "felt",
+            Table::Navy => "navy",
+            Table::Wine => "wine",
+        })
+    }
+}
+
+impl FromStr for Table {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "black" => Ok(Table::Black),
+            "felt" => Ok(Table::Felt),
+            "navy" => Ok(Table::Navy),
+            "wine" => Ok(Table::Wine),
+            _ => Err(format!(
+                "unknown table `{}`, try black, felt, navy or wine",
+                s
+            )),
+        }
+    }
+}
+
+/// a way of showing suits for people who can't make out the pips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SuitMarks {
+    /// just the pips from the atlas
+    #[default]
+    Pips,
+    /// C, D, H or S
+    Letters,
+    /// a circle for clubs, a diamond for diamonds, a triangle pointing down
+    /// for hearts and one pointing up for spades
+    Shapes,
+}
+
+impl fmt::Display for SuitMarks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SuitMarks::Pips => "pips",
+            SuitMarks::Letters => "letters",
+            SuitMarks::Shapes => "shapes",
+        })
+    }
+}
+
+impl FromStr for SuitMarks {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pips" => Ok(SuitMarks::Pips),
+            "letters" => Ok(SuitMarks::Letters),
+            "shapes" => Ok(SuitMarks::Shapes),
+            _ => Err(format!(
+                "unknown suit marks `{}`, try pips, letters or shapes",
+                s
+            )),
+        }
+    }
+}
+
+impl Style {
+    /// what's behind the cards. always black in monochrome, e-ink can't
+    /// show anything else.
+    pub fn table_color(self) -> Color {
+        if self.monochrome {
+            BLACK
+        } else {
+            self.table.color()
+        }
+    }
+
+    /// what the rank and suit of a card are tinted
+    pub fn suit_color(self, card: BitCard) -> Color {
+        if self.monochrome {
+            return WHITE;
+        }
+        match card.suit() {
+            Suit::Club if self.four_colors => Color::new(0.2, 0.8, 0.3, 1.0),
+            Suit::Diamond if self.four_colors => Color::new(0.3, 0.6, 1.0, 1.0),
+            _ if card.is_red() => RED,
+            _ => WHITE,
+        }
+    }
+
+    /// the colours of celebrations. monochrome doesn't have any.
+    pub fn confetti(self) -> &'static [Color] {
+        &[RED, WHITE, GOLD, SKYBLUE]
+    }
+
+    /// black drawn over the board with the given opacity. e-ink can't show
+    /// the greys that makes, so monochrome covers it completely.
+    pub fn shade(self, alpha: f32) -> Color {
+        if self.monochrome {
+            BLACK
+        } else {
+            Color::new(0.0, 0.0, 0.0, alpha)
+        }
+    }
+
+    /// greys out whatever is at `x`, `y`, the size of a card. monochrome
+    /// dithers it with black lines instead.
+    pub fn fade(self, x: f32, y: f32, w: f32, h: f32) {
+        if !self.monochrome {
+            draw_rectangle(x, y, w, h, Color::new(0.0, 0.0, 0.0, 0.5));
+            return;
+        }
+        let mut line = y;
+        while line < y + h {
+            draw_rectangle(x, line, w, 2.0, BLACK);
+            line += 4.0;
+        }
+    }
+
+    /// draws the suit big in the bottom half of a card at `x`, `y`, if asked
+    /// for
+    pub fn draw_suit_mark(self, suit: Suit, color: Color, x: f32, y: f32) {
+        if self.suit_marks == SuitMarks::Pips {
+            return;
+        }
+        // a backing so the mark stands out from the dots on red cards
+        draw_rectangle(x + 10.0, y + 30.0, 24.0, 28.0, BLACK);
+        let center = vec2(x + 22.0, y + 44.0);
+        match self.suit_marks {
+            SuitMarks::Pips => {}
+            SuitMarks::Letters => {
+                let letter = suit.letter().to_string();
+                let size = measure_text(&letter, None, 32, 1.0);
+                draw_text(
+                    &letter,
+                    center.x - size.width / 2.0,
+                    center.y + size.height / 2.0,
+                    32.0,
+                    color,
+                );
+            }
+            SuitMarks::Shapes => draw_suit_shape(suit, color, center, 1.0),
+        }
+    }
+
+    /// the suit's shape in the top right corner of a card at `x`, `y`, if
+    /// asked for. the rank and pip are on the left, so it's clear there.
+    pub fn draw_corner_badge(self, suit: Suit, color: Color, x: f32, y: f32) {
+        if !self.corner_badges {
+            return;
+        }
+        draw_rectangle(x + 28.0, y + 3.0, 13.0, 13.0, BLACK);
+        if self.monochrome {
+            draw_rectangle_lines(x + 28.0, y + 3.0, 13.0, 13.0, 1.0, WHITE);
+        }
+        draw_suit_shape(suit, color, vec2(x + 34.5, y + 9.5), 0.5);
+    }
+
+    /// the badge for a run of `len` cards ending in the card at `x`, `y`, in
+    /// its bottom right corner. single cards don't get one.
+    pub fn draw_run_badge(self, len: usize, x: f32, y: f32) {
+        if !self.run_badges || len < 2 {
+            return;
+        }
+        let (back, text) = if self.monochrome {
+            (BLACK, WHITE)
+        } else {
+            let back = match len {
+                2..=3 => Color::new(0.2, 0.6, 0.3, 1.0),
+                4..=6 => Color::new(0.85, 0.65, 0.1, 1.0),
+                _ => Color::new(0.85, 0.3, 0.15, 1.0),
+            };
+            (back, WHITE)
+        };
+        let label = len.to_string();
+        let width = measure_text(&label, None, 14, 1.0).width + 6.0;
+        let left = x + 44.0 - width - 2.0;
+        let top = y + 64.0 - 16.0;
+        draw_rectangle(left, top, width, 14.0, back);
+        if self.monochrome {
+            draw_rectangle_lines(left, top, width, 14.0, 1.0, WHITE);
+        }
+        draw_text(&label, left + 3.0, top + 11.0, 14.0, text);
+    }
+
+    /// the seconds left before a card decays, in the top right corner of
+    /// the card at `x`, `y`
+    pub fn draw_countdown(self, seconds: u32, x: f32, y: f32) {
+        let (back, text) = if self.monochrome {
+            (WHITE, BLACK)
+        } else {
+            (Color::new(0.8, 0.1, 0.1, 1.0), WHITE)
+        };
+        let label = seconds.to_string();
+        let width = measure_text(&label, None, 14, 1.0).width + 6.0;
+        let left = x + 44.0 - width - 2.0;
+        draw_rectangle(left, y + 2.0, width, 14.0, back);
+        draw_text(&label, left + 3.0, y + 13.0, 14.0, text);
+    }
+
+    /// a card as a block of its colour, for when they're too small to make
+    /// out. red ones are left hollow in monochrome.
+    pub fn draw_flat_card(self, card: BitCard, x: f32, y: f32, w: f32, h: f32) {
+        if card.is_red() && self.monochrome {
+            draw_rectangle(x, y, w, h, BLACK);
+            draw_rectangle_lines(x, y, w, h, 4.0, WHITE);
+        } else {
+            draw_rectangle(x, y, w, h, self.suit_color(card));
+            draw_rectangle_lines(x, y, w, h, 4.0, BLACK);
+        }
+    }
+
+    /// face down cards as a block, the same way
+    pub fn draw_flat_back(self, x: f32, y: f32, w: f32, h: f32) {
+        if self.monochrome {
+            draw_rectangle_lines(x, y, w, h, 4.0, WHITE);
+        } else {
+            draw_rectangle(x, y, w, h, Color::new(0.15, 0.25, 0.6, 1.0));
+        }
+    }
+
+    /// marks a red card without colour, with a checkerboard of dots below
+    /// the rank and suit
+    pub fn draw_red_pattern(self, x: f32, y: f32) {
+        if !self.monochrome {
+            return;
+        }
+        const STEP: f32 = 6.0;
+        for row in 0..6 {
+            let dot_y = y + 26.0 + STEP * row as f32;
+            let shift = if row % 2 == 0 { 0.0 } else { STEP / 2.0 };
+            for col in 0..5 {
+                let dot_x = x + 8.0 + shift + STEP * col as f32;
+                draw_rectangle(dot_x, dot_y, 2.0, 2.0, WHITE);
+            }
+        }
+    }
+}
+
+/// a circle for clubs, a diamond for diamonds, a triangle pointing down for
+/// hearts and one pointing up for spades, around `center`. at a `scale` of
+/// 1 it's about 18 across.
+fn draw_suit_shape(suit: Suit, color: Color, center: Vec2, scale: f32) {
+    match suit {
+        Suit::Club => draw_circle(center.x, center.y, 8.0 * scale, color),
+        Suit::Diamond => draw_poly(center.x, center.y, 4, 10.0 * scale, 0.0, color),
+        Suit::Heart => draw_triangle(
+            center + vec2(-9.0, -8.0) * scale,
+            center + vec2(9.0, -8.0) * scale,
+            center + vec2(0.0, 9.0) * scale,
+            color,
+        ),
+        Suit::Spade => draw_triangle(
+            center + vec2(-9.0, 8.0) * scale,
+            center + vec2(9.0, 8.0) * scale,
+            center + vec2(0.0, -9.0) * scale,
+            color,
+        ),
+    }
+}
@@ -0,0 +1,135 @@
+//! the columns of the board, kept in chunks of [`Tableau::CHUNK`] columns.
+//! only the chunks something has needed are dealt, so jumping a million
+//! columns ahead doesn't deal every column on the way there, and chunks far
+//! from anything can be let go of and dealt again from the seed later.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Index, IndexMut};
+
+use crate::cards::Column;
+
+#[derive(Clone, Default)]
+pub struct Tableau {
+    /// how many columns the board has, dealt or not
+    len: usize,
+    /// the chunks that have been dealt, by index. each one is always a full
+    /// `CHUNK` columns, even the ones that go past `len`.
+    chunks: HashMap<usize, Vec<Column>>,
+    /// the dealt chunks that might have changed since they were dealt or
+    /// last written out, for streamed games
+    dirty: BTreeSet<usize>,
+}
+
+impl Tableau {
+    /// how many columns are dealt at a time
+    pub const CHUNK: usize = 64;
+
+    /// how many columns the board has, including ones that haven't been
+    /// dealt
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// column `x`, if it's on the board and its chunk has been dealt
+    pub fn get(&self, x: usize) -> Option<&Column> {
+        if x >= self.len {
+            return None;
+        }
+        self.chunks
+            .get(&(x / Self::CHUNK))
+            .map(|chunk| &chunk[x % Self::CHUNK])
+    }
+
+    pub(crate) fn get_mut(&mut self, x: usize) -> Option<&mut Column> {
+        if x >= self.len {
+            return None;
+        }
+        self.slot(x)
+    }
+
+    /// column `x` of a dealt chunk, even past the end of the board
+    pub(crate) fn slot(&mut self, x: usize) -> Option<&mut Column> {
+        if self.chunks.contains_key(&(x / Self::CHUNK)) {
+            self.dirty.insert(x / Self::CHUNK);
+        }
+        self.chunks
+            .get_mut(&(x / Self::CHUNK))
+            .map(|chunk| &mut chunk[x % Self::CHUNK])
+    }
+
+    /// every column that's been dealt, in order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Column)> + '_ {
+        self.chunks().into_iter().flat_map(move |chunk| {
+            let start = chunk * Self::CHUNK;
+            let end = (start + Self::CHUNK).min(self.len);
+            (start..end).zip(&self.chunks[&chunk])
+        })
+    }
+
+    /// how many columns have been dealt and are in memory
+    pub fn dealt(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// the indices of the chunks that have been dealt, in order
+    pub(crate) fn chunks(&self) -> Vec<usize> {
+        let mut chunks: Vec<usize> = self.chunks.keys().copied().collect();
+        chunks.sort_unstable();
+        chunks
+    }
+
+    pub(crate) fn has_chunk(&self, chunk: usize) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    pub(crate) fn put_chunk(&mut self, chunk: usize, columns: Vec<Column>) {
+        debug_assert_eq!(columns.len(), Self::CHUNK);
+        self.chunks.insert(chunk, columns);
+    }
+
+    pub(crate) fn take_chunk(&mut self, chunk: usize) -> Option<Vec<Column>> {
+        self.dirty.remove(&chunk);
+        self.chunks.remove(&chunk)
+    }
+
+    /// the dealt chunks that might have changed, in order
+    pub(crate) fn dirty(&self) -> Vec<usize> {
+        self.dirty.iter().copied().collect()
+    }
+
+    pub(crate) fn is_dirty(&self, chunk: usize) -> bool {
+        self.dirty.contains(&chunk)
+    }
+
+    pub(crate) fn set_dirty(&mut self, chunk: usize, dirty: bool) {
+        if !dirty {
+            self.dirty.remove(&chunk);
+        } else if self.chunks.contains_key(&chunk) {
+            self.dirty.insert(chunk);
+        }
+    }
+
+    /// makes the board `len` columns long, without dealing anything
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+}
+
+impl Index<usize> for Tableau {
+    type Output = Column;
+
+    /// column `x`, which has to have been dealt
+    fn index(&self, x: usize) -> &Column {
+        self.get(x).expect("column hasn't been dealt")
+    }
+}
+
+impl IndexMut<usize> for Tableau {
+    fn index_mut(&mut self, x: usize) -> &mut Column {
+        self.get_mut(x).expect("column hasn't been dealt")
+    }
+}
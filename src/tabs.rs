@@ -0,0 +1,202 @@
+//! several games open at once, with a strip along the bottom of the window
+//! to switch between them
+
+use ::rand::{Rng, RngCore};
+use macroquad::prelude::*;
+
+use crate::input::{is_key_pressed, is_mouse_button_pressed, mouse_position};
+
+use crate::settings::Settings;
+use crate::{ctrl_down, State, NUMBER_KEYS};
+
+pub struct Tabs {
+    states: Vec<State>,
+    current: usize,
+    /// where new games get their seeds from
+    seeds: Box<dyn RngCore>,
+    /// the id the next tab opened gets
+    next_id: usize,
+}
+
+impl Tabs {
+    const STRIP_HEIGHT: f32 = 20.0;
+    const TAB_WIDTH: f32 = 96.0;
+    /// number keys only go up to 9
+    const MAX_TABS: usize = 9;
+
+    pub fn new(mut first: State, seeds: Box<dyn RngCore>) -> Self {
+        first.id = 0;
+        Self {
+            states: vec![first],
+            current: 0,
+            seeds,
+            next_id: 1,
+        }
+    }
+
+    /// a seed for a new game
+    pub fn random_seed(&mut self) -> u64 {
+        self.seeds.gen()
+    }
+
+    pub fn current(&mut self) -> &mut State {
+        &mut self.states[self.current]
+    }
+
+    /// gives the current tab `id`, for picking tabs back up with the ids
+    /// they had. tabs opened after it get later ones.
+    pub fn set_current_id(&mut self, id: usize) {
+        self.current().id = id;
+        self.next_id = self.next_id.max(id + 1);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &State)> {
+        self.states.iter().enumerate()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut State)> {
+        self.states.iter_mut().enumerate()
+    }
+
+    /// opens a fresh game and switches to it. returns false if there are
+    /// already as many tabs as there can be.
+    pub fn open(&mut self) -> bool {
+        if self.states.len() >= Self::MAX_TABS {
+            return false;
+        }
+        let settings = self.current().settings();
+        let mut state = State::new(settings, &mut *self.seeds);
+        if let Some(root) = self.current().stream.clone() {
+            state.stream_under(root);
+        }
+        state.id = self.next_id;
+        self.next_id += 1;
+        self.states.push(state);
+        self.switch(self.states.len() - 1);
+        true
+    }
+
+    /// replaces the current game with a new one dealt from `seed`. returns
+    /// the old game so its replay can be saved.
+    pub fn deal(&mut self, seed: u64) -> State {
+        let settings = Settings {
+            seed: Some(seed),
+            ..self.current().settings()
+        };
+        let mut new = State::new(settings, &mut *self.seeds);
+        if let Some(root) = self.current().stream.clone() {
+            new.stream_under(root);
+        }
+        // it's still the same tab
+        new.id = self.current().id;
+        let mut old = std::mem::replace(self.current(), new);
+        self.current().server = old.server.take();
+        self.current().publish(&[]);
+        old
+    }
+
+    /// closes the current tab, unless it's the last one. returns the closed
+    /// game so its replay can be saved.
+    pub fn close(&mut self) -> Option<State> {
+        if self.states.len() == 1 {
+            return None;
+        }
+        let mut closed = self.states.remove(self.current);
+        let next = self.current.min(self.states.len() - 1);
+        // the server follows whichever tab is on screen
+        self.states[next].server = closed.server.take();
+        self.current = next;
+        self.current().publish(&[]);
+        Some(closed)
+    }
+
+    pub fn switch(&mut self, idx: usize) {
+        if idx >= self.states.len() || idx == self.current {
+            return;
+        }
+        // cards in hand don't follow you to another game
+        let old = &mut self.states[self.current];
+        old.reset_column();
+        old.session.pause();
+        let server = old.server.take();
+        self.current = idx;
+        self.current().server = server;
+        self.current().publish(&[]);
+    }
+
+    /// handles the keys and clicks for switching tabs, returning true if
+    /// the click was used up by the strip
+    pub fn update(&mut self) -> bool {
+        let ctrl = ctrl_down();
+        if ctrl && is_key_pressed(KeyCode::T) {
+            self.open();
+        }
+        if ctrl && is_key_pressed(KeyCode::Tab) {
+            self.switch((self.current + 1) % self.states.len());
+        }
+        if ctrl {
+            for (idx, key) in NUMBER_KEYS.iter().enumerate() {
+                if is_key_pressed(*key) {
+                    self.switch(idx);
+                }
+            }
+        }
+        if self.states.len() == 1 || !is_mouse_button_pressed(MouseButton::Left) {
+            return false;
+        }
+        let (x, y) = mouse_position();
+        if y < screen_height() - Self::STRIP_HEIGHT {
+            return false;
+        }
+        self.switch((x / Self::TAB_WIDTH) as usize);
+        true
+    }
+
+    /// how much of the bottom of the window the strip takes up
+    pub fn height(&self) -> f32 {
+        if self.states.len() == 1 {
+            0.0
+        } else {
+            Self::STRIP_HEIGHT
+        }
+    }
+
+    pub fn draw(&self) {
+        if self.states.len() == 1 {
+            return;
+        }
+        let y = screen_height() - Self::STRIP_HEIGHT;
+        draw_rectangle(0.0, y, screen_width(), Self::STRIP_HEIGHT, DARKGRAY);
+        for (idx, state) in self.iter() {
+            let x = idx as f32 * Self::TAB_WIDTH;
+            if idx == self.current {
+                draw_rectangle(x, y, Self::TAB_WIDTH, Self::STRIP_HEIGHT, GRAY);
+            }
+            let label = format!("{}: {} up", idx + 1, state.game.founded());
+            draw_text(&label, x + 6.0, y + 15.0, 20.0, WHITE);
+        }
+    }
+}
+
+/// the path for the file of the tab with `id`, so the first tab keeps the
+/// plain name. ids stay the same when other tabs close, so a tab's files
+/// never turn into another's.
+pub fn tab_path(base: &str, id: usize, extension: &str) -> String {
+    if id == 0 {
+        format!("{}.{}", base, extension)
+    } else {
+        format!("{}-{}.{}", base, id + 1, extension)
+    }
+}
+
+/// the id of the tab `name` is the file of, if it's one `tab_path` would
+/// give for `base` and `extension`
+pub fn tab_id(base: &str, name: &str, extension: &str) -> Option<usize> {
+    let rest = name.strip_prefix(base)?.strip_suffix(extension)?;
+    let id = match rest.strip_suffix('.')?.strip_prefix('-') {
+        Some(number) => number.parse::<usize>().ok()?.checked_sub(1)?,
+        None => 0,
+    };
+    // anything else that happens to parse, like `-01`
+    (tab_path(base, id, extension) == name).then_some(id)
+}
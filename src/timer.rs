@@ -0,0 +1,122 @@
+//! the game clock in the corner, and the best times it's up against. every
+//! `MILESTONE` cards founded the time is written to the best times file,
+//! along with any new quickest stretch of cards in one sitting.
+
+use infiniteklondike::game::Game;
+use infiniteklondike::records::{self, Kind, Time, MILESTONE};
+use macroquad::prelude::*;
+
+use crate::stats::{clock, Session};
+use crate::toast;
+
+/// where best times are kept
+const BEST_TIMES_PATH: &str = "best-times.txt";
+
+pub struct Bests {
+    /// the best time on this seed to the next milestone
+    next: Option<f64>,
+    /// the quickest stretch of `MILESTONE` cards ever, on any seed
+    stretch: Option<f64>,
+    /// the session's quickest stretch, as it was last written down
+    saved: Option<f64>,
+}
+
+impl Bests {
+    /// looks up the times to beat in `game`
+    pub fn new(game: &Game) -> Self {
+        let variant = game.rules().key();
+        Self {
+            next: Self::to_next(game),
+            stretch: records::best(BEST_TIMES_PATH, Kind::Stretch, variant, MILESTONE),
+            saved: None,
+        }
+    }
+
+    fn to_next(game: &Game) -> Option<f64> {
+        let next = (game.founded() / MILESTONE + 1) * MILESTONE;
+        records::best(
+            BEST_TIMES_PATH,
+            Kind::Seed(game.seed()),
+            game.rules().key(),
+            next,
+        )
+    }
+
+    /// writes down the times a move made, given how many cards were on
+    /// foundations before it. the session's quickest stretch is written
+    /// down along with the milestones, whenever it got quicker. sandbox
+    /// games don't count, cards can be made up there.
+    pub fn update(&mut self, game: &Game, founded: usize, session: &Session) {
+        let rules = game.rules();
+        if rules.sandbox || game.founded() / MILESTONE <= founded / MILESTONE {
+            return;
+        }
+        let variant = rules.key();
+        let cards = game.founded() / MILESTONE * MILESTONE;
+        let time = Time {
+            kind: Kind::Seed(game.seed()),
+            variant,
+            cards,
+            seconds: session.elapsed(),
+        };
+        let quicker = |than: Option<f64>, seconds: f64| than.is_none_or(|than| seconds < than);
+        self.save(&time);
+        if quicker(self.next, time.seconds) {
+            toast::info(format!(
+                "{} cards in {}, a best for this seed",
+                cards,
+                clock(time.seconds)
+            ));
+        }
+        if let Some(seconds) = session
+            .fastest()
+            .filter(|&seconds| quicker(self.saved, seconds))
+        {
+            self.saved = Some(seconds);
+            self.save(&Time {
+                kind: Kind::Stretch,
+                variant,
+                cards: MILESTONE,
+                seconds,
+            });
+            if quicker(self.stretch, seconds) {
+                self.stretch = Some(seconds);
+                toast::info(format!(
+                    "{} cards in a row in {}, a new best",
+                    MILESTONE,
+                    clock(seconds)
+                ));
+            }
+        }
+        self.next = Self::to_next(game);
+    }
+
+    fn save(&self, time: &Time) {
+        if let Err(e) = records::record(BEST_TIMES_PATH, time) {
+            toast::error(format!("could not write to {}: {}", BEST_TIMES_PATH, e));
+        }
+    }
+
+    /// draws the clock and the times to beat, bottom left above the score
+    pub fn draw(&self, game: &Game, session: &Session) {
+        let next = (game.founded() / MILESTONE + 1) * MILESTONE;
+        let mut line = format!(
+            "time: {}{}",
+            clock(session.elapsed()),
+            if session.is_idle() { " (paused)" } else { "" }
+        );
+        if let Some(best) = self.next {
+            line += &format!(", best to {}: {}", next, clock(best));
+        }
+        let y = screen_height() - 96.0;
+        draw_text(&line, 8.0, y, 18.0, WHITE);
+        let fastest = [("this session", session.fastest()), ("ever", self.stretch)]
+            .into_iter()
+            .filter_map(|(when, time)| Some(format!("{} {}", when, clock(time?))))
+            .collect::<Vec<_>>();
+        if !fastest.is_empty() {
+            let line = format!("fastest {} cards: {}", MILESTONE, fastest.join(", "));
+            draw_text(&line, 8.0, y - 20.0, 18.0, WHITE);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! the title screen the game opens on, for setting up the first game before
+//! it's dealt: picking up the games from last time, dealing a new one (from
+//! a seed, if one's typed in), the rules it's played with, the challenges
+//! and the options screen. it's skipped when the command line already says
+//! what to play.
+
+use infiniteklondike::challenge::Period;
+use macroquad::prelude::*;
+
+use crate::cursor::Cursor;
+use crate::fullscreen;
+use crate::input::{self, is_key_pressed};
+use crate::options::{self, Page};
+use crate::screen::Screen;
+use crate::settings::Settings;
+use crate::ui::{Answer, Menu, Prompt};
+use crate::{config, toast, Asking, MenuAction};
+
+/// what was picked on the title screen
+pub enum Start {
+    /// deal a new game with the settings as they were left
+    New,
+    /// pick up the games that were open last time
+    Continue,
+    Quit,
+}
+
+/// the title menu, with `settings` as they are now
+fn menu(settings: &Settings, can_continue: bool) -> Menu<MenuAction> {
+    let seed = match settings.seed {
+        Some(seed) => format!("seed: {}", seed),
+        None => "seed: random".to_owned(),
+    };
+    let items: Vec<(&str, MenuAction)> = can_continue
+        .then_some(("continue", MenuAction::Continue))
+        .into_iter()
+        .chain([
+            ("new game", MenuAction::Resume),
+            (seed.as_str(), MenuAction::EnterSeed),
+            ("rules", MenuAction::OptionsPage(Page::Rules)),
+            ("daily challenge", MenuAction::Challenge(Period::Daily)),
+            ("weekly challenge", MenuAction::Challenge(Period::Weekly)),
+            ("options", MenuAction::Options),
+            ("quit", MenuAction::Quit),
+        ])
+        .collect();
+    Menu::new("infinite klondike", &items)
+}
+
+/// sets the seed the first game is dealt from to the one typed in, or back
+/// to a random one if nothing was
+fn set_seed(settings: &mut Settings, line: &str) {
+    if line.is_empty() {
+        settings.seed = None;
+        return;
+    }
+    match line.parse() {
+        Ok(seed) => settings.seed = Some(seed),
+        Err(_) => toast::error(format!("`{}` isn't a seed, seeds are whole numbers", line)),
+    }
+}
+
+/// shows the title screen until something's picked, setting up `settings`
+/// for the first game on the way. `can_continue` is whether there are games
+/// from last time to pick up.
+pub async fn run(settings: &mut Settings, can_continue: bool) -> Start {
+    let mut screen = Screen::Title(menu(settings, can_continue));
+    loop {
+        input::update();
+        fullscreen::update();
+        if is_quit_requested() {
+            return Start::Quit;
+        }
+        if let Screen::Prompt(_, prompt) = &mut screen {
+            if let Some(answer) = prompt.update() {
+                if let Answer::Typed(line) = answer {
+                    set_seed(settings, &line);
+                }
+                screen = Screen::Title(menu(settings, can_continue));
+            }
+        } else if let Some(open) = screen.menu() {
+            let picked = open.update();
+            if is_key_pressed(KeyCode::Escape) {
+                screen = Screen::Title(menu(settings, can_continue));
+            } else if let Some(action) = picked {
+                match action {
+                    MenuAction::Resume => return Start::New,
+                    MenuAction::Continue => return Start::Continue,
+                    MenuAction::Quit => return Start::Quit,
+                    MenuAction::Challenge(period) => {
+                        settings.challenge = Some(period);
+                        return Start::New;
+                    }
+                    MenuAction::EnterSeed => {
+                        let prompt = Prompt::new("seed", "empty for a random one");
+                        screen = Screen::Prompt(Asking::Seed, prompt);
+                    }
+                    MenuAction::Options => screen = Screen::Settings(options::menu()),
+                    MenuAction::OptionsPage(page) => {
+                        screen = Screen::Settings(options::page_menu(page, settings, None));
+                    }
+                    MenuAction::Change(page, choice) => {
+                        choice.change(settings);
+                        if let Err(e) = config::save(settings) {
+                            toast::error(format!("could not save settings: {}", e));
+                        }
+                        screen = Screen::Settings(options::page_menu(page, settings, Some(choice)));
+                    }
+                    _ => screen = Screen::Title(menu(settings, can_continue)),
+                }
+            }
+        }
+        clear_background(settings.style.table_color());
+        screen.draw();
+        toast::draw();
+        Cursor::Arrow.draw(settings.style);
+        next_frame().await;
+    }
+}
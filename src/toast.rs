@@ -0,0 +1,78 @@
+//! little messages that pop up in the top right corner for a few seconds,
+//! for things worth knowing that don't need an answer: saves, milestones,
+//! and anything that went wrong. they go to the log as well.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+struct Toast {
+    text: String,
+    error: bool,
+    /// when it was first shown, from `get_time`
+    shown: f64,
+}
+
+thread_local! {
+    static TOASTS: RefCell<VecDeque<Toast>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// how long a toast stays up, in seconds, counting the fade
+const SHOW_FOR: f64 = 4.0;
+/// how long the fade out at the end takes
+const FADE: f64 = 1.0;
+/// older toasts are dropped early past this many
+const MAX_SHOWN: usize = 5;
+const WIDTH: f32 = 300.0;
+const HEIGHT: f32 = 28.0;
+
+fn push(text: String, error: bool) {
+    TOASTS.with(|toasts| {
+        let mut toasts = toasts.borrow_mut();
+        toasts.push_back(Toast {
+            text,
+            error,
+            shown: get_time(),
+        });
+        while toasts.len() > MAX_SHOWN {
+            toasts.pop_front();
+        }
+    });
+}
+
+pub fn info(text: impl Into<String>) {
+    let text = text.into();
+    info!("{}", text);
+    push(text, false);
+}
+
+/// something went wrong, shown in red
+pub fn error(text: impl Into<String>) {
+    let text = text.into();
+    warn!("{}", text);
+    push(text, true);
+}
+
+/// draws the toasts, newest at the top, and forgets the ones that are done
+pub fn draw() {
+    let now = get_time();
+    TOASTS.with(|toasts| {
+        let mut toasts = toasts.borrow_mut();
+        toasts.retain(|toast| now - toast.shown < SHOW_FOR);
+        let x = screen_width() - WIDTH - 8.0;
+        for (n, toast) in toasts.iter().rev().enumerate() {
+            let alpha = ((SHOW_FOR - (now - toast.shown)) / FADE).min(1.0) as f32;
+            let y = 32.0 + (HEIGHT + 4.0) * n as f32;
+            draw_rectangle(x, y, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.85 * alpha));
+            let color = if toast.error { RED } else { WHITE };
+            draw_text(
+                &toast.text,
+                x + 8.0,
+                y + 19.0,
+                18.0,
+                Color { a: alpha, ..color },
+            );
+        }
+    });
+}
@@ -0,0 +1,96 @@
+//! cards sliding into place instead of jumping there. every card that moves
+//! remembers how far it was from where it landed, and that shrinks to
+//! nothing over a moment. it's kept by where the card is now rather than
+//! where it is on screen, so the slide goes along with the board if it
+//! scrolls, and a card that moves again before it's got there carries on
+//! from wherever it had got to. it goes by the clock, not frames, so it
+//! takes as long however fast the game's drawing.
+
+use infiniteklondike::cards::BitCard;
+use macroquad::prelude::*;
+
+use crate::hints::Spot;
+
+/// a card on its way to `spot`
+#[derive(Debug, Clone, Copy)]
+struct Slide {
+    spot: Spot,
+    /// how far from `spot` it started, in layout coordinates
+    from: Vec2,
+    /// when it started, from `get_time`
+    started: f64,
+    /// what shows at `spot` until the card gets there, for foundations
+    covers: Option<BitCard>,
+}
+
+#[derive(Debug, Default)]
+pub struct Tweens {
+    /// in the order they started, which is the order they're drawn in
+    slides: Vec<Slide>,
+}
+
+impl Tweens {
+    /// how long a slide takes, in seconds
+    pub const LENGTH: f64 = 0.18;
+
+    /// slides the card that's now at `spot`, which is at `to`, in from `from`.
+    /// `covers` is drawn at the spot until it gets there.
+    pub fn slide(&mut self, spot: Spot, from: Vec2, to: Vec2, covers: Option<BitCard>) {
+        let now = get_time();
+        self.slides
+            .retain(|slide| slide.spot != spot && now - slide.started < Self::LENGTH);
+        if from != to {
+            self.slides.push(Slide {
+                spot,
+                from: from - to,
+                started: now,
+                covers,
+            });
+        }
+    }
+
+    /// puts whatever was on its way to `spot` straight there
+    pub fn stop(&mut self, spot: Spot) {
+        self.slides.retain(|slide| slide.spot != spot);
+    }
+
+    pub fn clear(&mut self) {
+        self.slides.clear();
+    }
+
+    fn find(&self, spot: Spot) -> Option<&Slide> {
+        let now = get_time();
+        self.slides
+            .iter()
+            .find(|slide| slide.spot == spot && now - slide.started < Self::LENGTH)
+    }
+
+    /// how far from `spot` the card there is drawn right now
+    pub fn offset(&self, spot: Spot) -> Vec2 {
+        self.find(spot).map_or(Vec2::ZERO, Self::left)
+    }
+
+    pub fn is_sliding(&self, spot: Spot) -> bool {
+        self.find(spot).is_some()
+    }
+
+    /// what to draw at `spot` while a card's on its way there, if it is
+    pub fn covers(&self, spot: Spot) -> Option<Option<BitCard>> {
+        self.find(spot).map(|slide| slide.covers)
+    }
+
+    /// the cards on their way, with how far they've still to go
+    pub fn sliding(&self) -> impl Iterator<Item = (Spot, Vec2)> + '_ {
+        let now = get_time();
+        self.slides
+            .iter()
+            .filter(move |slide| now - slide.started < Self::LENGTH)
+            .map(|slide| (slide.spot, Self::left(slide)))
+    }
+
+    /// how far `slide` still has to go, slowing down as it gets there
+    fn left(slide: &Slide) -> Vec2 {
+        let done = ((get_time() - slide.started) / Self::LENGTH).clamp(0.0, 1.0) as f32;
+        slide.from * (1.0 - done).powi(3)
+    }
+}
@@ -0,0 +1,267 @@
+//! menus drawn over the board. everything in them can be reached with tab
+//! or the arrow keys and picked with enter, as well as with the mouse.
+
+use macroquad::prelude::*;
+
+use crate::fullscreen;
+use crate::input::{
+    get_char_pressed, is_key_down, is_key_pressed, is_mouse_button_pressed, mouse_position,
+};
+
+/// a list of buttons in the middle of the screen
+pub struct Menu<T> {
+    title: String,
+    items: Vec<(String, T)>,
+    /// the item the keyboard is on
+    focus: usize,
+    /// where the mouse was last frame
+    mouse: Vec2,
+}
+
+impl<T: Copy> Menu<T> {
+    const WIDTH: f32 = 240.0;
+    const ITEM_HEIGHT: f32 = 32.0;
+    const TITLE_HEIGHT: f32 = 40.0;
+
+    pub fn new(title: &str, items: &[(&str, T)]) -> Self {
+        Self {
+            title: title.to_owned(),
+            items: items
+                .iter()
+                .map(|(label, action)| (label.to_string(), *action))
+                .collect(),
+            focus: 0,
+            mouse: mouse_position().into(),
+        }
+    }
+
+    /// puts the keyboard on item `idx`
+    pub fn set_focus(&mut self, idx: usize) {
+        self.focus = idx.min(self.items.len() - 1);
+    }
+
+    fn item_rect(&self, idx: usize) -> Rect {
+        let height = Self::TITLE_HEIGHT + Self::ITEM_HEIGHT * self.items.len() as f32;
+        let top = (screen_height() - height) / 2.0;
+        Rect::new(
+            (screen_width() - Self::WIDTH) / 2.0,
+            top + Self::TITLE_HEIGHT + Self::ITEM_HEIGHT * idx as f32,
+            Self::WIDTH,
+            Self::ITEM_HEIGHT - 4.0,
+        )
+    }
+
+    fn item_under_mouse(&self) -> Option<usize> {
+        let mouse = mouse_position().into();
+        (0..self.items.len()).find(|idx| self.item_rect(*idx).contains(mouse))
+    }
+
+    /// handles input, returning what was picked if anything was
+    pub fn update(&mut self) -> Option<T> {
+        let picked = self.handle_input();
+        self.mouse = mouse_position().into();
+        picked
+    }
+
+    fn handle_input(&mut self) -> Option<T> {
+        let len = self.items.len();
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        if is_key_pressed(KeyCode::Down) || (is_key_pressed(KeyCode::Tab) && !shift) {
+            self.focus = (self.focus + 1) % len;
+        }
+        if is_key_pressed(KeyCode::Up) || (is_key_pressed(KeyCode::Tab) && shift) {
+            self.focus = (self.focus + len - 1) % len;
+        }
+        let enter = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter);
+        if (enter && !fullscreen::is_shortcut_pressed()) || is_key_pressed(KeyCode::Space) {
+            return Some(self.items[self.focus].1);
+        }
+        if let Some(idx) = self.item_under_mouse() {
+            // only follow the mouse when it moves, so it doesn't fight the keyboard
+            if Vec2::from(mouse_position()) != self.mouse {
+                self.focus = idx;
+            }
+            if is_mouse_button_pressed(MouseButton::Left) {
+                return Some(self.items[idx].1);
+            }
+        }
+        None
+    }
+
+    pub fn draw(&self) {
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        let first = self.item_rect(0);
+        draw_text(
+            &self.title,
+            first.x,
+            first.y - Self::TITLE_HEIGHT / 2.0,
+            32.0,
+            WHITE,
+        );
+        for (idx, (label, _)) in self.items.iter().enumerate() {
+            let rect = self.item_rect(idx);
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, DARKGRAY);
+            if idx == self.focus {
+                // the focus ring sits just outside the button
+                draw_rectangle_lines(
+                    rect.x - 3.0,
+                    rect.y - 3.0,
+                    rect.w + 6.0,
+                    rect.h + 6.0,
+                    2.0,
+                    YELLOW,
+                );
+            }
+            draw_text(label, rect.x + 10.0, rect.y + rect.h - 8.0, 24.0, WHITE);
+        }
+    }
+}
+
+/// what came of a `Prompt`
+pub enum Answer {
+    Typed(String),
+    Cancelled,
+}
+
+/// a line of text to type in, in the middle of the screen
+pub struct Prompt {
+    title: String,
+    /// shown under the line, for anything worth knowing while typing
+    note: String,
+    line: String,
+}
+
+impl Prompt {
+    const WIDTH: f32 = 360.0;
+    const HEIGHT: f32 = 112.0;
+    const MAX_LEN: usize = 32;
+
+    pub fn new(title: &str, note: &str) -> Self {
+        // whatever was typed to get here shouldn't end up in the line
+        while get_char_pressed().is_some() {}
+        Self {
+            title: title.to_owned(),
+            note: note.to_owned(),
+            line: String::new(),
+        }
+    }
+
+    /// handles input, returning the answer once enter or escape is pressed
+    pub fn update(&mut self) -> Option<Answer> {
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && self.line.len() < Self::MAX_LEN {
+                self.line.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(Answer::Cancelled);
+        }
+        let enter = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter);
+        if enter && !fullscreen::is_shortcut_pressed() {
+            return Some(Answer::Typed(self.line.trim().to_owned()));
+        }
+        None
+    }
+
+    pub fn draw(&self) {
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        let x = (screen_width() - Self::WIDTH) / 2.0;
+        let y = (screen_height() - Self::HEIGHT) / 2.0;
+        draw_rectangle(x, y, Self::WIDTH, Self::HEIGHT, DARKGRAY);
+        draw_text(&self.title, x + 10.0, y + 30.0, 32.0, WHITE);
+        draw_text(&format!("{}_", self.line), x + 10.0, y + 64.0, 24.0, WHITE);
+        draw_text(&self.note, x + 10.0, y + 96.0, 18.0, LIGHTGRAY);
+    }
+}
+
+/// a ring of actions around where it was opened, picked by pointing the
+/// mouse in a direction instead of aiming at a button. it's held open with
+/// a key, and letting go picks whatever is pointed at.
+pub struct Radial<T> {
+    items: Vec<(String, T)>,
+    center: Vec2,
+}
+
+impl<T: Copy> Radial<T> {
+    /// how far the labels are from the middle
+    const RADIUS: f32 = 100.0;
+    /// how far the mouse has to move from the middle to point at anything
+    const DEAD_ZONE: f32 = 24.0;
+
+    /// opens around the mouse, with the first item straight up and the
+    /// rest going round clockwise
+    pub fn new(items: &[(&str, T)]) -> Self {
+        Self {
+            items: items
+                .iter()
+                .map(|(label, action)| (label.to_string(), *action))
+                .collect(),
+            center: mouse_position().into(),
+        }
+    }
+
+    /// which way item `idx` is from the middle
+    fn direction(&self, idx: usize) -> Vec2 {
+        let angle = std::f32::consts::TAU * idx as f32 / self.items.len() as f32;
+        vec2(angle.sin(), -angle.cos())
+    }
+
+    fn pointed_at(&self) -> Option<usize> {
+        let towards = Vec2::from(mouse_position()) - self.center;
+        if towards.length() < Self::DEAD_ZONE {
+            return None;
+        }
+        // the item whose direction is closest to the pointing
+        (0..self.items.len()).max_by(|a, b| {
+            let a = self.direction(*a).dot(towards);
+            let b = self.direction(*b).dot(towards);
+            a.total_cmp(&b)
+        })
+    }
+
+    /// what letting go now would pick
+    pub fn picked(&self) -> Option<T> {
+        self.pointed_at().map(|idx| self.items[idx].1)
+    }
+
+    pub fn draw(&self) {
+        draw_circle(
+            self.center.x,
+            self.center.y,
+            Self::RADIUS + 40.0,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        draw_circle_lines(self.center.x, self.center.y, Self::DEAD_ZONE, 2.0, GRAY);
+        let pointed = self.pointed_at();
+        for (idx, (label, _)) in self.items.iter().enumerate() {
+            let size = measure_text(label, None, 20, 1.0);
+            let at = self.center + self.direction(idx) * Self::RADIUS;
+            let rect = Rect::new(
+                at.x - size.width / 2.0 - 8.0,
+                at.y - 14.0,
+                size.width + 16.0,
+                28.0,
+            );
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, DARKGRAY);
+            if pointed == Some(idx) {
+                draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, YELLOW);
+            }
+            draw_text(label, rect.x + 8.0, rect.y + 20.0, 20.0, WHITE);
+        }
+    }
+}
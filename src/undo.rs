@@ -0,0 +1,74 @@
+//! taking moves back with ctrl+z. each step is one move made by hand along
+//! with everything the game did by itself because of it, so an auto chain
+//! set off by a move, an auto finish or a macro played back all go back in
+//! one go, and so does everything the bot did since it was turned on.
+
+use infiniteklondike::game::Snapshot;
+
+/// the board from before a step, and what went with it
+pub struct Step {
+    pub board: Snapshot,
+    /// how many moves had been made
+    pub moves: usize,
+    pub score: i64,
+}
+
+#[derive(Default)]
+pub struct Undo {
+    /// the newest last
+    steps: Vec<Step>,
+    /// while set, moves made by hand go in the step already started instead
+    /// of starting their own, see [`Undo::group`]
+    grouped: bool,
+}
+
+impl Undo {
+    /// how many steps are kept, the oldest are let go of after that
+    const STEPS: usize = 50;
+
+    /// whether a move made by hand should start a step of its own
+    pub fn is_grouped(&self) -> bool {
+        self.grouped
+    }
+
+    /// starts a step with the board as it is before it
+    pub fn push(&mut self, step: Step) {
+        if self.steps.len() >= Self::STEPS {
+            self.steps.remove(0);
+        }
+        self.steps.push(step);
+    }
+
+    /// starts a step that every move goes in until [`Undo::ungroup`], for
+    /// actions that make a lot of moves at once
+    pub fn group(&mut self, step: Step) {
+        self.push(step);
+        self.grouped = true;
+    }
+
+    /// ends the step started by [`Undo::group`], dropping it if no moves
+    /// went in it after all
+    pub fn ungroup(&mut self, moves: usize) {
+        self.grouped = false;
+        self.drop_empty(moves);
+    }
+
+    /// the last step with moves in it, now that `moves` have been made.
+    /// steps started but left empty are skipped.
+    pub fn pop(&mut self, moves: usize) -> Option<Step> {
+        self.drop_empty(moves);
+        self.steps.pop()
+    }
+
+    fn drop_empty(&mut self, moves: usize) {
+        while self.steps.last().is_some_and(|step| step.moves >= moves) {
+            self.steps.pop();
+        }
+    }
+
+    /// forgets every step, for when the board is replaced
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.grouped = false;
+    }
+}
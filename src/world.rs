@@ -0,0 +1,153 @@
+//! streaming a board to disk as it's played, for explorations too long to
+//! keep in memory. a streamed game keeps a directory with a file for every
+//! chunk of the tableau (see [`crate::tableau::Tableau::CHUNK`]) that has
+//! changed columns in it. a chunk is written when it's let go of with
+//! [`crate::game::Game::forget_outside`] or flushed with
+//! [`crate::game::Game::flush`], but only if something changed since it was
+//! last written, and binary saves of the game only name the chunks that are
+//! in the directory instead of holding them.
+//!
+//! the directory only ever has the newest copy of each chunk, so it goes
+//! with the newest save of the game. an older save loads those chunks as
+//! they are now.
+//!
+//! a chunk file looks like this:
+//!
+//! ```text
+//! "IKCK"              magic
+//! u16                 format version, little endian
+//! varint              chunk index
+//! varint              how many changed columns there are
+//! ```
+//!
+//! then for each changed column: how far into the chunk it is (u8), face
+//! down count (varint), face up count (u8), the face up cards and then their
+//! ids (varints). version 2 goes on with how many of its face down cards
+//! decayed (varint), then each of those cards and its id, the first to decay
+//! first. version 1 chunks are still written when nothing decayed.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cards::{CardStack, Column};
+use crate::save::{write_varint, Reader, SaveError};
+use crate::tableau::Tableau;
+
+const MAGIC: &[u8; 4] = b"IKCK";
+const VERSION: u16 = 2;
+
+/// the directory a streamed game keeps its chunks in
+#[derive(Debug, Clone)]
+pub struct World {
+    dir: PathBuf,
+}
+
+impl World {
+    /// streams into `dir`, making it if it isn't there
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, chunk: usize) -> PathBuf {
+        self.dir.join(format!("chunk-{}", chunk))
+    }
+
+    /// writes the changed `columns` of chunk `chunk`, or removes its file
+    /// when there aren't any
+    pub(crate) fn write(&self, chunk: usize, columns: &[(usize, &Column)]) -> io::Result<()> {
+        let path = self.path(chunk);
+        if columns.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        // only decay games need the newer version
+        let decayed = (columns.iter()).any(|(_, column)| !column.turned_under().is_empty());
+        let version = if decayed { VERSION } else { 1 };
+        let mut out = Vec::new();
+        out.extend(MAGIC);
+        out.extend(version.to_le_bytes());
+        write_varint(&mut out, chunk as u64);
+        write_varint(&mut out, columns.len() as u64);
+        for (x, column) in columns {
+            out.push((x - chunk * Tableau::CHUNK) as u8);
+            write_varint(&mut out, column.under.into());
+            out.push(column.visible().len());
+            out.extend(column.visible().iter().map(|card| card.bits()));
+            for id in column.visible().ids() {
+                write_varint(&mut out, id.bits());
+            }
+            if decayed {
+                write_varint(&mut out, column.turned_under().len() as u64);
+                for (card, id) in column.turned_under() {
+                    out.push(card.bits());
+                    write_varint(&mut out, id.bits());
+                }
+            }
+        }
+        // written next to it first, so a crash can't leave half a chunk
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, out)?;
+        std::fs::rename(partial, path)
+    }
+
+    /// the changed columns of chunk `chunk`, by where they are on the board
+    pub(crate) fn read(&self, chunk: usize) -> Result<BTreeMap<usize, Column>, SaveError> {
+        let bytes = std::fs::read(self.path(chunk))?;
+        let mut reader = Reader(&bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(SaveError::Corrupt("not a chunk of a streamed board"));
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version > VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+        if reader.varint()? != chunk as u64 {
+            return Err(SaveError::Corrupt(
+                "chunk is from somewhere else on the board",
+            ));
+        }
+        let mut columns = BTreeMap::new();
+        for _ in 0..reader.varint()? {
+            let offset = reader.byte()? as usize;
+            if offset >= Tableau::CHUNK {
+                return Err(SaveError::Corrupt("column is past the end of the chunk"));
+            }
+            let under = reader
+                .varint()?
+                .try_into()
+                .map_err(|_| SaveError::Corrupt("too many face down cards"))?;
+            let len = reader.byte()?;
+            if len > 13 {
+                return Err(SaveError::Corrupt("too many face up cards"));
+            }
+            let cards = (0..len)
+                .map(|_| reader.card())
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut visible = CardStack::empty();
+            for card in cards {
+                visible.push(card, reader.id()?);
+            }
+            let mut column = Column::from_parts(under, visible);
+            if version >= 2 {
+                let turned_under = (0..reader.varint()?)
+                    .map(|_| Ok((reader.card()?, reader.id()?)))
+                    .collect::<Result<_, SaveError>>()?;
+                column = column
+                    .with_turned_under(turned_under)
+                    .map_err(SaveError::Corrupt)?;
+            }
+            let x = chunk * Tableau::CHUNK + offset;
+            columns.insert(x, column);
+        }
+        Ok(columns)
+    }
+}
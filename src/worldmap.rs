@@ -0,0 +1,179 @@
+//! the world map, a screen of its own with a tile for every region dealt so
+//! far. shift+n opens it. each tile fills up and turns greener the more of
+//! the region's cards are on foundations, with a pip for every `MILESTONE`
+//! of them. bookmarks show as their numbers, the view is boxed in yellow
+//! and the furthest region played in is flagged. clicking a tile goes
+//! there, and escape or shift+n again closes the map.
+
+use infiniteklondike::game::Game;
+use infiniteklondike::records::MILESTONE;
+use macroquad::prelude::*;
+
+use crate::input::mouse_position;
+use crate::style::Style;
+
+/// what the map shows about one region
+pub struct Tile {
+    pub region: usize,
+    pub founded: u32,
+    pub dealt: u64,
+    /// whether the view is in this region
+    pub here: bool,
+    /// whether this is the furthest region played in
+    pub furthest: bool,
+    /// the numbers of the bookmarks in this region, from 1
+    pub bookmarks: Vec<usize>,
+}
+
+/// the widest a tile gets, in window pixels
+const TILE_WIDTH: f32 = 160.0;
+/// room around the tiles for the title
+const MARGIN: f32 = 48.0;
+
+/// a tile for every region up to the furthest one played in or looked at.
+/// `here` is the first column on screen and `bookmarks` the first column of
+/// each bookmark.
+pub fn summarize(game: &Game, here: usize, bookmarks: &[usize]) -> Vec<Tile> {
+    let here = game.region_of(here);
+    let furthest = game.region_of(game.explored().saturating_sub(1));
+    let bookmarks: Vec<usize> = bookmarks
+        .iter()
+        .map(|column| game.region_of(*column))
+        .collect();
+    let last = bookmarks
+        .iter()
+        .copied()
+        .fold(here.max(furthest), usize::max);
+    (0..=last)
+        .map(|region| Tile {
+            region,
+            founded: game.region_founded(region),
+            dealt: game.region_dealt(region),
+            here: region == here,
+            furthest: region == furthest,
+            bookmarks: (1..)
+                .zip(&bookmarks)
+                .filter(|(_, mark)| **mark == region)
+                .map(|(n, _)| n)
+                .collect(),
+        })
+        .collect()
+}
+
+/// the first column of `region`, counting the columns inserted before it
+pub fn first_column(game: &Game, region: usize) -> usize {
+    let mut column = region * Game::REGION_COLUMNS;
+    while game.region_of(column) < region {
+        column += 1;
+    }
+    column
+}
+
+/// where each of `count` tiles goes, as many to a row as keeps them all on
+/// the screen
+fn rects(count: usize) -> impl Iterator<Item = Rect> {
+    let width = screen_width() - 2.0 * MARGIN;
+    let height = screen_height() - 2.0 * MARGIN;
+    let mut across = 1;
+    let size = loop {
+        let w = (width / across as f32).min(TILE_WIDTH);
+        let rows = count.div_ceil(across);
+        if rows as f32 * w * 2.0 / 3.0 <= height || across >= count {
+            break w;
+        }
+        across += 1;
+    };
+    (0..count).map(move |idx| {
+        Rect::new(
+            MARGIN + (idx % across) as f32 * size,
+            MARGIN + (idx / across) as f32 * size * 2.0 / 3.0,
+            size,
+            size * 2.0 / 3.0,
+        )
+    })
+}
+
+/// the region of the tile under the mouse, if any. `count` is how many
+/// tiles there are.
+pub fn region_at(count: usize) -> Option<usize> {
+    let mouse = Vec2::from(mouse_position());
+    rects(count).position(|rect| rect.contains(mouse))
+}
+
+/// draws the map over the whole window
+pub fn draw(tiles: &[Tile], style: Style) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), style.shade(0.92));
+    let founded: u32 = tiles.iter().map(|tile| tile.founded).sum();
+    let title = format!(
+        "{} regions, {} cards founded   click one to go there, esc to close",
+        tiles.len(),
+        founded
+    );
+    draw_text(&title, MARGIN, MARGIN - 16.0, 20.0, WHITE);
+    for (tile, rect) in tiles.iter().zip(rects(tiles.len())) {
+        let done = tile.founded as f32 / tile.dealt.max(1) as f32;
+        let (back, fill) = if style.monochrome {
+            (BLACK, WHITE)
+        } else {
+            (
+                Color::new(0.15, 0.2, 0.35, 1.0),
+                Color::new(0.2, 0.4 + 0.4 * done, 0.3, 1.0),
+            )
+        };
+        let inner = Rect::new(rect.x + 2.0, rect.y + 2.0, rect.w - 4.0, rect.h - 4.0);
+        draw_rectangle(inner.x, inner.y, inner.w, inner.h, back);
+        let filled = inner.h * done;
+        draw_rectangle(inner.x, inner.bottom() - filled, inner.w, filled, fill);
+        draw_rectangle_lines(inner.x, inner.y, inner.w, inner.h, 1.0, GRAY);
+        if tile.here {
+            let color = if style.monochrome { WHITE } else { YELLOW };
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 3.0, color);
+        }
+        // monochrome labels have to stand out against the white fill too
+        let text = |covered: bool| {
+            if style.monochrome && covered {
+                BLACK
+            } else {
+                WHITE
+            }
+        };
+        let (label, pip) = (text(filled > inner.h - 32.0), text(filled > 10.0));
+        draw_text(
+            &format!("{}", tile.region + 1),
+            inner.x + 4.0,
+            inner.y + 14.0,
+            16.0,
+            label,
+        );
+        if tile.furthest {
+            draw_text("furthest", inner.x + 4.0, inner.y + 28.0, 14.0, label);
+        }
+        // a pip for every `MILESTONE` cards founded from here
+        let pips = tile.founded as usize / MILESTONE;
+        for n in 0..pips.min(((inner.w - 8.0) / 6.0) as usize) {
+            draw_circle(
+                inner.x + 6.0 + n as f32 * 6.0,
+                inner.bottom() - 6.0,
+                2.0,
+                pip,
+            );
+        }
+        if !tile.bookmarks.is_empty() {
+            let marks = tile
+                .bookmarks
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let width = measure_text(&marks, None, 16, 1.0).width;
+            let color = if style.monochrome { label } else { SKYBLUE };
+            draw_text(
+                &marks,
+                inner.right() - width - 4.0,
+                inner.y + 14.0,
+                16.0,
+                color,
+            );
+        }
+    }
+}